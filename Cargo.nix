@@ -85,6 +85,49 @@ rec {
     #   inject test dependencies into the build
 
     crates = {
+      "ab_glyph" = rec {
+        crateName = "ab_glyph";
+        version = "0.2.32";
+        edition = "2021";
+        sha256 = "1hkc7y8yjd261d5cm9771dawnwc26rgdlniv3jysb3n3f9s4bh01";
+        authors = [
+          "Alex Butler <alexheretic@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "ab_glyph_rasterizer";
+            packageId = "ab_glyph_rasterizer";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "owned_ttf_parser";
+            packageId = "owned_ttf_parser";
+            usesDefaultFeatures = false;
+          }
+        ];
+        features = {
+          "default" = [ "std" "variable-fonts" "gvar-alloc" ];
+          "gvar-alloc" = [ "owned_ttf_parser/gvar-alloc" ];
+          "libm" = [ "dep:libm" "ab_glyph_rasterizer/libm" "owned_ttf_parser/no-std-float" ];
+          "std" = [ "owned_ttf_parser/default" "ab_glyph_rasterizer/default" ];
+          "variable-fonts" = [ "owned_ttf_parser/variable-fonts" ];
+        };
+        resolvedDefaultFeatures = [ "default" "gvar-alloc" "std" "variable-fonts" ];
+      };
+      "ab_glyph_rasterizer" = rec {
+        crateName = "ab_glyph_rasterizer";
+        version = "0.1.10";
+        edition = "2021";
+        sha256 = "065n6bj7kqk6f12336lm87fqmvf4lxg7rkg2j56nix228jmgnvrn";
+        authors = [
+          "Alex Butler <alexheretic@gmail.com>"
+        ];
+        features = {
+          "default" = [ "std" ];
+          "libm" = [ "dep:libm" ];
+        };
+        resolvedDefaultFeatures = [ "default" "std" ];
+      };
       "adler2" = rec {
         crateName = "adler2";
         version = "2.0.1";
@@ -267,6 +310,16 @@ rec {
           }
         ];
 
+      };
+      "arrayref" = rec {
+        crateName = "arrayref";
+        version = "0.3.9";
+        edition = "2015";
+        sha256 = "1jzyp0nvp10dmahaq9a2rnxqdd5wxgbvp8xaibps3zai8c9fi8kn";
+        authors = [
+          "David Roundy <roundyd@physics.oregonstate.edu>"
+        ];
+
       };
       "arrayvec" = rec {
         crateName = "arrayvec";
@@ -757,6 +810,7 @@ rec {
           "latest_stable_rust" = [ "aarch64_simd" "avx512_simd" "align_offset" "alloc_uninit" "const_zeroed" "derive" "impl_core_error" "min_const_generics" "must_cast" "must_cast_extra" "pod_saturating" "track_caller" "transparentwrapper_extra" "wasm_simd" "zeroable_atomics" "zeroable_maybe_uninit" "zeroable_unwind_fn" ];
           "must_cast_extra" = [ "must_cast" ];
         };
+        resolvedDefaultFeatures = [ "aarch64_simd" ];
       };
       "byteorder" = rec {
         crateName = "byteorder";
@@ -1920,6 +1974,13 @@ rec {
             packageId = "byteorder";
             optional = true;
           }
+          {
+            name = "chrono";
+            packageId = "chrono";
+            optional = true;
+            usesDefaultFeatures = false;
+            features = [ "clock" "std" ];
+          }
           {
             name = "diesel_derives";
             packageId = "diesel_derives";
@@ -2005,7 +2066,7 @@ rec {
           "with-deprecated" = [ "diesel_derives/with-deprecated" ];
           "without-deprecated" = [ "diesel_derives/without-deprecated" ];
         };
-        resolvedDefaultFeatures = [ "32-column-tables" "default" "postgres" "postgres_backend" "r2d2" "sqlite" "uuid" "with-deprecated" ];
+        resolvedDefaultFeatures = [ "32-column-tables" "chrono" "default" "postgres" "postgres_backend" "r2d2" "sqlite" "uuid" "with-deprecated" ];
       };
       "diesel_derives" = rec {
         crateName = "diesel_derives";
@@ -2041,7 +2102,7 @@ rec {
           "64-column-tables" = [ "32-column-tables" ];
           "nightly" = [ "proc-macro2/nightly" ];
         };
-        resolvedDefaultFeatures = [ "32-column-tables" "default" "postgres" "r2d2" "sqlite" "with-deprecated" ];
+        resolvedDefaultFeatures = [ "32-column-tables" "chrono" "default" "postgres" "r2d2" "sqlite" "with-deprecated" ];
       };
       "diesel_table_macro_syntax" = rec {
         crateName = "diesel_table_macro_syntax";
@@ -2362,6 +2423,22 @@ rec {
         };
         resolvedDefaultFeatures = [ "alloc" "default" "std" ];
       };
+      "fdeflate" = rec {
+        crateName = "fdeflate";
+        version = "0.3.7";
+        edition = "2021";
+        sha256 = "130ga18vyxbb5idbgi07njymdaavvk6j08yh1dfarm294ssm6s0y";
+        authors = [
+          "The image-rs Developers"
+        ];
+        dependencies = [
+          {
+            name = "simd-adler32";
+            packageId = "simd-adler32";
+          }
+        ];
+
+      };
       "find-msvc-tools" = rec {
         crateName = "find-msvc-tools";
         version = "0.1.4";
@@ -4653,29 +4730,6 @@ rec {
         };
         resolvedDefaultFeatures = [ "alloc" "default" "std" ];
       };
-      "meval" = rec {
-        crateName = "meval";
-        version = "0.2.0";
-        edition = "2015";
-        sha256 = "1ncj0fv9q3b4his7g947fmgkskpfly6dsniw0g6mg38wcnjrd57p";
-        authors = [
-          "Norbert Pozar <bertapozar@gmail.com>"
-        ];
-        dependencies = [
-          {
-            name = "fnv";
-            packageId = "fnv";
-          }
-          {
-            name = "nom";
-            packageId = "nom";
-          }
-        ];
-        features = {
-          "serde" = [ "dep:serde" ];
-        };
-        resolvedDefaultFeatures = [ "default" ];
-      };
       "mime" = rec {
         crateName = "mime";
         version = "0.3.17";
@@ -4801,7 +4855,7 @@ rec {
           "simd" = [ "simd-adler32" ];
           "simd-adler32" = [ "dep:simd-adler32" ];
         };
-        resolvedDefaultFeatures = [ "simd" "simd-adler32" "with-alloc" ];
+        resolvedDefaultFeatures = [ "default" "simd" "simd-adler32" "with-alloc" ];
       };
       "mio" = rec {
         crateName = "mio";
@@ -4983,23 +5037,6 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "std" ];
       };
-      "nom" = rec {
-        crateName = "nom";
-        version = "1.2.4";
-        edition = "2015";
-        sha256 = "1kjh42w67z1hh1dw3jrilgqrf54jk2xcvhw4rcdm4wclzmbc5f55";
-        authors = [
-          "contact@geoffroycouprie.com"
-        ];
-        features = {
-          "default" = [ "stream" ];
-          "lazy_static" = [ "dep:lazy_static" ];
-          "regex" = [ "dep:regex" ];
-          "regexp" = [ "regex" ];
-          "regexp_macros" = [ "regexp" "lazy_static" ];
-        };
-        resolvedDefaultFeatures = [ "default" "stream" ];
-      };
       "nu-ansi-term" = rec {
         crateName = "nu-ansi-term";
         version = "0.50.3";
@@ -5332,6 +5369,10 @@ rec {
         ];
         src = lib.cleanSourceWith { filter = sourceFilter;  src = ./.; };
         dependencies = [
+          {
+            name = "ab_glyph";
+            packageId = "ab_glyph";
+          }
           {
             name = "async-trait";
             packageId = "async-trait";
@@ -5347,7 +5388,7 @@ rec {
           {
             name = "diesel";
             packageId = "diesel";
-            features = [ "sqlite" "r2d2" "postgres" "uuid" ];
+            features = [ "sqlite" "r2d2" "postgres" "uuid" "chrono" ];
           }
           {
             name = "dotenv";
@@ -5361,10 +5402,6 @@ rec {
             name = "lazy_static";
             packageId = "lazy_static";
           }
-          {
-            name = "meval";
-            packageId = "meval";
-          }
           {
             name = "phf";
             packageId = "phf 0.11.3";
@@ -5412,6 +5449,10 @@ rec {
             packageId = "symphonia";
             features = [ "aac" "mp3" "isomp4" "alac" ];
           }
+          {
+            name = "tiny-skia";
+            packageId = "tiny-skia";
+          }
           {
             name = "tokio";
             packageId = "tokio";
@@ -5425,6 +5466,33 @@ rec {
         ];
 
       };
+      "owned_ttf_parser" = rec {
+        crateName = "owned_ttf_parser";
+        version = "0.25.1";
+        edition = "2021";
+        sha256 = "0fsqzcbc4sq8qhkmc3rgcfg1xg389nmhlxvmvi6h38dca680x0in";
+        authors = [
+          "Alex Butler <alexheretic@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "ttf-parser";
+            packageId = "ttf-parser";
+            usesDefaultFeatures = false;
+          }
+        ];
+        features = {
+          "apple-layout" = [ "ttf-parser/apple-layout" ];
+          "default" = [ "std" "opentype-layout" "apple-layout" "variable-fonts" "glyph-names" ];
+          "glyph-names" = [ "ttf-parser/glyph-names" ];
+          "gvar-alloc" = [ "std" "ttf-parser/gvar-alloc" ];
+          "no-std-float" = [ "ttf-parser/no-std-float" ];
+          "opentype-layout" = [ "ttf-parser/opentype-layout" ];
+          "std" = [ "ttf-parser/std" ];
+          "variable-fonts" = [ "ttf-parser/variable-fonts" ];
+        };
+        resolvedDefaultFeatures = [ "apple-layout" "default" "glyph-names" "gvar-alloc" "opentype-layout" "std" "variable-fonts" ];
+      };
       "parking_lot" = rec {
         crateName = "parking_lot";
         version = "0.12.5";
@@ -5821,6 +5889,41 @@ rec {
         ];
 
       };
+      "png" = rec {
+        crateName = "png";
+        version = "0.17.16";
+        edition = "2018";
+        sha256 = "09kmkms9fmkbkarw0lnf0scqvjwwg3r7riddag0i3q39r0pil5c2";
+        authors = [
+          "The image-rs Developers"
+        ];
+        dependencies = [
+          {
+            name = "bitflags";
+            packageId = "bitflags 1.3.2";
+          }
+          {
+            name = "crc32fast";
+            packageId = "crc32fast";
+          }
+          {
+            name = "fdeflate";
+            packageId = "fdeflate";
+          }
+          {
+            name = "flate2";
+            packageId = "flate2";
+          }
+          {
+            name = "miniz_oxide";
+            packageId = "miniz_oxide";
+            features = [ "simd" ];
+          }
+        ];
+        features = {
+          "unstable" = [ "crc32fast/nightly" ];
+        };
+      };
       "poise" = rec {
         crateName = "poise";
         version = "0.6.1";
@@ -8484,6 +8587,7 @@ rec {
         features = {
           "default" = [ "std" "const-generics" ];
         };
+        resolvedDefaultFeatures = [ "const-generics" "default" "std" ];
       };
       "siphasher" = rec {
         crateName = "siphasher";
@@ -9132,6 +9236,21 @@ rec {
         ];
 
       };
+      "strict-num" = rec {
+        crateName = "strict-num";
+        version = "0.1.1";
+        edition = "2018";
+        sha256 = "0cb7l1vhb8zj90mzm8avlk815k40sql9515s865rqdrdfavvldv6";
+        libName = "strict_num";
+        authors = [
+          "Yevhenii Reizner <razrfalcon@gmail.com>"
+        ];
+        features = {
+          "approx-eq" = [ "float-cmp" ];
+          "default" = [ "approx-eq" ];
+          "float-cmp" = [ "dep:float-cmp" ];
+        };
+      };
       "strsim 0.11.1" = rec {
         crateName = "strsim";
         version = "0.11.1";
@@ -10188,6 +10307,89 @@ rec {
         };
         resolvedDefaultFeatures = [ "formatting" "parsing" "serde" ];
       };
+      "tiny-skia" = rec {
+        crateName = "tiny-skia";
+        version = "0.11.4";
+        edition = "2018";
+        sha256 = "1aq9gd4qh4418g8v08qzakqqggx8hl66qcianl3k5bjdsja37lc3";
+        libName = "tiny_skia";
+        authors = [
+          "Yevhenii Reizner <razrfalcon@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "arrayref";
+            packageId = "arrayref";
+          }
+          {
+            name = "arrayvec";
+            packageId = "arrayvec";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "bytemuck";
+            packageId = "bytemuck";
+            features = [ "aarch64_simd" ];
+          }
+          {
+            name = "cfg-if";
+            packageId = "cfg-if";
+          }
+          {
+            name = "log";
+            packageId = "log";
+          }
+          {
+            name = "png";
+            packageId = "png";
+            optional = true;
+          }
+          {
+            name = "tiny-skia-path";
+            packageId = "tiny-skia-path";
+            usesDefaultFeatures = false;
+          }
+        ];
+        features = {
+          "default" = [ "std" "simd" "png-format" ];
+          "no-std-float" = [ "tiny-skia-path/no-std-float" ];
+          "png" = [ "dep:png" ];
+          "png-format" = [ "std" "png" ];
+          "std" = [ "tiny-skia-path/std" ];
+        };
+        resolvedDefaultFeatures = [ "default" "png" "png-format" "simd" "std" ];
+      };
+      "tiny-skia-path" = rec {
+        crateName = "tiny-skia-path";
+        version = "0.11.4";
+        edition = "2018";
+        sha256 = "14ywbdfakvacl6rxxmzbnycplaxpc6i2linh2yqk0sp8qb07z7lw";
+        libName = "tiny_skia_path";
+        authors = [
+          "Yevhenii Reizner <razrfalcon@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "arrayref";
+            packageId = "arrayref";
+          }
+          {
+            name = "bytemuck";
+            packageId = "bytemuck";
+          }
+          {
+            name = "strict-num";
+            packageId = "strict-num";
+            usesDefaultFeatures = false;
+          }
+        ];
+        features = {
+          "default" = [ "std" ];
+          "libm" = [ "dep:libm" ];
+          "no-std-float" = [ "libm" ];
+        };
+        resolvedDefaultFeatures = [ "std" ];
+      };
       "tinystr" = rec {
         crateName = "tinystr";
         version = "0.8.2";
@@ -11174,6 +11376,26 @@ rec {
         ];
 
       };
+      "ttf-parser" = rec {
+        crateName = "ttf-parser";
+        version = "0.25.1";
+        edition = "2018";
+        sha256 = "0cbgqglcwwjg3hirwq6xlza54w04mb5x02kf7zx4hrw50xmr1pyj";
+        libName = "ttf_parser";
+        authors = [
+          "Caleb Maclennan <caleb@alerque.com>"
+          "Laurenz Stampfl <laurenz.stampfl@gmail.com>"
+          "Yevhenii Reizner <razrfalcon@gmail.com>"
+          "خالد حسني (Khaled Hosny) <khaled@aliftype.com>"
+        ];
+        features = {
+          "core_maths" = [ "dep:core_maths" ];
+          "default" = [ "std" "opentype-layout" "apple-layout" "variable-fonts" "glyph-names" ];
+          "gvar-alloc" = [ "std" ];
+          "no-std-float" = [ "core_maths" ];
+        };
+        resolvedDefaultFeatures = [ "apple-layout" "glyph-names" "gvar-alloc" "opentype-layout" "std" "variable-fonts" ];
+      };
       "tungstenite 0.18.0" = rec {
         crateName = "tungstenite";
         version = "0.18.0";
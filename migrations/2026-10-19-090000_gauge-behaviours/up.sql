@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE "Gauges"
+ADD COLUMN "Rate" INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+CREATE TABLE level_ups (
+    id SERIAL PRIMARY KEY,
+    character_id INTEGER NOT NULL REFERENCES characters(id) ON DELETE CASCADE,
+    batch_id UUID NOT NULL,
+    level INTEGER NOT NULL,
+    rolls TEXT NOT NULL,
+    previous_stat_block TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX level_ups_character_id ON level_ups (character_id);
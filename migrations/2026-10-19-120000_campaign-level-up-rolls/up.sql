@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE "Campaigns"
+ADD COLUMN "LevelUpRolls" TEXT;
@@ -0,0 +1,7 @@
+-- Your SQL goes here
+CREATE TABLE character_owners (
+    character_id INTEGER NOT NULL REFERENCES characters(id) ON DELETE CASCADE,
+    user_id TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (character_id, user_id)
+);
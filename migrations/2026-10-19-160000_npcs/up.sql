@@ -0,0 +1,14 @@
+-- Your SQL goes here
+CREATE TABLE npcs (
+    id SERIAL PRIMARY KEY,
+    owner_id TEXT NOT NULL,
+    campaign_id INTEGER,
+    name TEXT NOT NULL,
+    stat_block TEXT,
+    is_template BOOLEAN NOT NULL DEFAULT FALSE,
+    template_id INTEGER REFERENCES npcs(id) ON DELETE CASCADE,
+    current_hp INTEGER,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX npcs_owner_id ON npcs (owner_id);
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+CREATE TABLE conditions (
+    id SERIAL PRIMARY KEY,
+    character_id INTEGER NOT NULL REFERENCES characters(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    modifier TEXT,
+    target TEXT,
+    disadvantage BOOLEAN NOT NULL DEFAULT FALSE,
+    turns_remaining INTEGER,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (character_id, name)
+);
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+CREATE TABLE items (
+    id SERIAL PRIMARY KEY,
+    character_id INTEGER REFERENCES characters(id) ON DELETE CASCADE,
+    campaign_id INTEGER REFERENCES "Campaigns"("Id") ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    quantity INTEGER NOT NULL DEFAULT 1,
+    weight DOUBLE PRECISION,
+    equipped_slot TEXT,
+    stat_bonuses TEXT,
+    saved_rolls TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Items belong to either a character or a campaign's party stash
+    CHECK ((character_id IS NULL) <> (campaign_id IS NULL))
+);
+
+CREATE INDEX items_character_id ON items (character_id);
+CREATE INDEX items_campaign_id ON items (campaign_id);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE ledger_entries;
+
+ALTER TABLE "Campaigns"
+DROP COLUMN "Currencies";
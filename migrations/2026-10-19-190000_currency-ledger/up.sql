@@ -0,0 +1,18 @@
+-- Your SQL goes here
+ALTER TABLE "Campaigns"
+ADD COLUMN "Currencies" TEXT;
+
+CREATE TABLE ledger_entries (
+    id SERIAL PRIMARY KEY,
+    character_id INTEGER NOT NULL REFERENCES characters(id) ON DELETE CASCADE,
+    campaign_id INTEGER,
+    amount BIGINT NOT NULL,
+    kind TEXT NOT NULL,
+    reason TEXT,
+    counterparty_id INTEGER REFERENCES characters(id) ON DELETE SET NULL,
+    author_id TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX ledger_entries_character_id ON ledger_entries (character_id);
+CREATE INDEX ledger_entries_campaign_id ON ledger_entries (campaign_id);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE xp_awards;
+
+ALTER TABLE "Campaigns"
+DROP COLUMN "XpThresholds";
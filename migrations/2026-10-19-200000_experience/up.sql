@@ -0,0 +1,14 @@
+-- Your SQL goes here
+ALTER TABLE "Campaigns"
+ADD COLUMN "XpThresholds" TEXT;
+
+CREATE TABLE xp_awards (
+    id SERIAL PRIMARY KEY,
+    character_id INTEGER NOT NULL REFERENCES characters(id) ON DELETE CASCADE,
+    amount INTEGER NOT NULL,
+    reason TEXT,
+    author_id TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX xp_awards_character_id ON xp_awards (character_id);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE session_reminders;
+DROP TABLE session_rsvps;
+DROP TABLE sessions;
@@ -0,0 +1,29 @@
+-- Your SQL goes here
+CREATE TABLE sessions (
+    id SERIAL PRIMARY KEY,
+    campaign_id INTEGER NOT NULL REFERENCES "Campaigns"("Id") ON DELETE CASCADE,
+    title TEXT,
+    scheduled_at TIMESTAMPTZ NOT NULL,
+    channel_id TEXT NOT NULL,
+    message_id TEXT,
+    created_by TEXT NOT NULL,
+    cancelled BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE session_rsvps (
+    session_id INTEGER NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    user_id TEXT NOT NULL,
+    status TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (session_id, user_id)
+);
+
+CREATE TABLE session_reminders (
+    id SERIAL PRIMARY KEY,
+    session_id INTEGER NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    remind_at TIMESTAMPTZ NOT NULL,
+    sent BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX session_reminders_due ON session_reminders (sent, remind_at);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE session_events;
+
+ALTER TABLE sessions DROP COLUMN recap;
+ALTER TABLE sessions DROP COLUMN ended_at;
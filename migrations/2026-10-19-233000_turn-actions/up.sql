@@ -0,0 +1,7 @@
+-- Your SQL goes here
+CREATE TABLE turn_actions (
+    character_id INTEGER PRIMARY KEY REFERENCES characters(id) ON DELETE CASCADE,
+    actions_used INTEGER NOT NULL DEFAULT 0,
+    reactions_used INTEGER NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
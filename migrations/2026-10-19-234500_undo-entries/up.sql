@@ -0,0 +1,18 @@
+-- Your SQL goes here
+CREATE TABLE undo_entries (
+    id SERIAL PRIMARY KEY,
+    batch_id UUID NOT NULL,
+    character_id INTEGER NOT NULL REFERENCES characters(id) ON DELETE CASCADE,
+    campaign_id INTEGER REFERENCES "Campaigns"("Id") ON DELETE CASCADE,
+    actor_id TEXT NOT NULL,
+    resource TEXT NOT NULL,
+    gauge_id UUID,
+    old_value BIGINT NOT NULL,
+    new_value BIGINT NOT NULL,
+    undone BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX undo_entries_batch_id ON undo_entries (batch_id);
+CREATE INDEX undo_entries_character_id ON undo_entries (character_id);
+CREATE INDEX undo_entries_actor_id ON undo_entries (actor_id);
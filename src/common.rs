@@ -480,16 +480,6 @@ pub fn strip_non_numerical(s: &str) -> String {
     s.chars().filter(|c| c.is_digit(10)).collect()
 }
 
-pub fn safe_to_number(s: &str) -> i32 {
-    let part_stripped = strip_non_numerical(s);
-
-    if part_stripped.len() == 0 {
-        return 0;
-    }
-
-    return part_stripped.parse::<i32>().unwrap();
-}
-
 pub fn safe_to_u64(s: &str) -> u64 {
     let part_stripped = strip_non_numerical(s);
 
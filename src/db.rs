@@ -1,10 +1,22 @@
 pub mod models;
 pub mod schema;
 
+pub mod campaigns;
+pub mod character_owners;
 pub mod characters;
+pub mod conditions;
 pub mod gauges;
+pub mod items;
+pub mod ledger;
+pub mod level_ups;
+pub mod npcs;
+pub mod revisions;
 pub mod servers;
+pub mod sessions;
+pub mod turn_actions;
+pub mod undo;
 pub mod users;
+pub mod xp;
 
 use self::models::*;
 
@@ -25,6 +37,11 @@ lazy_static::lazy_static! {
     };
 }
 
+diesel::define_sql_function! {
+    /// Lets names be compared case-insensitively without ilike treating % and _ in them as wildcards
+    fn lower(x: diesel::sql_types::Text) -> diesel::sql_types::Text;
+}
+
 #[derive(Debug)]
 pub enum DbError {
     NotFound,
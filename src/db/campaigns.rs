@@ -0,0 +1,94 @@
+use crate::common::Error;
+use crate::db::DbError;
+use super::models::{Campaign, NewCampaign};
+use super::schema::Campaigns::dsl::*;
+use super::POOL;
+use diesel::prelude::*;
+
+pub fn get(campaign_id: i32) -> Result<Campaign, Error> {
+    let mut connection = POOL.get()?;
+
+    let result = Campaigns
+        .filter(Id.eq(campaign_id))
+        .select(Campaign::as_select())
+        .first(&mut connection)
+        .optional()?;
+
+    result.ok_or(Box::new(DbError::NotFound))
+}
+
+/// Campaigns a user is running, not including archived ones
+pub fn get_for_dm(user_id: u64) -> Result<Vec<Campaign>, Error> {
+    let mut connection = POOL.get()?;
+
+    let results = Campaigns
+        .filter(DungeonMasterId.eq(user_id.to_string()))
+        .filter(Archived.eq(false))
+        .order(Id.asc())
+        .select(Campaign::as_select())
+        .load(&mut connection)?;
+
+    Ok(results)
+}
+
+/// Campaigns a user is running or has a character in (as its main owner or a co-owner)
+pub fn get_for_member(user_id: u64, include_archived: bool) -> Result<Vec<Campaign>, Error> {
+    use super::schema::character_owners;
+    use super::schema::characters;
+
+    let mut connection = POOL.get()?;
+
+    let user = user_id.to_string();
+
+    let co_owned = character_owners::table
+        .filter(character_owners::user_id.eq(user.clone()))
+        .select(character_owners::character_id);
+
+    let played = characters::table
+        .filter(
+            characters::user_id
+                .eq(user.clone())
+                .or(characters::id.eq_any(co_owned)),
+        )
+        .select(characters::CampaignId);
+
+    let mut query = Campaigns
+        .filter(DungeonMasterId.eq(user).or(Id.nullable().eq_any(played)))
+        .into_boxed();
+
+    if !include_archived {
+        query = query.filter(Archived.eq(false));
+    }
+
+    let results = query
+        .order(Id.asc())
+        .select(Campaign::as_select())
+        .load(&mut connection)?;
+
+    Ok(results)
+}
+
+pub fn create(campaign: &NewCampaign) -> Result<Campaign, Error> {
+    let mut connection = POOL.get()?;
+
+    println!("Creating campaign {}", campaign.name);
+
+    let created = diesel::insert_into(Campaigns)
+        .values(campaign)
+        .returning(Campaign::as_returning())
+        .get_result(&mut connection)?;
+
+    Ok(created)
+}
+
+pub fn update(campaign: &Campaign) -> Result<(), Error> {
+    let mut connection = POOL.get()?;
+
+    println!("Updating campaign {}", campaign.id);
+
+    diesel::update(Campaigns.filter(Id.eq(campaign.id)))
+        .set(campaign)
+        .execute(&mut connection)?;
+
+    Ok(())
+}
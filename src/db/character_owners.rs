@@ -0,0 +1,74 @@
+use crate::common::Error;
+use super::models::{Character, NewCharacterOwner};
+use super::schema::character_owners::dsl::*;
+use super::POOL;
+use diesel::prelude::*;
+
+pub fn add(character: i32, user: u64) -> Result<(), Error> {
+    let mut connection = POOL.get()?;
+
+    diesel::insert_into(character_owners)
+        .values(&NewCharacterOwner {
+            character_id: character,
+            user_id: user.to_string(),
+        })
+        .on_conflict_do_nothing()
+        .execute(&mut connection)?;
+
+    Ok(())
+}
+
+pub fn remove(character: i32, user: u64) -> Result<(), Error> {
+    let mut connection = POOL.get()?;
+
+    diesel::delete(
+        character_owners
+            .filter(character_id.eq(character))
+            .filter(user_id.eq(user.to_string())),
+    )
+    .execute(&mut connection)?;
+
+    Ok(())
+}
+
+/// Users a character has been shared with (not including its main owner)
+pub fn get_for_character(character: i32) -> Result<Vec<String>, Error> {
+    let mut connection = POOL.get()?;
+
+    let results = character_owners
+        .filter(character_id.eq(character))
+        .order(created_at.asc())
+        .select(user_id)
+        .load(&mut connection)?;
+
+    Ok(results)
+}
+
+/// Ids of the characters that have been shared with a user
+pub fn get_shared_with(user: u64) -> Result<Vec<i32>, Error> {
+    let mut connection = POOL.get()?;
+
+    let results = character_owners
+        .filter(user_id.eq(user.to_string()))
+        .select(character_id)
+        .load(&mut connection)?;
+
+    Ok(results)
+}
+
+/// Whether a user owns a character or has had it shared with them
+pub fn is_owner(character: &Character, user: u64) -> Result<bool, Error> {
+    if character.user_id.as_deref() == Some(user.to_string().as_str()) {
+        return Ok(true);
+    }
+
+    let mut connection = POOL.get()?;
+
+    let count: i64 = character_owners
+        .filter(character_id.eq(character.id))
+        .filter(user_id.eq(user.to_string()))
+        .count()
+        .get_result(&mut connection)?;
+
+    Ok(count > 0)
+}
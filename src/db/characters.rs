@@ -18,6 +18,59 @@ pub fn create(character: &Character) -> Result<(), Error> {
     return Ok(());
 }
 
+/// Creates an imported character along with its gauges, conditions, items and XP, all or nothing
+/// Their character ids are filled in once the character has one
+pub fn import(
+    character: &Character,
+    mut gauges: Vec<models::NewGauge>,
+    mut conditions: Vec<models::NewCondition>,
+    mut items: Vec<models::NewItem>,
+    mut xp: Option<models::NewXpAward>,
+) -> Result<Character, Error> {
+    let mut connection = crate::db::POOL.get()?;
+
+    let new_character = models::NewCharacter::from(character);
+
+    let created = connection.transaction::<Character, diesel::result::Error, _>(|connection| {
+        let created = diesel::insert_into(schema::characters::table)
+            .values(&new_character)
+            .returning(Character::as_returning())
+            .get_result(connection)?;
+
+        gauges
+            .iter_mut()
+            .for_each(|gauge| gauge.player_character_id = created.id);
+        conditions
+            .iter_mut()
+            .for_each(|condition| condition.character_id = created.id);
+        items
+            .iter_mut()
+            .for_each(|item| item.character_id = Some(created.id));
+
+        diesel::insert_into(schema::Gauges::table)
+            .values(&gauges)
+            .execute(connection)?;
+        diesel::insert_into(schema::conditions::table)
+            .values(&conditions)
+            .execute(connection)?;
+        diesel::insert_into(schema::items::table)
+            .values(&items)
+            .execute(connection)?;
+
+        if let Some(xp) = xp.as_mut() {
+            xp.character_id = created.id;
+
+            diesel::insert_into(schema::xp_awards::table)
+                .values(&*xp)
+                .execute(connection)?;
+        }
+
+        Ok(created)
+    })?;
+
+    Ok(created)
+}
+
 pub fn delete(character_id: i32, owner_id: u64) -> Result<(), Error> {
     use self::schema::characters::dsl::*;
 
@@ -45,10 +98,13 @@ pub fn delete_global(character_id: i32) -> Result<QueryResult<usize>, Error> {
 pub fn get_from_user_id(user: u64) -> Result<Vec<Character>, Error> {
     use self::schema::characters::dsl::*;
 
+    let shared = crate::db::character_owners::get_shared_with(user)?;
+
     let connection = &mut crate::db::POOL.get()?;
 
     let characters_result = characters
-        .filter(user_id.eq(user.to_string()))
+        .filter(user_id.eq(user.to_string()).or(id.eq_any(shared)))
+        .order(id.asc())
         .select(Character::as_select())
         .load(connection)
         .expect("Error loading posts");
@@ -60,6 +116,20 @@ pub fn get_from_user_id(user: u64) -> Result<Vec<Character>, Error> {
     }
 }
 
+pub fn get_by_campaign(campaign: i32) -> Result<Vec<Character>, Error> {
+    use self::schema::characters::dsl::*;
+
+    let connection = &mut crate::db::POOL.get()?;
+
+    let characters_result = characters
+        .filter(CampaignId.eq(campaign))
+        .order(id.asc())
+        .select(Character::as_select())
+        .load(connection)?;
+
+    Ok(characters_result)
+}
+
 #[allow(dead_code)]
 pub fn get_latest(user: u64) -> Result<Character, crate::common::Error> {
     use self::schema::characters::dsl::*;
@@ -141,6 +211,19 @@ pub fn update(character: &Character) -> Result<(), Error> {
     return Ok(());
 }
 
+/// update() skips fields that are None, so leaving a campaign is saved separately
+pub fn update_campaign(character: &Character) -> Result<(), Error> {
+    use self::schema::characters::dsl::*;
+
+    let connection = &mut crate::db::POOL.get()?;
+
+    diesel::update(characters.filter(id.eq(character.id)))
+        .set(CampaignId.eq(character.campaign_id))
+        .execute(connection)?;
+
+    Ok(())
+}
+
 // #[allow(dead_code)]
 // pub fn update(connection: &mut SqliteConnection, character: &Character) -> Result<(), DbError> {
 //     println!("Updating character");
@@ -0,0 +1,83 @@
+use crate::common::Error;
+use super::models::{Condition, NewCondition};
+use super::schema::conditions::dsl::*;
+use super::lower;
+use super::POOL;
+use diesel::prelude::*;
+
+/// Applies a condition, replacing one with the same name if the character already has it
+pub fn set(condition: &NewCondition) -> Result<Condition, Error> {
+    let mut connection = POOL.get()?;
+
+    println!(
+        "Applying condition {} to character {}",
+        condition.name, condition.character_id
+    );
+
+    diesel::delete(
+        conditions
+            .filter(character_id.eq(condition.character_id))
+            .filter(lower(name).eq(lower(&condition.name))),
+    )
+    .execute(&mut connection)?;
+
+    let created = diesel::insert_into(conditions)
+        .values(condition)
+        .returning(Condition::as_returning())
+        .get_result(&mut connection)?;
+
+    Ok(created)
+}
+
+pub fn get_for_character(character: i32) -> Result<Vec<Condition>, Error> {
+    let mut connection = POOL.get()?;
+
+    let results = conditions
+        .filter(character_id.eq(character))
+        .order(created_at.asc())
+        .select(Condition::as_select())
+        .load(&mut connection)?;
+
+    Ok(results)
+}
+
+pub fn update(condition: &Condition) -> Result<(), Error> {
+    let mut connection = POOL.get()?;
+
+    diesel::update(conditions.filter(id.eq(condition.id)))
+        .set(condition)
+        .execute(&mut connection)?;
+
+    Ok(())
+}
+
+/// Returns how many conditions were removed
+pub fn remove(character: i32, condition_name: &str) -> Result<usize, Error> {
+    let mut connection = POOL.get()?;
+
+    let removed = diesel::delete(
+        conditions
+            .filter(character_id.eq(character))
+            .filter(lower(name).eq(lower(condition_name))),
+    )
+    .execute(&mut connection)?;
+
+    Ok(removed)
+}
+
+pub fn delete(condition_id: i32) -> Result<(), Error> {
+    let mut connection = POOL.get()?;
+
+    diesel::delete(conditions.filter(id.eq(condition_id))).execute(&mut connection)?;
+
+    Ok(())
+}
+
+pub fn clear(character: i32) -> Result<usize, Error> {
+    let mut connection = POOL.get()?;
+
+    let removed =
+        diesel::delete(conditions.filter(character_id.eq(character))).execute(&mut connection)?;
+
+    Ok(removed)
+}
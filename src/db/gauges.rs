@@ -1,6 +1,8 @@
 use crate::common::Error;
-use super::models::Gauge;
+use crate::db::DbError;
+use super::models::{Gauge, NewGauge};
 use super::schema::Gauges::dsl::*;
+use super::lower;
 use super::POOL;
 use diesel::prelude::*;
 
@@ -9,8 +11,68 @@ pub fn get_for_character(character_id: i32) -> Result<Vec<Gauge>, Error> {
 
     let results = Gauges
         .filter(PlayerCharacterId.eq(character_id))
+        .order(Name.asc())
         .select(Gauge::as_select())
         .load(&mut connection)?;
 
     Ok(results)
 }
+
+pub fn get(gauge_id: uuid::Uuid) -> Result<Gauge, Error> {
+    let mut connection = POOL.get()?;
+
+    let result = Gauges
+        .filter(Id.eq(gauge_id))
+        .select(Gauge::as_select())
+        .first(&mut connection)
+        .optional()?;
+
+    result.ok_or(Box::new(DbError::NotFound))
+}
+
+/// Gauge names are matched case-insensitively, as they are typed in by hand
+pub fn get_by_name(character_id: i32, gauge_name: &str) -> Result<Gauge, Error> {
+    let mut connection = POOL.get()?;
+
+    let result = Gauges
+        .filter(PlayerCharacterId.eq(character_id))
+        .filter(lower(Name).eq(lower(gauge_name)))
+        .select(Gauge::as_select())
+        .first(&mut connection)
+        .optional()?;
+
+    result.ok_or(Box::new(DbError::NotFound))
+}
+
+pub fn create(gauge: &NewGauge) -> Result<Gauge, Error> {
+    let mut connection = POOL.get()?;
+
+    println!("Creating gauge {}", gauge.name);
+
+    let created = diesel::insert_into(Gauges)
+        .values(gauge)
+        .returning(Gauge::as_returning())
+        .get_result(&mut connection)?;
+
+    Ok(created)
+}
+
+pub fn update(gauge: &Gauge) -> Result<(), Error> {
+    let mut connection = POOL.get()?;
+
+    println!("Updating gauge {}", gauge.id);
+
+    diesel::update(Gauges.filter(Id.eq(gauge.id)))
+        .set(gauge)
+        .execute(&mut connection)?;
+
+    Ok(())
+}
+
+pub fn delete(gauge_id: uuid::Uuid) -> Result<(), Error> {
+    let mut connection = POOL.get()?;
+
+    diesel::delete(Gauges.filter(Id.eq(gauge_id))).execute(&mut connection)?;
+
+    Ok(())
+}
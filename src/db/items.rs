@@ -0,0 +1,104 @@
+use crate::common::Error;
+use crate::db::DbError;
+use super::models::{Item, NewItem};
+use super::schema::items::dsl::*;
+use super::lower;
+use super::POOL;
+use diesel::prelude::*;
+
+pub fn create(item: &NewItem) -> Result<Item, Error> {
+    let mut connection = POOL.get()?;
+
+    println!("Creating item {}", item.name);
+
+    let created = diesel::insert_into(items)
+        .values(item)
+        .returning(Item::as_returning())
+        .get_result(&mut connection)?;
+
+    Ok(created)
+}
+
+pub fn get_for_character(character: i32) -> Result<Vec<Item>, Error> {
+    let mut connection = POOL.get()?;
+
+    let results = items
+        .filter(character_id.eq(character))
+        .order(id.asc())
+        .select(Item::as_select())
+        .load(&mut connection)?;
+
+    Ok(results)
+}
+
+/// A campaign's party stash
+pub fn get_for_campaign(campaign: i32) -> Result<Vec<Item>, Error> {
+    let mut connection = POOL.get()?;
+
+    let results = items
+        .filter(campaign_id.eq(campaign))
+        .order(id.asc())
+        .select(Item::as_select())
+        .load(&mut connection)?;
+
+    Ok(results)
+}
+
+pub fn get_equipped(character: i32) -> Result<Vec<Item>, Error> {
+    let mut connection = POOL.get()?;
+
+    let results = items
+        .filter(character_id.eq(character))
+        .filter(equipped_slot.is_not_null())
+        .order(id.asc())
+        .select(Item::as_select())
+        .load(&mut connection)?;
+
+    Ok(results)
+}
+
+/// Finds a character's item by name (case insensitive), preferring unequipped stacks
+pub fn find_for_character(character: i32, item_name: &str) -> Result<Item, Error> {
+    let mut connection = POOL.get()?;
+
+    let result = items
+        .filter(character_id.eq(character))
+        .filter(lower(name).eq(lower(item_name)))
+        .order((equipped_slot.is_not_null(), id.asc()))
+        .select(Item::as_select())
+        .first(&mut connection)
+        .optional()?;
+
+    result.ok_or(Box::new(DbError::NotFound))
+}
+
+pub fn find_in_stash(campaign: i32, item_name: &str) -> Result<Item, Error> {
+    let mut connection = POOL.get()?;
+
+    let result = items
+        .filter(campaign_id.eq(campaign))
+        .filter(lower(name).eq(lower(item_name)))
+        .select(Item::as_select())
+        .first(&mut connection)
+        .optional()?;
+
+    result.ok_or(Box::new(DbError::NotFound))
+}
+
+pub fn update(item: &Item) -> Result<(), Error> {
+    let mut connection = POOL.get()?;
+
+    diesel::update(items.filter(id.eq(item.id)))
+        .set(item)
+        .execute(&mut connection)?;
+
+    Ok(())
+}
+
+pub fn delete(item_id: i32) -> Result<(), Error> {
+    let mut connection = POOL.get()?;
+
+    diesel::delete(items.filter(id.eq(item_id))).execute(&mut connection)?;
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use crate::common::Error;
+use super::models::{LedgerEntry, NewLedgerEntry};
+use super::schema::characters;
+use super::schema::ledger_entries::dsl::*;
+use super::POOL;
+use diesel::dsl::sql;
+use diesel::prelude::*;
+use diesel::sql_types::BigInt;
+
+fn balance_of(connection: &mut PgConnection, character: i32) -> QueryResult<i64> {
+    // SUM of a bigint is a numeric, so it's cast back rather than pulling in bigdecimal
+    ledger_entries
+        .filter(character_id.eq(character))
+        .select(sql::<BigInt>("COALESCE(SUM(amount), 0)::bigint"))
+        .first(connection)
+}
+
+/// A character's balance in their campaign's smallest denomination
+pub fn balance(character: i32) -> Result<i64, Error> {
+    let mut connection = POOL.get()?;
+
+    Ok(balance_of(&mut connection, character)?)
+}
+
+pub fn create(entries: &[NewLedgerEntry]) -> Result<(), Error> {
+    let mut connection = POOL.get()?;
+
+    diesel::insert_into(ledger_entries)
+        .values(entries)
+        .execute(&mut connection)?;
+
+    Ok(())
+}
+
+/// Whether a character can pay a cost - call it inside a transaction, as it locks the character's row
+/// until the transaction ends, so two payments can't both spend the same balance
+pub(super) fn can_afford(
+    connection: &mut PgConnection,
+    payer: i32,
+    cost: i64,
+) -> QueryResult<bool> {
+    characters::table
+        .find(payer)
+        .select(characters::id)
+        .for_update()
+        .first::<i32>(connection)?;
+
+    Ok(balance_of(connection, payer)? >= cost)
+}
+
+/// Records a payment's entries, as long as the payer can afford it
+/// Returns false (recording nothing) if they can't
+pub fn transfer(payer: i32, cost: i64, entries: &[NewLedgerEntry]) -> Result<bool, Error> {
+    let mut connection = POOL.get()?;
+
+    let paid = connection.transaction::<bool, diesel::result::Error, _>(|connection| {
+        if !can_afford(connection, payer, cost)? {
+            return Ok(false);
+        }
+
+        diesel::insert_into(ledger_entries)
+            .values(entries)
+            .execute(connection)?;
+
+        Ok(true)
+    })?;
+
+    Ok(paid)
+}
+
+pub fn get_for_character(
+    character: i32,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<Vec<LedgerEntry>, Error> {
+    let mut connection = POOL.get()?;
+
+    let mut query = ledger_entries
+        .filter(character_id.eq(character))
+        .into_boxed();
+
+    if let Some(since) = since {
+        query = query.filter(created_at.ge(since));
+    }
+
+    let results = query
+        .order(created_at.asc())
+        .select(LedgerEntry::as_select())
+        .load(&mut connection)?;
+
+    Ok(results)
+}
+
+pub fn get_for_campaign(
+    campaign: i32,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<Vec<LedgerEntry>, Error> {
+    let mut connection = POOL.get()?;
+
+    let mut query = ledger_entries
+        .filter(campaign_id.eq(campaign))
+        .into_boxed();
+
+    if let Some(since) = since {
+        query = query.filter(created_at.ge(since));
+    }
+
+    let results = query
+        .order(created_at.asc())
+        .select(LedgerEntry::as_select())
+        .load(&mut connection)?;
+
+    Ok(results)
+}
@@ -0,0 +1,59 @@
+use crate::common::Error;
+use super::models::{LevelUp, NewLevelUp};
+use super::schema::level_ups::dsl::*;
+use super::POOL;
+use diesel::prelude::*;
+
+pub fn create(new_level_ups: &[NewLevelUp]) -> Result<(), Error> {
+    let mut connection = POOL.get()?;
+
+    diesel::insert_into(level_ups)
+        .values(new_level_ups)
+        .execute(&mut connection)?;
+
+    Ok(())
+}
+
+pub fn get_for_character(character: i32) -> Result<Vec<LevelUp>, Error> {
+    let mut connection = POOL.get()?;
+
+    let results = level_ups
+        .filter(character_id.eq(character))
+        .order(id.asc())
+        .select(LevelUp::as_select())
+        .load(&mut connection)?;
+
+    Ok(results)
+}
+
+/// Gets every level gained in the character's most recent level up, oldest first
+pub fn get_latest_batch(character: i32) -> Result<Vec<LevelUp>, Error> {
+    let mut connection = POOL.get()?;
+
+    let latest: Option<uuid::Uuid> = level_ups
+        .filter(character_id.eq(character))
+        .order(id.desc())
+        .select(batch_id)
+        .first(&mut connection)
+        .optional()?;
+
+    let Some(latest) = latest else {
+        return Ok(vec![]);
+    };
+
+    let results = level_ups
+        .filter(batch_id.eq(latest))
+        .order(id.asc())
+        .select(LevelUp::as_select())
+        .load(&mut connection)?;
+
+    Ok(results)
+}
+
+pub fn delete_batch(batch: uuid::Uuid) -> Result<(), Error> {
+    let mut connection = POOL.get()?;
+
+    diesel::delete(level_ups.filter(batch_id.eq(batch))).execute(&mut connection)?;
+
+    Ok(())
+}
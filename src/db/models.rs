@@ -46,7 +46,7 @@ impl Character {
 #[diesel(table_name = schema::characters)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 #[diesel(belongs_to(User))]
-#[derive(serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct Character {
     pub id: i32,
     pub user_id: Option<String>,
@@ -133,7 +133,7 @@ pub struct Server {
     pub default_roll_server: Option<String>,
 }
 
-#[derive(Queryable, Selectable, Debug, Clone)]
+#[derive(Queryable, Selectable, AsChangeset, Debug, Clone)]
 #[diesel(table_name = schema::Gauges)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 #[diesel(belongs_to(Character, foreign_key = PlayerCharacterId))]
@@ -154,4 +154,363 @@ pub struct Gauge {
     pub gauge_type: i32,
     #[diesel(column_name = Colour)]
     pub colour: Option<String>,
+    #[diesel(column_name = Rate)]
+    pub rate: i32,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::Gauges)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewGauge {
+    #[diesel(column_name = Id)]
+    pub id: uuid::Uuid,
+    #[diesel(column_name = Icon)]
+    pub icon: Option<String>,
+    #[diesel(column_name = Name)]
+    pub name: String,
+    #[diesel(column_name = Value)]
+    pub value: i32,
+    #[diesel(column_name = Max)]
+    pub max: i32,
+    #[diesel(column_name = PlayerCharacterId)]
+    pub player_character_id: i32,
+    #[diesel(column_name = GaugeType)]
+    pub gauge_type: i32,
+    #[diesel(column_name = Colour)]
+    pub colour: Option<String>,
+    #[diesel(column_name = Rate)]
+    pub rate: i32,
+}
+
+#[derive(Queryable, Selectable, AsChangeset, Debug, Clone)]
+#[diesel(table_name = schema::Campaigns)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(treat_none_as_null = true)]
+pub struct Campaign {
+    #[diesel(column_name = Id)]
+    pub id: i32,
+    #[diesel(column_name = Name)]
+    pub name: String,
+    #[diesel(column_name = DungeonMasterId)]
+    pub dungeon_master_id: Option<String>,
+    #[diesel(column_name = DefaultRollDie)]
+    pub default_roll_die: Option<String>,
+    #[diesel(column_name = StatModifierFormula)]
+    pub stat_modifier_formula: Option<String>,
+    #[diesel(column_name = GameSystem)]
+    pub game_system: Option<String>,
+    #[diesel(column_name = LevelUpRolls)]
+    pub level_up_rolls: Option<String>,
+    #[diesel(column_name = Archived)]
+    pub archived: bool,
+    #[diesel(column_name = Currencies)]
+    pub currencies: Option<String>,
+    #[diesel(column_name = XpThresholds)]
+    pub xp_thresholds: Option<String>,
+    #[diesel(column_name = AdvantageStacking)]
+    pub advantage_stacking: Option<String>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::Campaigns)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewCampaign {
+    #[diesel(column_name = Name)]
+    pub name: String,
+    #[diesel(column_name = DungeonMasterId)]
+    pub dungeon_master_id: Option<String>,
+    #[diesel(column_name = DefaultRollDie)]
+    pub default_roll_die: Option<String>,
+    #[diesel(column_name = StatModifierFormula)]
+    pub stat_modifier_formula: Option<String>,
+    #[diesel(column_name = GameSystem)]
+    pub game_system: Option<String>,
+}
+
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = schema::level_ups)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct LevelUp {
+    pub batch_id: uuid::Uuid,
+    pub level: i32,
+    pub rolls: String,
+    pub previous_stat_block: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::level_ups)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewLevelUp {
+    pub character_id: i32,
+    pub batch_id: uuid::Uuid,
+    pub level: i32,
+    pub rolls: String,
+    pub previous_stat_block: Option<String>,
+}
+
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = schema::stat_block_revisions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct StatBlockRevision {
+    pub revision: i32,
+    pub kind: String,
+    pub content: String,
+    pub source: String,
+    pub author_id: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::stat_block_revisions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewStatBlockRevision {
+    pub character_id: i32,
+    pub revision: i32,
+    pub kind: String,
+    pub content: String,
+    pub source: String,
+    pub author_id: Option<String>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::character_owners)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewCharacterOwner {
+    pub character_id: i32,
+    pub user_id: String,
+}
+
+#[derive(Queryable, Selectable, AsChangeset, Debug, Clone)]
+#[diesel(table_name = schema::npcs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Npc {
+    pub id: i32,
+    pub owner_id: String,
+    pub campaign_id: Option<i32>,
+    pub name: String,
+    pub stat_block: Option<String>,
+    pub is_template: bool,
+    pub template_id: Option<i32>,
+    pub current_hp: Option<i32>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::npcs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewNpc {
+    pub owner_id: String,
+    pub campaign_id: Option<i32>,
+    pub name: String,
+    pub stat_block: Option<String>,
+    pub is_template: bool,
+    pub template_id: Option<i32>,
+    pub current_hp: Option<i32>,
+}
+
+#[derive(Queryable, Selectable, AsChangeset, Debug, Clone)]
+#[diesel(table_name = schema::conditions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(treat_none_as_null = true)]
+pub struct Condition {
+    pub id: i32,
+    pub character_id: i32,
+    pub name: String,
+    pub modifier: Option<String>,
+    pub target: Option<String>,
+    pub disadvantage: bool,
+    pub turns_remaining: Option<i32>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::conditions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewCondition {
+    pub character_id: i32,
+    pub name: String,
+    pub modifier: Option<String>,
+    pub target: Option<String>,
+    pub disadvantage: bool,
+    pub turns_remaining: Option<i32>,
+}
+
+#[derive(Queryable, Selectable, AsChangeset, Debug, Clone)]
+#[diesel(table_name = schema::items)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(treat_none_as_null = true)]
+pub struct Item {
+    pub id: i32,
+    pub character_id: Option<i32>,
+    pub campaign_id: Option<i32>,
+    pub name: String,
+    pub quantity: i32,
+    pub weight: Option<f64>,
+    pub equipped_slot: Option<String>,
+    pub stat_bonuses: Option<String>,
+    pub saved_rolls: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::items)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewItem {
+    pub character_id: Option<i32>,
+    pub campaign_id: Option<i32>,
+    pub name: String,
+    pub quantity: i32,
+    pub weight: Option<f64>,
+    pub equipped_slot: Option<String>,
+    pub stat_bonuses: Option<String>,
+    pub saved_rolls: Option<String>,
+}
+
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = schema::ledger_entries)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct LedgerEntry {
+    pub character_id: i32,
+    pub amount: i64,
+    pub kind: String,
+    pub reason: Option<String>,
+    pub counterparty_id: Option<i32>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::ledger_entries)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewLedgerEntry {
+    pub character_id: i32,
+    pub campaign_id: Option<i32>,
+    pub amount: i64,
+    pub kind: String,
+    pub reason: Option<String>,
+    pub counterparty_id: Option<i32>,
+    pub author_id: String,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::xp_awards)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewXpAward {
+    pub character_id: i32,
+    pub amount: i32,
+    pub reason: Option<String>,
+    pub author_id: String,
+}
+
+#[derive(Queryable, Selectable, AsChangeset, Debug, Clone)]
+#[diesel(table_name = schema::sessions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Session {
+    pub id: i32,
+    pub campaign_id: i32,
+    pub title: Option<String>,
+    pub scheduled_at: chrono::DateTime<chrono::Utc>,
+    pub channel_id: String,
+    pub message_id: Option<String>,
+    pub created_by: String,
+    pub cancelled: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub ended_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub recap: Option<String>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::sessions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewSession {
+    pub campaign_id: i32,
+    pub title: Option<String>,
+    pub scheduled_at: chrono::DateTime<chrono::Utc>,
+    pub channel_id: String,
+    pub created_by: String,
+}
+
+#[derive(Queryable, Selectable, Insertable, Debug, Clone)]
+#[diesel(table_name = schema::session_rsvps)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct SessionRsvp {
+    pub session_id: i32,
+    pub user_id: String,
+    pub status: String,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = schema::session_events)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct SessionEvent {
+    pub character_id: i32,
+    pub kind: String,
+    pub description: String,
+    pub value: Option<f64>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::session_events)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewSessionEvent {
+    pub campaign_id: i32,
+    pub character_id: i32,
+    pub kind: String,
+    pub description: String,
+    pub value: Option<f64>,
+}
+
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = schema::session_reminders)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct SessionReminder {
+    pub id: i32,
+    pub session_id: i32,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::session_reminders)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewSessionReminder {
+    pub session_id: i32,
+    pub remind_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Queryable, Selectable, Insertable, Debug, Clone)]
+#[diesel(table_name = schema::turn_actions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct TurnActions {
+    pub character_id: i32,
+    pub actions_used: i32,
+    pub reactions_used: i32,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = schema::undo_entries)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct UndoEntry {
+    pub character_id: i32,
+    pub campaign_id: Option<i32>,
+    pub actor_id: String,
+    pub resource: String,
+    pub gauge_id: Option<uuid::Uuid>,
+    pub old_value: i64,
+    pub new_value: i64,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::undo_entries)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewUndoEntry {
+    pub batch_id: uuid::Uuid,
+    pub character_id: i32,
+    pub campaign_id: Option<i32>,
+    pub actor_id: String,
+    pub resource: String,
+    pub gauge_id: Option<uuid::Uuid>,
+    pub old_value: i64,
+    pub new_value: i64,
 }
@@ -0,0 +1,88 @@
+use crate::common::Error;
+use crate::db::DbError;
+use super::models::{NewNpc, Npc};
+use super::schema::npcs::dsl::*;
+use super::lower;
+use super::POOL;
+use diesel::prelude::*;
+
+pub fn create(npc: &NewNpc) -> Result<Npc, Error> {
+    let mut connection = POOL.get()?;
+
+    println!("Creating npc {}", npc.name);
+
+    let created = diesel::insert_into(npcs)
+        .values(npc)
+        .returning(Npc::as_returning())
+        .get_result(&mut connection)?;
+
+    Ok(created)
+}
+
+pub fn get(npc_id: i32) -> Result<Npc, Error> {
+    let mut connection = POOL.get()?;
+
+    let result = npcs
+        .filter(id.eq(npc_id))
+        .select(Npc::as_select())
+        .first(&mut connection)
+        .optional()?;
+
+    result.ok_or(Box::new(DbError::NotFound))
+}
+
+pub fn get_by_name(owner: u64, npc_name: &str) -> Result<Npc, Error> {
+    let mut connection = POOL.get()?;
+
+    let result = npcs
+        .filter(owner_id.eq(owner.to_string()))
+        .filter(lower(name).eq(lower(npc_name)))
+        .select(Npc::as_select())
+        .first(&mut connection)
+        .optional()?;
+
+    result.ok_or(Box::new(DbError::NotFound))
+}
+
+pub fn get_for_owner(owner: u64) -> Result<Vec<Npc>, Error> {
+    let mut connection = POOL.get()?;
+
+    let results = npcs
+        .filter(owner_id.eq(owner.to_string()))
+        .order(id.asc())
+        .select(Npc::as_select())
+        .load(&mut connection)?;
+
+    Ok(results)
+}
+
+pub fn get_instances(template: i32) -> Result<Vec<Npc>, Error> {
+    let mut connection = POOL.get()?;
+
+    let results = npcs
+        .filter(template_id.eq(template))
+        .order(id.asc())
+        .select(Npc::as_select())
+        .load(&mut connection)?;
+
+    Ok(results)
+}
+
+pub fn update(npc: &Npc) -> Result<(), Error> {
+    let mut connection = POOL.get()?;
+
+    diesel::update(npcs.filter(id.eq(npc.id)))
+        .set(npc)
+        .execute(&mut connection)?;
+
+    Ok(())
+}
+
+/// Deleting a template also deletes its instances
+pub fn delete(npc_id: i32) -> Result<(), Error> {
+    let mut connection = POOL.get()?;
+
+    diesel::delete(npcs.filter(id.eq(npc_id))).execute(&mut connection)?;
+
+    Ok(())
+}
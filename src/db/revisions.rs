@@ -0,0 +1,115 @@
+use crate::common::Error;
+use super::models::{NewStatBlockRevision, StatBlockRevision};
+use super::schema::stat_block_revisions::dsl::*;
+use super::POOL;
+use diesel::prelude::*;
+
+/// Saves a revision, numbering it after the character's latest one
+pub fn create(
+    character: i32,
+    revision_kind: &str,
+    revision_content: &str,
+    revision_source: &str,
+    author: Option<String>,
+) -> Result<StatBlockRevision, Error> {
+    let mut connection = POOL.get()?;
+
+    let latest: Option<i32> = stat_block_revisions
+        .filter(character_id.eq(character))
+        .select(diesel::dsl::max(revision))
+        .first(&mut connection)?;
+
+    let new_revision = NewStatBlockRevision {
+        character_id: character,
+        revision: latest.unwrap_or(0) + 1,
+        kind: revision_kind.to_string(),
+        content: revision_content.to_string(),
+        source: revision_source.to_string(),
+        author_id: author,
+    };
+
+    let result = diesel::insert_into(stat_block_revisions)
+        .values(&new_revision)
+        .returning(StatBlockRevision::as_returning())
+        .get_result(&mut connection)?;
+
+    Ok(result)
+}
+
+pub fn get_for_character(character: i32) -> Result<Vec<StatBlockRevision>, Error> {
+    let mut connection = POOL.get()?;
+
+    let results = stat_block_revisions
+        .filter(character_id.eq(character))
+        .order(revision.asc())
+        .select(StatBlockRevision::as_select())
+        .load(&mut connection)?;
+
+    Ok(results)
+}
+
+pub fn get(character: i32, revision_number: i32) -> Result<StatBlockRevision, Error> {
+    let mut connection = POOL.get()?;
+
+    let result = stat_block_revisions
+        .filter(character_id.eq(character))
+        .filter(revision.eq(revision_number))
+        .select(StatBlockRevision::as_select())
+        .first(&mut connection)?;
+
+    Ok(result)
+}
+
+/// The revision of the same kind saved before the given one, used as the base for diffs
+pub fn get_previous(
+    character: i32,
+    revision_kind: &str,
+    revision_number: i32,
+) -> Result<Option<StatBlockRevision>, Error> {
+    let mut connection = POOL.get()?;
+
+    let result = stat_block_revisions
+        .filter(character_id.eq(character))
+        .filter(kind.eq(revision_kind))
+        .filter(revision.lt(revision_number))
+        .order(revision.desc())
+        .select(StatBlockRevision::as_select())
+        .first(&mut connection)
+        .optional()?;
+
+    Ok(result)
+}
+
+/// The first revision from a source saved at or after the given time
+pub fn get_first_since(
+    character: i32,
+    revision_kind: &str,
+    revision_source: &str,
+    since: chrono::DateTime<chrono::Utc>,
+) -> Result<Option<StatBlockRevision>, Error> {
+    let mut connection = POOL.get()?;
+
+    let result = stat_block_revisions
+        .filter(character_id.eq(character))
+        .filter(kind.eq(revision_kind))
+        .filter(source.eq(revision_source))
+        .filter(created_at.ge(since))
+        .order(revision.asc())
+        .select(StatBlockRevision::as_select())
+        .first(&mut connection)
+        .optional()?;
+
+    Ok(result)
+}
+
+pub fn has_revisions(character: i32, revision_kind: &str) -> Result<bool, Error> {
+    let mut connection = POOL.get()?;
+
+    let count: i64 = stat_block_revisions
+        .filter(character_id.eq(character))
+        .filter(kind.eq(revision_kind))
+        .count()
+        .get_result(&mut connection)?;
+
+    Ok(count > 0)
+}
@@ -7,6 +7,12 @@ diesel::table! {
         DungeonMasterId -> Nullable<Text>,
         DefaultRollDie -> Nullable<Text>,
         StatModifierFormula -> Nullable<Text>,
+        GameSystem -> Nullable<Text>,
+        LevelUpRolls -> Nullable<Text>,
+        Archived -> Bool,
+        Currencies -> Nullable<Text>,
+        XpThresholds -> Nullable<Text>,
+        AdvantageStacking -> Nullable<Text>,
     }
 }
 
@@ -20,6 +26,15 @@ diesel::table! {
         PlayerCharacterId -> Int4,
         GaugeType -> Int4,
         Colour -> Nullable<Text>,
+        Rate -> Int4,
+    }
+}
+
+diesel::table! {
+    character_owners (character_id, user_id) {
+        character_id -> Int4,
+        user_id -> Text,
+        created_at -> Timestamptz,
     }
 }
 
@@ -46,6 +61,74 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    conditions (id) {
+        id -> Int4,
+        character_id -> Int4,
+        name -> Text,
+        modifier -> Nullable<Text>,
+        target -> Nullable<Text>,
+        disadvantage -> Bool,
+        turns_remaining -> Nullable<Int4>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    items (id) {
+        id -> Int4,
+        character_id -> Nullable<Int4>,
+        campaign_id -> Nullable<Int4>,
+        name -> Text,
+        quantity -> Int4,
+        weight -> Nullable<Float8>,
+        equipped_slot -> Nullable<Text>,
+        stat_bonuses -> Nullable<Text>,
+        saved_rolls -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    ledger_entries (id) {
+        id -> Int4,
+        character_id -> Int4,
+        campaign_id -> Nullable<Int4>,
+        amount -> Int8,
+        kind -> Text,
+        reason -> Nullable<Text>,
+        counterparty_id -> Nullable<Int4>,
+        author_id -> Text,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    level_ups (id) {
+        id -> Int4,
+        character_id -> Int4,
+        batch_id -> Uuid,
+        level -> Int4,
+        rolls -> Text,
+        previous_stat_block -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    npcs (id) {
+        id -> Int4,
+        owner_id -> Text,
+        campaign_id -> Nullable<Int4>,
+        name -> Text,
+        stat_block -> Nullable<Text>,
+        is_template -> Bool,
+        template_id -> Nullable<Int4>,
+        current_hp -> Nullable<Int4>,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     servers (id) {
         id -> Text,
@@ -54,6 +137,90 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    session_events (id) {
+        id -> Int4,
+        campaign_id -> Int4,
+        character_id -> Int4,
+        kind -> Text,
+        description -> Text,
+        value -> Nullable<Float8>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    session_reminders (id) {
+        id -> Int4,
+        session_id -> Int4,
+        remind_at -> Timestamptz,
+        sent -> Bool,
+    }
+}
+
+diesel::table! {
+    session_rsvps (session_id, user_id) {
+        session_id -> Int4,
+        user_id -> Text,
+        status -> Text,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    sessions (id) {
+        id -> Int4,
+        campaign_id -> Int4,
+        title -> Nullable<Text>,
+        scheduled_at -> Timestamptz,
+        channel_id -> Text,
+        message_id -> Nullable<Text>,
+        created_by -> Text,
+        cancelled -> Bool,
+        created_at -> Timestamptz,
+        ended_at -> Nullable<Timestamptz>,
+        recap -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    stat_block_revisions (id) {
+        id -> Int4,
+        character_id -> Int4,
+        revision -> Int4,
+        kind -> Text,
+        content -> Text,
+        source -> Text,
+        author_id -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    turn_actions (character_id) {
+        character_id -> Int4,
+        actions_used -> Int4,
+        reactions_used -> Int4,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    undo_entries (id) {
+        id -> Int4,
+        batch_id -> Uuid,
+        character_id -> Int4,
+        campaign_id -> Nullable<Int4>,
+        actor_id -> Text,
+        resource -> Text,
+        gauge_id -> Nullable<Uuid>,
+        old_value -> Int8,
+        new_value -> Int8,
+        undone -> Bool,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     users (id) {
         id -> Text,
@@ -68,14 +235,54 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    xp_awards (id) {
+        id -> Int4,
+        character_id -> Int4,
+        amount -> Int4,
+        reason -> Nullable<Text>,
+        author_id -> Text,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::joinable!(Campaigns -> users (DungeonMasterId));
 diesel::joinable!(Gauges -> characters (PlayerCharacterId));
 diesel::joinable!(characters -> Campaigns (CampaignId));
+diesel::joinable!(character_owners -> characters (character_id));
+diesel::joinable!(conditions -> characters (character_id));
+diesel::joinable!(items -> Campaigns (campaign_id));
+diesel::joinable!(items -> characters (character_id));
+diesel::joinable!(level_ups -> characters (character_id));
+diesel::joinable!(session_events -> Campaigns (campaign_id));
+diesel::joinable!(session_events -> characters (character_id));
+diesel::joinable!(session_reminders -> sessions (session_id));
+diesel::joinable!(session_rsvps -> sessions (session_id));
+diesel::joinable!(sessions -> Campaigns (campaign_id));
+diesel::joinable!(stat_block_revisions -> characters (character_id));
+diesel::joinable!(turn_actions -> characters (character_id));
+diesel::joinable!(undo_entries -> Campaigns (campaign_id));
+diesel::joinable!(undo_entries -> characters (character_id));
+diesel::joinable!(xp_awards -> characters (character_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     Campaigns,
     Gauges,
+    character_owners,
     characters,
+    conditions,
+    items,
+    ledger_entries,
+    level_ups,
+    npcs,
     servers,
+    session_events,
+    session_reminders,
+    session_rsvps,
+    sessions,
+    stat_block_revisions,
+    turn_actions,
+    undo_entries,
     users,
+    xp_awards,
 );
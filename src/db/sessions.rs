@@ -0,0 +1,188 @@
+use crate::common::Error;
+use crate::db::DbError;
+use super::models::{
+    NewSession, NewSessionEvent, NewSessionReminder, Session, SessionEvent, SessionReminder,
+    SessionRsvp,
+};
+use super::schema::session_events;
+use super::schema::session_reminders;
+use super::schema::session_rsvps;
+use super::schema::sessions;
+use super::POOL;
+use diesel::prelude::*;
+
+pub fn create(session: &NewSession, reminders: &[chrono::DateTime<chrono::Utc>]) -> Result<Session, Error> {
+    let mut connection = POOL.get()?;
+
+    println!("Scheduling session for campaign {}", session.campaign_id);
+
+    let created = connection.transaction::<Session, diesel::result::Error, _>(|connection| {
+        let created = diesel::insert_into(sessions::table)
+            .values(session)
+            .returning(Session::as_returning())
+            .get_result(connection)?;
+
+        let new_reminders: Vec<NewSessionReminder> = reminders
+            .iter()
+            .map(|remind_at| NewSessionReminder {
+                session_id: created.id,
+                remind_at: *remind_at,
+            })
+            .collect();
+
+        diesel::insert_into(session_reminders::table)
+            .values(&new_reminders)
+            .execute(connection)?;
+
+        Ok(created)
+    })?;
+
+    Ok(created)
+}
+
+pub fn get(session_id: i32) -> Result<Session, Error> {
+    let mut connection = POOL.get()?;
+
+    let result = sessions::table
+        .filter(sessions::id.eq(session_id))
+        .select(Session::as_select())
+        .first(&mut connection)
+        .optional()?;
+
+    result.ok_or(Box::new(DbError::NotFound))
+}
+
+pub fn update(session: &Session) -> Result<(), Error> {
+    let mut connection = POOL.get()?;
+
+    diesel::update(sessions::table.filter(sessions::id.eq(session.id)))
+        .set(session)
+        .execute(&mut connection)?;
+
+    Ok(())
+}
+
+/// Sessions that haven't been cancelled and start after the given time, soonest first
+pub fn get_upcoming(
+    campaign: i32,
+    after: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<Session>, Error> {
+    let mut connection = POOL.get()?;
+
+    let results = sessions::table
+        .filter(sessions::campaign_id.eq(campaign))
+        .filter(sessions::cancelled.eq(false))
+        .filter(sessions::scheduled_at.ge(after))
+        .order(sessions::scheduled_at.asc())
+        .select(Session::as_select())
+        .load(&mut connection)?;
+
+    Ok(results)
+}
+
+/// The most recent session that has started
+pub fn get_latest_started(
+    campaign: i32,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<Session, Error> {
+    let mut connection = POOL.get()?;
+
+    let result = sessions::table
+        .filter(sessions::campaign_id.eq(campaign))
+        .filter(sessions::cancelled.eq(false))
+        .filter(sessions::scheduled_at.le(now))
+        .order(sessions::scheduled_at.desc())
+        .select(Session::as_select())
+        .first(&mut connection)
+        .optional()?;
+
+    result.ok_or(Box::new(DbError::NotFound))
+}
+
+pub fn set_rsvp(session: i32, user: u64, rsvp_status: &str) -> Result<(), Error> {
+    let mut connection = POOL.get()?;
+
+    let rsvp = SessionRsvp {
+        session_id: session,
+        user_id: user.to_string(),
+        status: rsvp_status.to_string(),
+        updated_at: chrono::Utc::now(),
+    };
+
+    diesel::insert_into(session_rsvps::table)
+        .values(&rsvp)
+        .on_conflict((session_rsvps::session_id, session_rsvps::user_id))
+        .do_update()
+        .set((
+            session_rsvps::status.eq(&rsvp.status),
+            session_rsvps::updated_at.eq(rsvp.updated_at),
+        ))
+        .execute(&mut connection)?;
+
+    Ok(())
+}
+
+pub fn get_rsvps(session: i32) -> Result<Vec<SessionRsvp>, Error> {
+    let mut connection = POOL.get()?;
+
+    let results = session_rsvps::table
+        .filter(session_rsvps::session_id.eq(session))
+        .order(session_rsvps::updated_at.asc())
+        .select(SessionRsvp::as_select())
+        .load(&mut connection)?;
+
+    Ok(results)
+}
+
+/// Reminders that are due and haven't been sent yet
+pub fn get_due_reminders(now: chrono::DateTime<chrono::Utc>) -> Result<Vec<SessionReminder>, Error> {
+    let mut connection = POOL.get()?;
+
+    let results = session_reminders::table
+        .filter(session_reminders::sent.eq(false))
+        .filter(session_reminders::remind_at.le(now))
+        .order(session_reminders::remind_at.asc())
+        .select(SessionReminder::as_select())
+        .load(&mut connection)?;
+
+    Ok(results)
+}
+
+pub fn mark_reminder_sent(reminder: i32) -> Result<(), Error> {
+    let mut connection = POOL.get()?;
+
+    diesel::update(session_reminders::table.filter(session_reminders::id.eq(reminder)))
+        .set(session_reminders::sent.eq(true))
+        .execute(&mut connection)?;
+
+    Ok(())
+}
+
+pub fn log_event(event: &NewSessionEvent) -> Result<(), Error> {
+    let mut connection = POOL.get()?;
+
+    diesel::insert_into(session_events::table)
+        .values(event)
+        .execute(&mut connection)?;
+
+    Ok(())
+}
+
+/// Everything logged for a campaign between two times, oldest first
+pub fn get_events(
+    campaign: i32,
+    from: chrono::DateTime<chrono::Utc>,
+    to: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<SessionEvent>, Error> {
+    let mut connection = POOL.get()?;
+
+    let results = session_events::table
+        .filter(session_events::campaign_id.eq(campaign))
+        .filter(session_events::created_at.ge(from))
+        .filter(session_events::created_at.le(to))
+        .order(session_events::created_at.asc())
+        .select(SessionEvent::as_select())
+        .load(&mut connection)?;
+
+    Ok(results)
+}
@@ -0,0 +1,50 @@
+use crate::common::Error;
+use super::models::TurnActions;
+use super::schema::turn_actions::dsl::*;
+use super::POOL;
+use diesel::prelude::*;
+
+/// What a character has used this turn - nothing if they haven't acted yet
+pub fn get(character: i32) -> Result<TurnActions, Error> {
+    let mut connection = POOL.get()?;
+
+    let result = turn_actions
+        .filter(character_id.eq(character))
+        .select(TurnActions::as_select())
+        .first(&mut connection)
+        .optional()?;
+
+    Ok(result.unwrap_or(TurnActions {
+        character_id: character,
+        actions_used: 0,
+        reactions_used: 0,
+        updated_at: chrono::Utc::now(),
+    }))
+}
+
+pub fn set(used: &TurnActions) -> Result<(), Error> {
+    let mut connection = POOL.get()?;
+
+    diesel::insert_into(turn_actions)
+        .values(used)
+        .on_conflict(character_id)
+        .do_update()
+        .set((
+            actions_used.eq(used.actions_used),
+            reactions_used.eq(used.reactions_used),
+            updated_at.eq(used.updated_at),
+        ))
+        .execute(&mut connection)?;
+
+    Ok(())
+}
+
+/// Returns whether the character had used anything
+pub fn reset(character: i32) -> Result<bool, Error> {
+    let mut connection = POOL.get()?;
+
+    let removed =
+        diesel::delete(turn_actions.filter(character_id.eq(character))).execute(&mut connection)?;
+
+    Ok(removed > 0)
+}
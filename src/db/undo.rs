@@ -0,0 +1,128 @@
+use crate::common::Error;
+use super::ledger;
+use super::models::{Character, Gauge, NewLedgerEntry, NewUndoEntry, UndoEntry};
+use super::schema::undo_entries::dsl::*;
+use super::schema::{characters, ledger_entries, Gauges};
+use super::POOL;
+use diesel::prelude::*;
+use std::collections::BTreeMap;
+
+/// How reverting a batch went - nothing is saved unless it was Reverted
+pub enum RevertOutcome {
+    Reverted,
+    AlreadyUndone,
+    InsufficientFunds,
+}
+
+pub fn create(entries: &[NewUndoEntry]) -> Result<(), Error> {
+    let mut connection = POOL.get()?;
+
+    diesel::insert_into(undo_entries)
+        .values(entries)
+        .execute(&mut connection)?;
+
+    Ok(())
+}
+
+/// Every change made together in one batch, oldest first
+pub fn get_batch(batch: uuid::Uuid) -> Result<Vec<UndoEntry>, Error> {
+    let mut connection = POOL.get()?;
+
+    let results = undo_entries
+        .filter(batch_id.eq(batch))
+        .order(id.asc())
+        .select(UndoEntry::as_select())
+        .load(&mut connection)?;
+
+    Ok(results)
+}
+
+/// The most recent batch made by a user that hasn't been undone yet
+pub fn latest_batch_by_actor(actor: &str) -> Result<Option<uuid::Uuid>, Error> {
+    let mut connection = POOL.get()?;
+
+    let latest = undo_entries
+        .filter(actor_id.eq(actor))
+        .filter(undone.eq(false))
+        .order(id.desc())
+        .select(batch_id)
+        .first(&mut connection)
+        .optional()?;
+
+    Ok(latest)
+}
+
+/// The most recent batch that changed a character, by anyone, that hasn't been undone yet
+pub fn latest_batch_for_character(character: i32) -> Result<Option<uuid::Uuid>, Error> {
+    let mut connection = POOL.get()?;
+
+    let latest = undo_entries
+        .filter(character_id.eq(character))
+        .filter(undone.eq(false))
+        .order(id.desc())
+        .select(batch_id)
+        .first(&mut connection)
+        .optional()?;
+
+    Ok(latest)
+}
+
+/// Marks a batch as undone and saves what reverting it changed, all or nothing,
+/// so a revert that fails part way can be retried
+/// Money taken back has to be affordable, the same as a payment
+pub fn revert(
+    batch: uuid::Uuid,
+    reverted_characters: &[Character],
+    reverted_gauges: &[Gauge],
+    entries: &[NewLedgerEntry],
+) -> Result<RevertOutcome, Error> {
+    let mut connection = POOL.get()?;
+
+    let outcome =
+        connection.transaction::<RevertOutcome, diesel::result::Error, _>(|connection| {
+            let mut costs: BTreeMap<i32, i64> = BTreeMap::new();
+
+            for entry in entries {
+                *costs.entry(entry.character_id).or_default() -= entry.amount;
+            }
+
+            for (payer, cost) in costs {
+                if cost > 0 && !ledger::can_afford(connection, payer, cost)? {
+                    return Ok(RevertOutcome::InsufficientFunds);
+                }
+            }
+
+            // Marked before anything else is saved, so pressing the button twice can't revert it twice
+            let marked = diesel::update(
+                undo_entries
+                    .filter(batch_id.eq(batch))
+                    .filter(undone.eq(false)),
+            )
+            .set(undone.eq(true))
+            .execute(connection)?;
+
+            if marked == 0 {
+                return Ok(RevertOutcome::AlreadyUndone);
+            }
+
+            for character in reverted_characters {
+                diesel::update(characters::table.find(character.id))
+                    .set(character)
+                    .execute(connection)?;
+            }
+
+            for gauge in reverted_gauges {
+                diesel::update(Gauges::table.find(gauge.id))
+                    .set(gauge)
+                    .execute(connection)?;
+            }
+
+            diesel::insert_into(ledger_entries::table)
+                .values(entries)
+                .execute(connection)?;
+
+            Ok(RevertOutcome::Reverted)
+        })?;
+
+    Ok(outcome)
+}
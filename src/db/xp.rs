@@ -0,0 +1,32 @@
+use crate::common::Error;
+use super::models::NewXpAward;
+use super::schema::xp_awards::dsl::*;
+use super::POOL;
+use diesel::prelude::*;
+
+pub fn award(xp_award: &NewXpAward) -> Result<(), Error> {
+    let mut connection = POOL.get()?;
+
+    println!(
+        "Awarding {} xp to character {}",
+        xp_award.amount, xp_award.character_id
+    );
+
+    diesel::insert_into(xp_awards)
+        .values(xp_award)
+        .execute(&mut connection)?;
+
+    Ok(())
+}
+
+/// A character's total XP
+pub fn total(character: i32) -> Result<i64, Error> {
+    let mut connection = POOL.get()?;
+
+    let amounts: Vec<i32> = xp_awards
+        .filter(character_id.eq(character))
+        .select(amount)
+        .load(&mut connection)?;
+
+    Ok(amounts.iter().map(|xp| *xp as i64).sum())
+}
@@ -8,17 +8,12 @@ use poise::CreateReply;
 use rand::prelude::*;
 use serde::Serialize;
 
-use crate::common::safe_to_number;
-
 use crate::common::join_to_string;
 use crate::common::sum_array;
 
-use meval::eval_str;
+use crate::formula;
 
-extern crate regex;
-use regex::Regex;
 use std::collections::HashMap;
-use std::fmt;
 
 #[derive(Serialize)]
 pub struct RollResult {
@@ -33,50 +28,6 @@ pub struct Roll {
     pub expression: String,
 }
 
-#[derive(Debug)]
-pub enum DiceError {
-    TooMuchD,
-    InvalidFaceCount,
-    InvalidDiceCount,
-}
-
-impl fmt::Display for DiceError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:?}", self)
-    }
-}
-
-impl std::error::Error for DiceError {}
-
-pub fn roll_dice_string(instance: &str) -> Result<Vec<Roll>, DiceError> {
-    let mut number_of_dice = 1;
-    let faces_of_die;
-
-    let components: Vec<&str> = instance.split('d').collect();
-    if components[0] == "" {
-        faces_of_die = safe_to_number(components[1]);
-    } else if components.len() == 2 {
-        faces_of_die = safe_to_number(components[1]);
-        number_of_dice = safe_to_number(components[0]);
-    } else {
-        return Err(DiceError::TooMuchD);
-    }
-
-    if number_of_dice == 0 {
-        return Err(DiceError::InvalidDiceCount);
-    }
-    if faces_of_die == 0 {
-        return Err(DiceError::InvalidFaceCount);
-    }
-    if faces_of_die == 1 {
-        return Err(DiceError::InvalidFaceCount);
-    }
-
-    let dice_rolls = generate_randoms(number_of_dice, faces_of_die);
-
-    Ok(dice_rolls)
-}
-
 pub fn join_rolls_to_string(rolls: &[Roll], separator: &str) -> String {
     let s: String = rolls
         .iter()
@@ -86,34 +37,22 @@ pub fn join_rolls_to_string(rolls: &[Roll], separator: &str) -> String {
     return s;
 }
 
-pub fn sum_roll_array(arr: &Vec<Roll>) -> i32 {
-    let mut result = 0;
-
-    for num in arr {
-        result = result + num.result;
-    }
-    return result;
-}
 pub fn eval_roll(input: &str) -> Result<RollResult, Error> {
-    let pattern = Regex::new(r"\d+d\d+").unwrap(); // This regex pattern matches three-letter words
-
-    let mut replaced = input.to_string();
-
-    let mut all_rolls: Vec<Roll> = vec![];
-
-    for mat in pattern.find_iter(input) {
-        // Has to be mutable to go into mutable array
-        // Probably a better way but I have no net connection to check
-        let mut rolls = roll_dice_string(mat.as_str())?;
-
-        let mat_str = mat.as_str();
+    let mut rng = rand::thread_rng();
 
-        replaced = replaced.replacen(mat_str, &sum_roll_array(&rolls).to_string(), 1);
+    let (calc_result, rolled) = formula::roll(input, &mut |faces| rng.gen_range(1..faces + 1))?;
 
-        all_rolls.append(&mut rolls);
-    }
+    let all_rolls: Vec<Roll> = rolled
+        .into_iter()
+        .flat_map(|dice| {
+            let expression = format!("{}d{}", dice.count, dice.faces);
 
-    let calc_result = eval_str(&replaced)?;
+            dice.results.into_iter().map(move |result| Roll {
+                result: result as i32,
+                expression: expression.clone(),
+            })
+        })
+        .collect();
 
     let rolls_message = format_rolls(&all_rolls);
 
@@ -126,21 +65,6 @@ pub fn eval_roll(input: &str) -> Result<RollResult, Error> {
     })
 }
 
-fn generate_randoms(count: i32, faces: i32) -> Vec<Roll> {
-    let mut rng = rand::thread_rng();
-
-    let mut rolls: Vec<Roll> = vec![];
-
-    for _i in 0..count {
-        rolls.push(Roll {
-            result: rng.gen_range(1..faces + 1),
-            expression: format!("{count}d{faces}"),
-        });
-    }
-
-    return rolls;
-}
-
 fn group_rolls(rolls: &Vec<Roll>) -> HashMap<String, Vec<&Roll>> {
     let mut map: HashMap<String, Vec<&Roll>> = HashMap::new();
     for r in rolls {
@@ -0,0 +1,524 @@
+//! The one place user-supplied formulas and dice expressions are evaluated
+//! Everything is capped (length, nesting, dice, faces and time) and only whitelisted functions can be called
+
+use std::fmt;
+use std::time::Duration;
+use std::time::Instant;
+
+pub static MAX_LENGTH: usize = 1000;
+pub static MAX_DEPTH: usize = 32;
+pub static MAX_DICE: i64 = 1000;
+pub static MAX_FACES: i64 = 10000;
+pub static MAX_EVAL_TIME: Duration = Duration::from_millis(100);
+
+static FUNCTIONS: [(&str, usize); 9] = [
+    ("floor", 1),
+    ("ceil", 1),
+    ("round", 1),
+    ("trunc", 1),
+    ("abs", 1),
+    ("sqrt", 1),
+    ("min", 2),
+    ("max", 2),
+    ("pow", 2),
+];
+
+static CONSTANTS: [(&str, f64); 2] = [("pi", std::f64::consts::PI), ("e", std::f64::consts::E)];
+
+#[derive(Debug, PartialEq)]
+pub enum FormulaError {
+    Empty,
+    TooLong,
+    TooDeep,
+    TooManyDice,
+    TooManyFaces,
+    InvalidDice(String),
+    DiceNotAllowed,
+    UnknownFunction(String),
+    UnknownVariable(String),
+    WrongArgumentCount(String, usize),
+    UnexpectedCharacter(char),
+    UnexpectedToken(String),
+    UnexpectedEnd,
+    NotFinite,
+    TimedOut,
+}
+
+impl fmt::Display for FormulaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormulaError::Empty => write!(f, "The formula is empty"),
+            FormulaError::TooLong => write!(f, "Formulas can be at most {MAX_LENGTH} characters long"),
+            FormulaError::TooDeep => write!(f, "Formulas can only be nested {MAX_DEPTH} levels deep"),
+            FormulaError::TooManyDice => write!(f, "You can roll at most {MAX_DICE} dice at once"),
+            FormulaError::TooManyFaces => write!(f, "Dice can have at most {MAX_FACES} faces"),
+            FormulaError::InvalidDice(dice) => write!(f, "{dice} isn't a valid roll - dice need a whole number of at least 1 die with at least 2 faces"),
+            FormulaError::DiceNotAllowed => write!(f, "Dice can't be rolled here"),
+            FormulaError::UnknownFunction(name) => write!(f, "Unknown function {name} - available functions are {}", FUNCTIONS.map(|(name, _)| name).join(", ")),
+            FormulaError::UnknownVariable(name) => write!(f, "Unknown value {name}"),
+            FormulaError::WrongArgumentCount(name, expected) => write!(f, "{name} takes {expected} argument{}", if *expected == 1 { "" } else { "s" }),
+            FormulaError::UnexpectedCharacter(c) => write!(f, "Unexpected character '{c}'"),
+            FormulaError::UnexpectedToken(token) => write!(f, "Unexpected '{token}'"),
+            FormulaError::UnexpectedEnd => write!(f, "The formula ended unexpectedly - check for missing numbers or brackets"),
+            FormulaError::NotFinite => write!(f, "The result isn't a number (dividing by zero?)"),
+            FormulaError::TimedOut => write!(f, "The formula took too long to evaluate"),
+        }
+    }
+}
+
+impl std::error::Error for FormulaError {}
+
+/// One dice term (e.g. 3d6) and what each die rolled
+#[derive(Debug, Clone, PartialEq)]
+pub struct RolledDice {
+    pub count: i64,
+    pub faces: i64,
+    pub results: Vec<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Dice(i64, i64),
+    Identifier(String),
+    Operator(char),
+    OpenBracket,
+    CloseBracket,
+    Comma,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(number) => write!(f, "{number}"),
+            Token::Dice(count, faces) => write!(f, "{count}d{faces}"),
+            Token::Identifier(name) => write!(f, "{name}"),
+            Token::Operator(operator) => write!(f, "{operator}"),
+            Token::OpenBracket => write!(f, "("),
+            Token::CloseBracket => write!(f, ")"),
+            Token::Comma => write!(f, ","),
+        }
+    }
+}
+
+fn parse_dice(count: &str, faces: &str) -> Result<Token, FormulaError> {
+    let dice = format!("{count}d{faces}");
+
+    let count: i64 = if count.is_empty() {
+        1
+    } else {
+        count
+            .parse()
+            .map_err(|_| FormulaError::InvalidDice(dice.clone()))?
+    };
+
+    let faces: i64 = faces
+        .parse()
+        .map_err(|_| FormulaError::InvalidDice(dice.clone()))?;
+
+    if count > MAX_DICE {
+        return Err(FormulaError::TooManyDice);
+    }
+    if faces > MAX_FACES {
+        return Err(FormulaError::TooManyFaces);
+    }
+    if count < 1 || faces < 2 {
+        return Err(FormulaError::InvalidDice(dice));
+    }
+
+    Ok(Token::Dice(count, faces))
+}
+
+fn tokenise(expression: &str) -> Result<Vec<Token>, FormulaError> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    let take_while = |i: &mut usize, predicate: &dyn Fn(char) -> bool| -> String {
+        let start = *i;
+        while *i < chars.len() && predicate(chars[*i]) {
+            *i += 1;
+        }
+        chars[start..*i].iter().collect()
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let number = take_while(&mut i, &|c| c.is_ascii_digit() || c == '.');
+
+            if i < chars.len() && chars[i] == 'd' {
+                i += 1;
+                let faces = take_while(&mut i, &|c| c.is_ascii_digit());
+                tokens.push(parse_dice(&number, &faces)?);
+            } else {
+                let value = number
+                    .parse()
+                    .map_err(|_| FormulaError::UnexpectedToken(number))?;
+                tokens.push(Token::Number(value));
+            }
+        } else if c.is_alphabetic() || c == '_' {
+            let name = take_while(&mut i, &|c| c.is_alphanumeric() || c == '_');
+
+            // d20 on its own is a single die
+            match name.strip_prefix('d') {
+                Some(faces) if !faces.is_empty() && faces.chars().all(|c| c.is_ascii_digit()) => {
+                    tokens.push(parse_dice("", faces)?)
+                }
+                _ => tokens.push(Token::Identifier(name.to_lowercase())),
+            }
+        } else {
+            tokens.push(match c {
+                '+' | '-' | '*' | '/' | '%' | '^' => Token::Operator(c),
+                '(' => Token::OpenBracket,
+                ')' => Token::CloseBracket,
+                ',' => Token::Comma,
+                _ => return Err(FormulaError::UnexpectedCharacter(c)),
+            });
+            i += 1;
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Evaluates while parsing (recursive descent), so nothing is built up beyond the call stack
+struct Evaluator<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    depth: usize,
+    deadline: Instant,
+    variables: &'a [(&'a str, f64)],
+    roll_die: Option<&'a mut dyn FnMut(i64) -> i64>,
+    dice_rolled: i64,
+    rolled: Vec<RolledDice>,
+}
+
+impl<'a> Evaluator<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<Token, FormulaError> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or(FormulaError::UnexpectedEnd)?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), FormulaError> {
+        let token = self.next()?;
+
+        if token != expected {
+            return Err(FormulaError::UnexpectedToken(token.to_string()));
+        }
+
+        Ok(())
+    }
+
+    fn enter(&mut self) -> Result<(), FormulaError> {
+        self.depth += 1;
+
+        if self.depth > MAX_DEPTH {
+            return Err(FormulaError::TooDeep);
+        }
+        if Instant::now() > self.deadline {
+            return Err(FormulaError::TimedOut);
+        }
+
+        Ok(())
+    }
+
+    fn expression(&mut self) -> Result<f64, FormulaError> {
+        self.enter()?;
+
+        let mut value = self.term()?;
+
+        while let Some(Token::Operator(operator @ ('+' | '-'))) = self.peek().cloned() {
+            self.position += 1;
+            let right = self.term()?;
+
+            value = if operator == '+' { value + right } else { value - right };
+        }
+
+        self.depth -= 1;
+        Ok(value)
+    }
+
+    fn term(&mut self) -> Result<f64, FormulaError> {
+        let mut value = self.unary()?;
+
+        while let Some(Token::Operator(operator @ ('*' | '/' | '%'))) = self.peek().cloned() {
+            self.position += 1;
+            let right = self.unary()?;
+
+            value = match operator {
+                '*' => value * right,
+                '/' => value / right,
+                _ => value % right,
+            };
+        }
+
+        Ok(value)
+    }
+
+    fn unary(&mut self) -> Result<f64, FormulaError> {
+        self.enter()?;
+
+        let value = match self.peek() {
+            Some(Token::Operator('-')) => {
+                self.position += 1;
+                -self.unary()?
+            }
+            Some(Token::Operator('+')) => {
+                self.position += 1;
+                self.unary()?
+            }
+            _ => self.power()?,
+        };
+
+        self.depth -= 1;
+        Ok(value)
+    }
+
+    fn power(&mut self) -> Result<f64, FormulaError> {
+        let base = self.primary()?;
+
+        if let Some(Token::Operator('^')) = self.peek() {
+            self.position += 1;
+            let exponent = self.unary()?;
+
+            return Ok(base.powf(exponent));
+        }
+
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<f64, FormulaError> {
+        match self.next()? {
+            Token::Number(value) => Ok(value),
+            Token::Dice(count, faces) => self.roll(count, faces),
+            Token::OpenBracket => {
+                let value = self.expression()?;
+                self.expect(Token::CloseBracket)?;
+                Ok(value)
+            }
+            Token::Identifier(name) => {
+                if let Some(Token::OpenBracket) = self.peek() {
+                    return self.call(&name);
+                }
+
+                self.variables
+                    .iter()
+                    .chain(CONSTANTS.iter())
+                    .find(|(variable, _)| variable.eq_ignore_ascii_case(&name))
+                    .map(|(_, value)| *value)
+                    .ok_or(FormulaError::UnknownVariable(name))
+            }
+            token => Err(FormulaError::UnexpectedToken(token.to_string())),
+        }
+    }
+
+    fn call(&mut self, name: &str) -> Result<f64, FormulaError> {
+        let (name, expected) = *FUNCTIONS
+            .iter()
+            .find(|(function, _)| *function == name)
+            .ok_or(FormulaError::UnknownFunction(name.to_string()))?;
+
+        self.expect(Token::OpenBracket)?;
+
+        let mut arguments = vec![self.expression()?];
+
+        while let Some(Token::Comma) = self.peek() {
+            self.position += 1;
+            arguments.push(self.expression()?);
+        }
+
+        self.expect(Token::CloseBracket)?;
+
+        if arguments.len() != expected {
+            return Err(FormulaError::WrongArgumentCount(name.to_string(), expected));
+        }
+
+        Ok(match name {
+            "floor" => arguments[0].floor(),
+            "ceil" => arguments[0].ceil(),
+            "round" => arguments[0].round(),
+            "trunc" => arguments[0].trunc(),
+            "abs" => arguments[0].abs(),
+            "sqrt" => arguments[0].sqrt(),
+            "min" => arguments[0].min(arguments[1]),
+            "max" => arguments[0].max(arguments[1]),
+            _ => arguments[0].powf(arguments[1]),
+        })
+    }
+
+    fn roll(&mut self, count: i64, faces: i64) -> Result<f64, FormulaError> {
+        let roll_die = self.roll_die.as_mut().ok_or(FormulaError::DiceNotAllowed)?;
+
+        self.dice_rolled += count;
+
+        if self.dice_rolled > MAX_DICE {
+            return Err(FormulaError::TooManyDice);
+        }
+
+        let results: Vec<i64> = (0..count).map(|_| roll_die(faces)).collect();
+        let total = results.iter().sum::<i64>() as f64;
+
+        self.rolled.push(RolledDice {
+            count,
+            faces,
+            results,
+        });
+
+        Ok(total)
+    }
+}
+
+fn run<'a>(
+    expression: &str,
+    variables: &'a [(&'a str, f64)],
+    roll_die: Option<&'a mut dyn FnMut(i64) -> i64>,
+) -> Result<(f64, Vec<RolledDice>), FormulaError> {
+    if expression.chars().count() > MAX_LENGTH {
+        return Err(FormulaError::TooLong);
+    }
+
+    let tokens = tokenise(expression)?;
+
+    if tokens.is_empty() {
+        return Err(FormulaError::Empty);
+    }
+
+    let mut evaluator = Evaluator {
+        tokens,
+        position: 0,
+        depth: 0,
+        deadline: Instant::now() + MAX_EVAL_TIME,
+        variables,
+        roll_die,
+        dice_rolled: 0,
+        rolled: vec![],
+    };
+
+    let value = evaluator.expression()?;
+
+    if let Some(token) = evaluator.peek() {
+        return Err(FormulaError::UnexpectedToken(token.to_string()));
+    }
+
+    if !value.is_finite() {
+        return Err(FormulaError::NotFinite);
+    }
+
+    Ok((value, evaluator.rolled))
+}
+
+/// Evaluates a formula without any dice or variables, e.g. for /calc
+pub fn evaluate(expression: &str) -> Result<f64, FormulaError> {
+    evaluate_with(expression, &[])
+}
+
+/// Evaluates a formula with named values, e.g. "floor((stat - 10) / 2)" with stat = 14
+pub fn evaluate_with(expression: &str, variables: &[(&str, f64)]) -> Result<f64, FormulaError> {
+    run(expression, variables, None).map(|(value, _)| value)
+}
+
+/// Evaluates a formula containing dice (1d20, 3d6, d8...), rolling each die with `roll_die(faces)`
+pub fn roll(
+    expression: &str,
+    roll_die: &mut dyn FnMut(i64) -> i64,
+) -> Result<(f64, Vec<RolledDice>), FormulaError> {
+    run(expression, &[], Some(roll_die))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn max_roll(expression: &str) -> Result<(f64, Vec<RolledDice>), FormulaError> {
+        roll(expression, &mut |faces| faces)
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(evaluate("1 + 2 * 3"), Ok(7.0));
+        assert_eq!(evaluate("(1 + 2) * 3"), Ok(9.0));
+        assert_eq!(evaluate("10 / 4"), Ok(2.5));
+        assert_eq!(evaluate("7 % 3"), Ok(1.0));
+        assert_eq!(evaluate("2 ^ 3 ^ 2"), Ok(512.0));
+        assert_eq!(evaluate("-2 ^ 2"), Ok(-4.0));
+        assert_eq!(evaluate("3 - -2"), Ok(5.0));
+        assert_eq!(evaluate("1 + -1"), Ok(0.0));
+    }
+
+    #[test]
+    fn test_functions_and_variables() {
+        assert_eq!(evaluate("floor(7 / 2)"), Ok(3.0));
+        assert_eq!(evaluate("max(2, min(5, 3))"), Ok(3.0));
+        assert_eq!(evaluate_with("floor((stat - 10) / 2)", &[("stat", 8.0)]), Ok(-1.0));
+        assert_eq!(evaluate_with("n-20", &[("n", 50.0)]), Ok(30.0));
+        assert_eq!(evaluate("pi"), Ok(std::f64::consts::PI));
+    }
+
+    #[test]
+    fn test_whitelist() {
+        assert_eq!(
+            evaluate("exp(2)"),
+            Err(FormulaError::UnknownFunction("exp".to_string()))
+        );
+        assert_eq!(
+            evaluate("stat + 1"),
+            Err(FormulaError::UnknownVariable("stat".to_string()))
+        );
+        assert_eq!(
+            evaluate("min(1)"),
+            Err(FormulaError::WrongArgumentCount("min".to_string(), 2))
+        );
+        assert_eq!(evaluate("1; 2"), Err(FormulaError::UnexpectedCharacter(';')));
+    }
+
+    #[test]
+    fn test_malformed() {
+        assert_eq!(evaluate(""), Err(FormulaError::Empty));
+        assert_eq!(evaluate("1 +"), Err(FormulaError::UnexpectedEnd));
+        assert_eq!(evaluate("(1 + 2"), Err(FormulaError::UnexpectedEnd));
+        assert_eq!(evaluate("1 + 2)"), Err(FormulaError::UnexpectedToken(")".to_string())));
+        assert_eq!(evaluate("1 / 0"), Err(FormulaError::NotFinite));
+    }
+
+    #[test]
+    fn test_dice() {
+        let (value, rolled) = max_roll("2d6 + d4 + 1").unwrap();
+
+        assert_eq!(value, 17.0);
+        assert_eq!(rolled.len(), 2);
+        assert_eq!(rolled[0].results, vec![6, 6]);
+        assert_eq!(rolled[1].count, 1);
+
+        assert_eq!(evaluate("1d20"), Err(FormulaError::DiceNotAllowed));
+    }
+
+    #[test]
+    fn test_limits() {
+        assert_eq!(max_roll("10000000d100"), Err(FormulaError::TooManyDice));
+        assert_eq!(max_roll("600d6 + 600d6"), Err(FormulaError::TooManyDice));
+        assert_eq!(max_roll("1d1000000"), Err(FormulaError::TooManyFaces));
+        assert_eq!(max_roll("0d6"), Err(FormulaError::InvalidDice("0d6".to_string())));
+        assert_eq!(max_roll("1d1"), Err(FormulaError::InvalidDice("1d1".to_string())));
+        assert_eq!(max_roll("1.5d6"), Err(FormulaError::InvalidDice("1.5d6".to_string())));
+
+        assert_eq!(evaluate(&"1+".repeat(600)), Err(FormulaError::TooLong));
+        assert_eq!(
+            evaluate(&format!("{}1{}", "(".repeat(40), ")".repeat(40))),
+            Err(FormulaError::TooDeep)
+        );
+        assert_eq!(evaluate(&"-".repeat(40)), Err(FormulaError::TooDeep));
+    }
+}
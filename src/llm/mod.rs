@@ -384,11 +384,19 @@ pub async fn generate_image(prompt: &str) -> Result<String, Error> {
     Ok(image_data.url.clone())
 }
 
+static DEFAULT_MODEL: &str = "gpt-4o-mini";
+
+/// Whether there's a token set for the provider of the given (or default) model
+pub fn is_configured(model: Option<&str>) -> bool {
+    get_provider_by_model(model.unwrap_or(DEFAULT_MODEL))
+        .is_some_and(|config| std::env::var(config.access_token_env).is_ok())
+}
+
 pub async fn generate(
     model: Option<&str>,
     messages: Vec<Message>,
 ) -> Result<OpenAIResponse, Error> {
-    let model = model.unwrap_or("gpt-4o-mini");
+    let model = model.unwrap_or(DEFAULT_MODEL);
 
     let provider_config = get_provider_by_model(model).ok_or(LLMError::NoProviderConfigForModel)?;
 
@@ -4,7 +4,6 @@ use axum::{routing::post, Json, Router};
 use std::net::SocketAddr;
 
 use common::ButtonEventSystem;
-use meval::eval_str;
 
 mod admin;
 mod games;
@@ -33,6 +32,7 @@ mod dictionary;
 
 mod db;
 mod dice;
+mod formula;
 mod llm;
 mod rpg;
 mod voice;
@@ -120,7 +120,7 @@ impl EventHandler for Handler {
 
 #[poise::command(slash_command, prefix_command)]
 async fn calc(ctx: Context<'_>, formula: String) -> Result<(), Error> {
-    let evaluation = eval_str(&formula)?;
+    let evaluation = formula::evaluate(&formula)?;
 
     let _ = ctx.say(format!("{formula} = {evaluation}")).await?;
 
@@ -374,6 +374,7 @@ async fn main() {
         .setup(|ctx, _ready, framework| {
             Box::pin(async move {
                 poise::builtins::register_globally(ctx, &framework.options().commands).await?;
+                tokio::spawn(crate::rpg::mir::sessions::run_reminders(ctx.clone()));
                 Ok(Data {})
             })
         })
@@ -0,0 +1,238 @@
+use std::fmt;
+
+use poise::CreateReply;
+
+use crate::common;
+use crate::common::Context;
+use crate::common::Error;
+use crate::db;
+
+use super::super::get_user_character;
+use super::super::RpgError;
+use super::stat_block::StatBlock;
+
+/// Used when the stat block doesn't say, matching 5e's one action and one reaction per turn
+static DEFAULT_ACTIONS: i32 = 1;
+static DEFAULT_REACTIONS: i32 = 1;
+
+/// Pips are only drawn up to this many, after that it's just the numbers
+static MAX_PIPS: i32 = 10;
+
+/// What a spell's cast time takes out of the turn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ActionCost {
+    Actions(i32),
+    Reaction,
+    /// Needs every action, so can only be started at the beginning of a turn
+    WholeTurn,
+    Free,
+}
+
+impl ActionCost {
+    /// Reads cast times like "2 actions", "1 action", "reaction", "entire turn" or "instant"
+    pub fn from_cast_time(cast_time: &str) -> Self {
+        let cast_time = cast_time.trim().trim_matches('"').to_lowercase();
+
+        if cast_time.contains("reaction") {
+            ActionCost::Reaction
+        } else if cast_time.contains("turn") {
+            ActionCost::WholeTurn
+        } else if cast_time.contains("action") {
+            let count = cast_time
+                .split_whitespace()
+                .find_map(|word| word.parse::<i32>().ok())
+                .unwrap_or(1);
+
+            ActionCost::Actions(count.max(0))
+        } else {
+            ActionCost::Free
+        }
+    }
+}
+
+/// How many actions and reactions a character has left this turn
+pub struct ActionEconomy {
+    pub actions_left: i32,
+    pub max_actions: i32,
+    pub reactions_left: i32,
+    pub max_reactions: i32,
+}
+
+fn pips(left: i32, max: i32, colour: &str) -> String {
+    if max <= 0 || max > MAX_PIPS {
+        return String::new();
+    }
+
+    common::draw_bar(left.clamp(0, max), max, max as usize, colour, "⬛") + " "
+}
+
+impl fmt::Display for ActionEconomy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "⚡ Actions {}``{} / {}``    ↩️ Reactions {}``{} / {}``",
+            pips(self.actions_left, self.max_actions, "🟨"),
+            self.actions_left,
+            self.max_actions,
+            pips(self.reactions_left, self.max_reactions, "🟪"),
+            self.reactions_left,
+            self.max_reactions
+        )
+    }
+}
+
+pub fn economy_of(character_id: i32, stat_block: &StatBlock) -> Result<ActionEconomy, Error> {
+    let used = db::turn_actions::get(character_id)?;
+
+    let max_actions = stat_block
+        .actions
+        .map(|actions| actions as i32)
+        .unwrap_or(DEFAULT_ACTIONS);
+    let max_reactions = stat_block
+        .reactions
+        .map(|reactions| reactions as i32)
+        .unwrap_or(DEFAULT_REACTIONS);
+
+    Ok(ActionEconomy {
+        actions_left: max_actions - used.actions_used,
+        max_actions,
+        reactions_left: max_reactions - used.reactions_used,
+        max_reactions,
+    })
+}
+
+/// Uses up actions or a reaction for this turn, failing if there aren't enough left
+pub fn spend(
+    character_id: i32,
+    stat_block: &StatBlock,
+    cost: ActionCost,
+) -> Result<ActionEconomy, Error> {
+    let mut used = db::turn_actions::get(character_id)?;
+    let economy = economy_of(character_id, stat_block)?;
+
+    match cost {
+        ActionCost::Actions(count) => {
+            if count > economy.actions_left {
+                return Err(Box::new(RpgError::NotEnoughActions));
+            }
+
+            used.actions_used += count;
+        }
+        ActionCost::WholeTurn => {
+            if economy.actions_left < economy.max_actions {
+                return Err(Box::new(RpgError::NotEnoughActions));
+            }
+
+            used.actions_used = economy.max_actions;
+        }
+        ActionCost::Reaction => {
+            if economy.reactions_left < 1 {
+                return Err(Box::new(RpgError::NotEnoughReactions));
+            }
+
+            used.reactions_used += 1;
+        }
+        ActionCost::Free => return Ok(economy),
+    }
+
+    used.updated_at = chrono::Utc::now();
+    db::turn_actions::set(&used)?;
+
+    economy_of(character_id, stat_block)
+}
+
+/// Gives a character their actions and reactions back - done at the end of their turn
+/// Returns whether they had used any
+pub fn reset(character_id: i32) -> Result<bool, Error> {
+    db::turn_actions::reset(character_id)
+}
+
+async fn reply_with_economy(ctx: Context<'_>, message: &str, economy: &ActionEconomy) -> Result<(), Error> {
+    ctx.send(
+        CreateReply::default()
+            .content(format!("{message}\n{economy}"))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands("show", "spend_actions", "react", "reset_actions"),
+    subcommand_required
+)]
+pub async fn actions(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// See how many actions and reactions you have left this turn
+#[poise::command(slash_command, prefix_command)]
+pub async fn show(ctx: Context<'_>) -> Result<(), Error> {
+    let character = get_user_character(&ctx)
+        .await?
+        .ok_or(RpgError::NoCharacterSelected)?;
+
+    let stat_block: StatBlock = super::super::get_sheet(Some(ctx.serenity_context()), &character).await?;
+
+    let economy = economy_of(character.id, &stat_block)?;
+
+    reply_with_economy(ctx, "This turn:", &economy).await
+}
+
+/// Use actions for something the bot doesn't track, like attacking or moving
+#[poise::command(slash_command, prefix_command, rename = "spend")]
+pub async fn spend_actions(
+    ctx: Context<'_>,
+    #[description = "Defaults to 1"] count: Option<i32>,
+) -> Result<(), Error> {
+    let character = get_user_character(&ctx)
+        .await?
+        .ok_or(RpgError::NoCharacterSelected)?;
+
+    let stat_block: StatBlock = super::super::get_sheet(Some(ctx.serenity_context()), &character).await?;
+
+    let count = count.unwrap_or(1).max(1);
+
+    let economy = spend(character.id, &stat_block, ActionCost::Actions(count))?;
+
+    reply_with_economy(
+        ctx,
+        &format!("Used {count} action{}", if count == 1 { "" } else { "s" }),
+        &economy,
+    )
+    .await
+}
+
+/// Use your reaction for this turn
+#[poise::command(slash_command, prefix_command)]
+pub async fn react(ctx: Context<'_>) -> Result<(), Error> {
+    let character = get_user_character(&ctx)
+        .await?
+        .ok_or(RpgError::NoCharacterSelected)?;
+
+    let stat_block: StatBlock = super::super::get_sheet(Some(ctx.serenity_context()), &character).await?;
+
+    let economy = spend(character.id, &stat_block, ActionCost::Reaction)?;
+
+    reply_with_economy(ctx, "Used a reaction", &economy).await
+}
+
+/// Get your actions and reactions back without ending your turn
+#[poise::command(slash_command, prefix_command, rename = "reset")]
+pub async fn reset_actions(ctx: Context<'_>) -> Result<(), Error> {
+    let character = get_user_character(&ctx)
+        .await?
+        .ok_or(RpgError::NoCharacterSelected)?;
+
+    let stat_block: StatBlock = super::super::get_sheet(Some(ctx.serenity_context()), &character).await?;
+
+    reset(character.id)?;
+
+    let economy = economy_of(character.id, &stat_block)?;
+
+    reply_with_economy(ctx, "Actions and reactions reset", &economy).await
+}
+
@@ -0,0 +1,110 @@
+use poise::serenity_prelude::AutocompleteChoice;
+use serde_json::Value;
+
+use crate::common::Context;
+use crate::db;
+use crate::db::models::Character;
+
+use super::super::get_user_character;
+use super::fuzzy;
+
+/// Reads a stored stat or spell block as is - refetching the sheet would be too slow for an autocomplete
+fn stored_block(block: &Option<String>) -> Option<Value> {
+    block
+        .as_ref()
+        .and_then(|block| serde_json::from_str(block).ok())
+}
+
+fn keys_of(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(|value| value.as_object())
+        .map(|object| object.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+pub fn spell_names(character: &Character) -> Vec<String> {
+    keys_of(
+        stored_block(&character.spell_block)
+            .as_ref()
+            .and_then(|block| block.get("spells")),
+    )
+}
+
+/// The stat block's own keys along with each stat and special stat
+pub fn stat_names(character: &Character) -> Vec<String> {
+    let block = stored_block(&character.stat_block);
+
+    let mut names = keys_of(block.as_ref());
+
+    for section in ["stats", "special_stats"] {
+        names.extend(keys_of(block.as_ref().and_then(|block| block.get(section))));
+    }
+
+    names.sort();
+    names.dedup();
+
+    names
+}
+
+/// Names from the "name: expression" lines of a character's saved rolls
+pub fn saved_roll_names(character: &Character) -> Vec<String> {
+    character
+        .saved_rolls
+        .as_deref()
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, _)| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+async fn suggest(ctx: Context<'_>, partial: &str, names_of: fn(&Character) -> Vec<String>) -> Vec<String> {
+    let Ok(Some(character)) = get_user_character(&ctx).await else {
+        return vec![];
+    };
+
+    let names = names_of(&character);
+
+    fuzzy::rank(partial, names.iter().map(String::as_str))
+        .into_iter()
+        .map(str::to_string)
+        .collect()
+}
+
+pub async fn spell_name(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    suggest(ctx, partial, spell_names).await
+}
+
+pub async fn stat_name(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    suggest(ctx, partial, stat_names).await
+}
+
+pub async fn saved_roll(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    suggest(ctx, partial, saved_roll_names).await
+}
+
+/// The author's characters, shown by name but filled in as their id
+pub async fn character_id(ctx: Context<'_>, partial: &str) -> Vec<AutocompleteChoice> {
+    let characters = db::characters::get_from_user_id(ctx.author().id.get()).unwrap_or_default();
+
+    let labels: Vec<(String, i32)> = characters
+        .into_iter()
+        .map(|character| {
+            (
+                format!(
+                    "{} [#{}]",
+                    character.name.unwrap_or("No name?".to_string()),
+                    character.id
+                ),
+                character.id,
+            )
+        })
+        .collect();
+
+    fuzzy::rank(partial, labels.iter().map(|(label, _)| label.as_str()))
+        .into_iter()
+        .filter_map(|label| labels.iter().find(|(other, _)| other == label))
+        .map(|(label, id)| AutocompleteChoice::new(label.clone(), *id))
+        .collect()
+}
@@ -0,0 +1,461 @@
+use poise::serenity_prelude::CreateEmbed;
+use poise::serenity_prelude::Mentionable;
+use poise::serenity_prelude::User;
+use poise::CreateReply;
+
+use crate::common;
+use crate::common::Context;
+use crate::common::Error;
+use crate::db;
+use crate::db::models::Campaign;
+use crate::db::models::Character;
+use crate::db::models::NewCampaign;
+
+use super::super::get_user_character;
+use super::super::RpgError;
+use super::level_up::LevelUpRoll;
+use super::roll_mode::StackingRule;
+use super::stat_block::StatBlock;
+
+/// Game system used when neither the campaign nor the sheet specify one
+pub static DEFAULT_GAME_SYSTEM: &str = "mir";
+
+/// Roll used when neither the sheet nor the campaign specify one
+pub static DEFAULT_ROLL: &str = "1d100";
+
+/// Gets the campaign the author is running - campaign_id is only needed if they DM more than one
+pub async fn get_dm_campaign(ctx: &Context<'_>, campaign_id: Option<i32>) -> Result<Campaign, Error> {
+    let author_id = ctx.author().id.get();
+
+    if let Some(campaign_id) = campaign_id {
+        let campaign = db::campaigns::get(campaign_id).map_err(|_| RpgError::CampaignNotFound)?;
+
+        if campaign.dungeon_master_id != Some(author_id.to_string()) {
+            return Err(Box::new(RpgError::NotDungeonMaster));
+        }
+
+        return Ok(campaign);
+    }
+
+    let mut campaigns = db::campaigns::get_for_dm(author_id)?;
+
+    match campaigns.len() {
+        0 => Err(Box::new(RpgError::NotDungeonMaster)),
+        1 => Ok(campaigns.remove(0)),
+        _ => Err(Box::new(RpgError::AmbiguousCampaign)),
+    }
+}
+
+/// The game system rules a character plays by - the campaign's if set,
+/// otherwise guessed from the sheet's modifier formula preset (e.g. "5e")
+pub fn game_system_for(character: &Character, stat_block: Option<&StatBlock>) -> String {
+    let campaign_system = campaign_of(character).and_then(|campaign| campaign.game_system);
+
+    if let Some(system) = campaign_system {
+        return system.to_lowercase();
+    }
+
+    modifier_formula_for(character, stat_block)
+        .map(|formula| formula.trim().to_lowercase())
+        .filter(|formula| super::PRESET_FORMULAS.contains_key(formula.as_str()))
+        .unwrap_or(DEFAULT_GAME_SYSTEM.to_string())
+}
+
+fn campaign_of(character: &Character) -> Option<Campaign> {
+    character
+        .campaign_id
+        .and_then(|id| db::campaigns::get(id).ok())
+}
+
+/// The dice rolled when no expression is given - the sheet's default_roll, then the campaign's DefaultRollDie
+pub fn default_roll_for(character: &Character, stat_block: Option<&StatBlock>) -> String {
+    stat_block
+        .and_then(|stat_block| stat_block.default_roll.clone())
+        .or_else(|| campaign_of(character).and_then(|campaign| campaign.default_roll_die))
+        .filter(|roll| !roll.is_empty())
+        .unwrap_or(DEFAULT_ROLL.to_string())
+}
+
+/// The formula turning stats into modifiers - the sheet's modifier_formula, then the campaign's StatModifierFormula
+pub fn modifier_formula_for(character: &Character, stat_block: Option<&StatBlock>) -> Option<String> {
+    stat_block
+        .and_then(|stat_block| stat_block.modifier_formula.clone())
+        .or_else(|| campaign_of(character).and_then(|campaign| campaign.stat_modifier_formula))
+        .filter(|formula| !formula.is_empty())
+}
+
+/// How advantage and disadvantage combine on a character's rolls - the campaign's AdvantageStacking, or cancel
+pub fn advantage_stacking_for(character: &Character) -> StackingRule {
+    campaign_of(character)
+        .and_then(|campaign| campaign.advantage_stacking)
+        .and_then(|rule| StackingRule::from_str(&rule))
+        .unwrap_or_default()
+}
+
+fn describe_dm(campaign: &Campaign) -> String {
+    campaign
+        .dungeon_master_id
+        .as_ref()
+        .map(|id| format!("<@{id}>"))
+        .unwrap_or("No DM".to_string())
+}
+
+/// Settings given as "none" are cleared rather than set
+fn setting_value(value: String) -> Option<String> {
+    if value.trim().eq_ignore_ascii_case("none") {
+        None
+    } else {
+        Some(value.trim().to_string())
+    }
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands("create", "list", "info", "join", "leave", "archive", "set_dm", "settings"),
+    subcommand_required
+)]
+pub async fn campaign(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Start a new campaign, with you as its DM
+#[poise::command(slash_command, prefix_command)]
+pub async fn create(
+    ctx: Context<'_>,
+    name: String,
+    #[description = "Rolled when a sheet has no default roll, e.g. 1d20"] default_roll_die: Option<
+        String,
+    >,
+    #[description = "Used when a sheet has no modifier formula, e.g. 5e"]
+    stat_modifier_formula: Option<String>,
+    #[description = "Rules used for rests etc., e.g. mir or 5e"] game_system: Option<String>,
+) -> Result<(), Error> {
+    let campaign = db::campaigns::create(&NewCampaign {
+        name,
+        dungeon_master_id: Some(ctx.author().id.to_string()),
+        default_roll_die,
+        stat_modifier_formula,
+        game_system: game_system.map(|system| system.to_lowercase()),
+    })?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "Created campaign **{}** (id {}) - players can join it with /campaign join {}",
+                campaign.name, campaign.id, campaign.id
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// List the campaigns you run or have a character in
+#[poise::command(slash_command, prefix_command)]
+pub async fn list(ctx: Context<'_>, include_archived: Option<bool>) -> Result<(), Error> {
+    let campaigns =
+        db::campaigns::get_for_member(ctx.author().id.get(), include_archived.unwrap_or(false))?;
+
+    let content = if campaigns.is_empty() {
+        "You aren't in any campaigns yet - start one with /campaign create".to_string()
+    } else {
+        campaigns
+            .iter()
+            .map(|campaign| {
+                format!(
+                    "- **{}** [#{}] run by {}{}",
+                    campaign.name,
+                    campaign.id,
+                    describe_dm(campaign),
+                    if campaign.archived { " (archived)" } else { "" }
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+
+    ctx.send(CreateReply::default().content(content).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Show a campaign's settings and characters - defaults to your selected character's campaign
+#[poise::command(slash_command, prefix_command)]
+pub async fn info(ctx: Context<'_>, campaign_id: Option<i32>) -> Result<(), Error> {
+    let campaign_id = match campaign_id {
+        Some(campaign_id) => campaign_id,
+        None => get_user_character(&ctx)
+            .await?
+            .ok_or(RpgError::NoCharacterSelected)?
+            .campaign_id
+            .ok_or(RpgError::CampaignNotFound)?,
+    };
+
+    let campaign = db::campaigns::get(campaign_id).map_err(|_| RpgError::CampaignNotFound)?;
+
+    let characters = db::characters::get_by_campaign(campaign.id)?
+        .iter()
+        .map(|character| {
+            format!(
+                "- {} [#{}]",
+                character.name.clone().unwrap_or("No name?".to_string()),
+                character.id
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let setting = |value: &Option<String>| value.clone().unwrap_or("Not set".to_string());
+
+    let title = if campaign.archived {
+        format!("{} (archived)", campaign.name)
+    } else {
+        campaign.name.clone()
+    };
+
+    let embed = CreateEmbed::default()
+        .title(title)
+        .field("DM", describe_dm(&campaign), false)
+        .field("Default roll", setting(&campaign.default_roll_die), true)
+        .field(
+            "Stat modifier formula",
+            setting(&campaign.stat_modifier_formula),
+            true,
+        )
+        .field("Game system", setting(&campaign.game_system), true)
+        .field(
+            "Currencies",
+            campaign
+                .currencies
+                .clone()
+                .unwrap_or(format!("{} (default)", super::currency::DEFAULT_DENOMINATIONS)),
+            true,
+        )
+        .field(
+            "XP thresholds",
+            setting(&campaign.xp_thresholds),
+            false,
+        )
+        .field(
+            "Advantage stacking",
+            campaign
+                .advantage_stacking
+                .clone()
+                .unwrap_or(format!("{} (default)", StackingRule::default().as_str())),
+            true,
+        )
+        .field(
+            "Characters",
+            if characters.is_empty() {
+                "No characters yet".to_string()
+            } else {
+                characters
+            },
+            false,
+        );
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Add your selected character to a campaign
+#[poise::command(slash_command, prefix_command)]
+pub async fn join(ctx: Context<'_>, campaign_id: i32) -> Result<(), Error> {
+    let mut character = get_user_character(&ctx)
+        .await?
+        .ok_or(RpgError::NoCharacterSelected)?;
+
+    let campaign = db::campaigns::get(campaign_id).map_err(|_| RpgError::CampaignNotFound)?;
+
+    if campaign.archived {
+        return Err(Box::new(RpgError::CampaignArchived));
+    }
+
+    character.campaign_id = Some(campaign.id);
+    db::characters::update(&character)?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "{} joined **{}**",
+                character.name.clone().unwrap_or("No name?".to_string()),
+                campaign.name
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Remove your selected character from its campaign
+#[poise::command(slash_command, prefix_command)]
+pub async fn leave(ctx: Context<'_>) -> Result<(), Error> {
+    let mut character = get_user_character(&ctx)
+        .await?
+        .ok_or(RpgError::NoCharacterSelected)?;
+
+    character.campaign_id = None;
+    db::characters::update_campaign(&character)?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "{} is no longer in a campaign",
+                character.name.clone().unwrap_or("No name?".to_string())
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Archived campaigns can't be joined and are hidden from /campaign list
+#[poise::command(slash_command, prefix_command)]
+pub async fn archive(
+    ctx: Context<'_>,
+    campaign_id: i32,
+    #[description = "Set to false to unarchive"] archived: Option<bool>,
+) -> Result<(), Error> {
+    let mut campaign = get_dm_campaign(&ctx, Some(campaign_id)).await?;
+
+    campaign.archived = archived.unwrap_or(true);
+    db::campaigns::update(&campaign)?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "**{}** is {}",
+                campaign.name,
+                if campaign.archived {
+                    "now archived"
+                } else {
+                    "no longer archived"
+                }
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Hand a campaign over to another DM - server admins can also assign DMs
+#[poise::command(slash_command, prefix_command)]
+pub async fn set_dm(ctx: Context<'_>, campaign_id: i32, user: User) -> Result<(), Error> {
+    let mut campaign = db::campaigns::get(campaign_id).map_err(|_| RpgError::CampaignNotFound)?;
+
+    let is_dm = campaign.dungeon_master_id == Some(ctx.author().id.to_string());
+
+    let is_admin = match ctx.guild_id() {
+        Some(guild_id) => common::check_admin(ctx, guild_id, ctx.author().id).await,
+        None => false,
+    };
+
+    if !is_dm && !is_admin {
+        return Err(Box::new(RpgError::NotDungeonMaster));
+    }
+
+    campaign.dungeon_master_id = Some(user.id.to_string());
+    db::campaigns::update(&campaign)?;
+
+    ctx.send(CreateReply::default().content(format!(
+        "{} is now the DM of **{}**",
+        user.mention(),
+        campaign.name
+    )))
+    .await?;
+
+    Ok(())
+}
+
+/// Change a campaign's settings - set a value to "none" to clear it
+#[poise::command(slash_command, prefix_command)]
+#[allow(clippy::too_many_arguments)]
+pub async fn settings(
+    ctx: Context<'_>,
+    #[description = "Only needed if you DM more than one campaign"] campaign_id: Option<i32>,
+    name: Option<String>,
+    default_roll_die: Option<String>,
+    stat_modifier_formula: Option<String>,
+    game_system: Option<String>,
+    #[description = "JSON list of level up rolls, see the readme"] level_up_rolls: Option<String>,
+    #[description = "Coins and their worth in the smallest coin, e.g. gp 100, sp 10, cp 1"]
+    currencies: Option<String>,
+    #[description = "XP needed for each level, e.g. 0, 300, 900"] xp_thresholds: Option<String>,
+    #[description = "How several sources of advantage and disadvantage combine"]
+    advantage_stacking: Option<StackingRule>,
+) -> Result<(), Error> {
+    let mut campaign = get_dm_campaign(&ctx, campaign_id).await?;
+
+    if let Some(name) = name {
+        campaign.name = name;
+    }
+
+    if let Some(default_roll_die) = default_roll_die {
+        campaign.default_roll_die = setting_value(default_roll_die);
+    }
+
+    if let Some(stat_modifier_formula) = stat_modifier_formula {
+        campaign.stat_modifier_formula = setting_value(stat_modifier_formula);
+    }
+
+    if let Some(game_system) = game_system {
+        campaign.game_system = setting_value(game_system).map(|system| system.to_lowercase());
+    }
+
+    if let Some(level_up_rolls) = level_up_rolls {
+        let level_up_rolls = setting_value(level_up_rolls);
+
+        if let Some(rolls) = &level_up_rolls {
+            serde_json::from_str::<Vec<LevelUpRoll>>(rolls)
+                .map_err(|_| RpgError::InvalidLevelUpRolls)?;
+        }
+
+        campaign.level_up_rolls = level_up_rolls;
+    }
+
+    if let Some(currencies) = currencies {
+        let currencies = setting_value(currencies);
+
+        if let Some(currencies) = &currencies {
+            super::currency::parse_denominations(currencies)?;
+        }
+
+        campaign.currencies = currencies;
+    }
+
+    if let Some(xp_thresholds) = xp_thresholds {
+        let xp_thresholds = setting_value(xp_thresholds);
+
+        if let Some(thresholds) = &xp_thresholds {
+            super::xp::parse_thresholds(thresholds)?;
+        }
+
+        campaign.xp_thresholds = xp_thresholds;
+    }
+
+    if let Some(advantage_stacking) = advantage_stacking {
+        campaign.advantage_stacking = Some(advantage_stacking.as_str().to_string());
+    }
+
+    db::campaigns::update(&campaign)?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "Updated **{}** - see /campaign info {}",
+                campaign.name, campaign.id
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
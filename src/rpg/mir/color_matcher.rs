@@ -156,6 +156,11 @@ fn parse_color(color_str: &str) -> Option<RGB> {
     None
 }
 
+/// Parse a color string into its red, green and blue values
+pub fn parse_rgb(color_str: &str) -> Option<(u8, u8, u8)> {
+    parse_color(color_str).map(|rgb| (rgb.r, rgb.g, rgb.b))
+}
+
 /// Find the closest colored square emoji for a given color string
 pub fn get_closest_color_emoji(color_str: Option<&str>) -> &'static str {
     let default_emoji = "🟦"; // Blue square as fallback
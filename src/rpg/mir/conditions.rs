@@ -0,0 +1,349 @@
+use poise::CreateReply;
+
+use crate::common::Context;
+use crate::common::Error;
+use crate::db;
+use crate::db::models::Character;
+use crate::db::models::Condition;
+use crate::db::models::NewCondition;
+use crate::dice;
+use crate::dice::RollResult;
+
+use super::super::RpgError;
+use super::ownership::get_managed_character;
+use super::roll_mode;
+use super::roll_mode::RollMode;
+
+/// Turns "2", "-1", "d4" etc. into something that can be appended to a roll ("+2", "-1", "+1d4")
+fn normalise_modifier(modifier: &str) -> Result<String, Error> {
+    let modifier = super::normalise_dice(modifier.trim());
+
+    let modifier = if modifier.starts_with('+') || modifier.starts_with('-') {
+        modifier
+    } else {
+        format!("+{modifier}")
+    };
+
+    // Make sure it can actually be rolled before saving it
+    dice::eval_roll(&format!("0{modifier}")).map_err(|_| RpgError::InvalidConditionModifier)?;
+
+    Ok(modifier)
+}
+
+/// Checks a condition before it's applied, tidying its modifier and target the same way for every source
+pub fn check_new_condition(condition: &mut NewCondition) -> Result<(), Error> {
+    condition.name = condition.name.trim().to_string();
+    condition.modifier = condition
+        .modifier
+        .as_deref()
+        .map(normalise_modifier)
+        .transpose()?;
+    condition.target = condition
+        .target
+        .as_ref()
+        .map(|target| target.trim().to_string())
+        .filter(|target| !target.is_empty() && !target.eq_ignore_ascii_case("all"));
+    condition.turns_remaining = condition.turns_remaining.filter(|turns| *turns > 0);
+
+    Ok(())
+}
+
+/// Whether a condition affects a roll - conditions without a target affect every roll,
+/// otherwise the roll has to use the targeted stat (matched the same way stats are substituted)
+fn applies_to(condition: &Condition, expression: &str, stat_keys: &[String]) -> bool {
+    let Some(target) = condition
+        .target
+        .as_ref()
+        .map(|target| target.to_lowercase())
+    else {
+        return true;
+    };
+
+    // "str" and "strength" both target a "Strength" stat
+    let mut targeted = stat_keys
+        .iter()
+        .filter(|stat| {
+            let stat = stat.to_lowercase();
+            stat == target || stat.chars().take(3).collect::<String>() == target
+        })
+        .peekable();
+
+    if targeted.peek().is_none() {
+        return expression.to_lowercase().contains(&target);
+    }
+
+    targeted.any(|stat| super::uses_stat(expression, stat))
+}
+
+fn describe(condition: &Condition) -> String {
+    let mut effects = vec![];
+
+    if let Some(modifier) = &condition.modifier {
+        effects.push(modifier.to_string());
+    }
+    if condition.disadvantage {
+        effects.push("disadvantage".to_string());
+    }
+    if let Some(target) = &condition.target {
+        effects.push(format!("on {target} rolls"));
+    }
+    if let Some(turns) = condition.turns_remaining {
+        effects.push(format!("{turns} turn{} left", if turns == 1 { "" } else { "s" }));
+    }
+
+    if effects.is_empty() {
+        condition.name.clone()
+    } else {
+        format!("{} ({})", condition.name, effects.join(", "))
+    }
+}
+
+/// A character's conditions with their effects, e.g. "Poisoned (-2, 3 turns left)"
+pub fn descriptions(character_id: i32) -> Vec<String> {
+    db::conditions::get_for_character(character_id)
+        .unwrap_or_default()
+        .iter()
+        .map(describe)
+        .collect()
+}
+
+/// Conditions listed one per line, for the status embed and party dashboard
+pub fn summary(character_id: i32) -> Option<String> {
+    let descriptions = descriptions(character_id);
+
+    if descriptions.is_empty() {
+        return None;
+    }
+
+    Some(
+        descriptions
+            .iter()
+            .map(|description| format!("- {description}"))
+            .collect::<Vec<String>>()
+            .join("\n"),
+    )
+}
+
+/// Rolls an expression with a character's active conditions applied, listing them under the result
+/// `original_expression` is the roll as written, before stats were substituted, so targeted conditions can be matched
+/// Conditions giving disadvantage are combined with `mode` using the campaign's stacking rule
+pub fn roll_with_conditions(
+    character: &Character,
+    original_expression: &str,
+    expression: &str,
+    stat_keys: &[String],
+    mode: RollMode,
+) -> Result<RollResult, Error> {
+    let active: Vec<Condition> = db::conditions::get_for_character(character.id)?
+        .into_iter()
+        .filter(|condition| applies_to(condition, original_expression, stat_keys))
+        .collect();
+
+    let stacking = super::campaigns::advantage_stacking_for(character);
+
+    let advantages = (mode == RollMode::Advantage) as usize;
+    let disadvantages = (mode == RollMode::Disadvantage) as usize
+        + active.iter().filter(|condition| condition.disadvantage).count();
+
+    let (mode, rolls) = stacking.resolve(advantages, disadvantages);
+
+    if active.is_empty() {
+        return roll_mode::roll(expression, mode, rolls);
+    }
+
+    let modifiers: String = active
+        .iter()
+        .filter_map(|condition| condition.modifier.clone())
+        .collect();
+
+    let expression = format!("{expression}{modifiers}");
+
+    let mut result = roll_mode::roll(&expression, mode, rolls)?;
+
+    result.message = format!(
+        "{}\n\nConditions:\n{}",
+        result.message,
+        active
+            .iter()
+            .map(|condition| format!("- {}", describe(condition)))
+            .collect::<Vec<String>>()
+            .join("\n")
+    );
+
+    Ok(result)
+}
+
+/// Counts down conditions with a duration at the end of a turn, returning the names of those that ended
+pub fn tick(character_id: i32) -> Result<Vec<String>, Error> {
+    let mut ended = vec![];
+
+    for mut condition in db::conditions::get_for_character(character_id)? {
+        let Some(turns) = condition.turns_remaining else {
+            continue;
+        };
+
+        if turns <= 1 {
+            db::conditions::delete(condition.id)?;
+            ended.push(condition.name);
+        } else {
+            condition.turns_remaining = Some(turns - 1);
+            db::conditions::update(&condition)?;
+        }
+    }
+
+    Ok(ended)
+}
+
+/// Ends every condition counting down in turns, as a rest outlasts them, returning their names
+pub fn end_timed(character_id: i32) -> Result<Vec<String>, Error> {
+    let mut ended = vec![];
+
+    for condition in db::conditions::get_for_character(character_id)? {
+        if condition.turns_remaining.is_some() {
+            db::conditions::delete(condition.id)?;
+            ended.push(condition.name);
+        }
+    }
+
+    Ok(ended)
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands("add", "remove", "list", "clear"),
+    subcommand_required
+)]
+pub async fn condition(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Apply a condition (e.g. poisoned) to a character - reapplying one replaces it
+#[poise::command(slash_command, prefix_command)]
+pub async fn add(
+    ctx: Context<'_>,
+    name: String,
+    #[description = "Added to affected rolls, e.g. -2 or +1d4"] modifier: Option<String>,
+    #[description = "Only affect rolls using this stat, e.g. str - defaults to every roll"]
+    target: Option<String>,
+    #[description = "Roll twice and keep the lowest"] disadvantage: Option<bool>,
+    #[description = "Ends after this many turns - lasts until removed otherwise"] turns: Option<i32>,
+    #[description = "Defaults to your selected character"] character_id: Option<i32>,
+) -> Result<(), Error> {
+    let character = get_managed_character(&ctx, character_id).await?;
+
+    let mut new_condition = NewCondition {
+        character_id: character.id,
+        name,
+        modifier,
+        target,
+        disadvantage: disadvantage.unwrap_or(false),
+        turns_remaining: turns,
+    };
+
+    check_new_condition(&mut new_condition)?;
+
+    let condition = db::conditions::set(&new_condition)?;
+
+    ctx.send(CreateReply::default().content(format!(
+        "{} is now {}",
+        character.name.unwrap_or("No name?".to_string()),
+        describe(&condition)
+    )))
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command)]
+pub async fn remove(
+    ctx: Context<'_>,
+    name: String,
+    #[description = "Defaults to your selected character"] character_id: Option<i32>,
+) -> Result<(), Error> {
+    let character = get_managed_character(&ctx, character_id).await?;
+
+    if db::conditions::remove(character.id, name.trim())? == 0 {
+        return Err(Box::new(RpgError::ConditionNotFound));
+    }
+
+    ctx.send(CreateReply::default().content(format!(
+        "{} is no longer {}",
+        character.name.unwrap_or("No name?".to_string()),
+        name.trim()
+    )))
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command)]
+pub async fn list(
+    ctx: Context<'_>,
+    #[description = "Defaults to your selected character"] character_id: Option<i32>,
+) -> Result<(), Error> {
+    let character = get_managed_character(&ctx, character_id).await?;
+
+    let content = summary(character.id).unwrap_or("No conditions".to_string());
+
+    ctx.send(CreateReply::default().content(content).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Remove every condition from a character
+#[poise::command(slash_command, prefix_command)]
+pub async fn clear(
+    ctx: Context<'_>,
+    #[description = "Defaults to your selected character"] character_id: Option<i32>,
+) -> Result<(), Error> {
+    let character = get_managed_character(&ctx, character_id).await?;
+
+    let removed = db::conditions::clear(character.id)?;
+
+    ctx.send(CreateReply::default().content(format!(
+        "Removed {removed} condition{} from {}",
+        if removed == 1 { "" } else { "s" },
+        character.name.unwrap_or("No name?".to_string())
+    )))
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn targeting(target: &str) -> Condition {
+        Condition {
+            id: 0,
+            character_id: 0,
+            name: "Test".to_string(),
+            modifier: None,
+            target: Some(target.to_string()),
+            disadvantage: false,
+            turns_remaining: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    fn stats() -> Vec<String> {
+        vec!["Strength".to_string(), "Constitution".to_string()]
+    }
+
+    #[test]
+    fn targets_match_stat_keys() {
+        assert!(applies_to(&targeting("str"), "1d20+str", &stats()));
+        assert!(applies_to(&targeting("Strength"), "1d20+str", &stats()));
+        assert!(!applies_to(&targeting("str"), "1d20+con", &stats()));
+        assert!(!applies_to(&targeting("strong"), "1d20+str", &stats()));
+    }
+
+    #[test]
+    fn multibyte_targets_do_not_panic() {
+        assert!(!applies_to(&targeting("a日"), "1d20+str", &stats()));
+        assert!(applies_to(&targeting("a日"), "a日", &[]));
+    }
+}
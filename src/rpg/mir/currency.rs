@@ -0,0 +1,590 @@
+use poise::serenity_prelude::Mentionable;
+use poise::serenity_prelude::User;
+use poise::CreateReply;
+use regex::Regex;
+
+use crate::common::Context;
+use crate::common::Error;
+use crate::db;
+use crate::db::models::Character;
+use crate::db::models::LedgerEntry;
+use crate::db::models::NewLedgerEntry;
+
+use super::super::get_user_character;
+use super::super::RpgError;
+use super::campaigns;
+use super::ownership::get_managed_character;
+use super::stat_block::parse_stat_list;
+use super::undo;
+
+/// Used when a character's campaign doesn't set its own currencies
+pub static DEFAULT_DENOMINATIONS: &str = "gp 100, sp 10, cp 1";
+
+/// Most ledger entries shown at once, to stay within discord's message length limit
+static LEDGER_LENGTH: usize = 25;
+
+/// A coin and how many of the smallest coin it's worth
+#[derive(Debug, Clone, PartialEq)]
+pub struct Denomination {
+    pub name: String,
+    pub value: i64,
+}
+
+/// Parses denominations written like "gp 100, sp 10, cp 1", largest first
+pub fn parse_denominations(denominations: &str) -> Result<Vec<Denomination>, Error> {
+    let mut parsed: Vec<Denomination> = parse_stat_list(denominations)
+        .map_err(|_| RpgError::InvalidDenominations)?
+        .into_iter()
+        .map(|(name, value)| Denomination {
+            name,
+            value: value.as_i64().unwrap_or(0),
+        })
+        .collect();
+
+    if parsed.is_empty() || parsed.iter().any(|denomination| denomination.value <= 0) {
+        return Err(Box::new(RpgError::InvalidDenominations));
+    }
+
+    parsed.sort_by_key(|denomination| std::cmp::Reverse(denomination.value));
+
+    Ok(parsed)
+}
+
+/// Parses an amount like "30gp", "2gp 5sp" or "-10" into the smallest denomination
+/// Numbers without a denomination are counted in the smallest one
+pub fn parse_amount(amount: &str, denominations: &[Denomination]) -> Result<i64, Error> {
+    let amount = amount.trim().to_lowercase();
+
+    let (negative, amount) = match amount.strip_prefix('-') {
+        Some(rest) => (true, rest.trim().to_string()),
+        None => (false, amount),
+    };
+
+    let part_pattern = Regex::new(r"^(\d+)\s*([a-z]*)$").unwrap();
+    let smallest = denominations.last().ok_or(RpgError::InvalidDenominations)?;
+
+    let mut total: i64 = 0;
+
+    let parts: Vec<&str> = amount
+        .split(|c: char| c == ',' || c == '+' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect();
+
+    // "30 gp" is split in two, so glue lone denominations back onto the number before them
+    let mut joined: Vec<String> = vec![];
+    for part in parts {
+        match joined.last_mut() {
+            Some(last)
+                if part.chars().all(|c| c.is_alphabetic())
+                    && last.chars().all(|c| c.is_ascii_digit()) =>
+            {
+                last.push_str(part)
+            }
+            _ => joined.push(part.to_string()),
+        }
+    }
+
+    if joined.is_empty() {
+        return Err(Box::new(RpgError::InvalidCurrencyAmount));
+    }
+
+    for part in joined {
+        let captures = part_pattern
+            .captures(&part)
+            .ok_or(RpgError::InvalidCurrencyAmount)?;
+
+        let count: i64 = captures[1]
+            .parse()
+            .map_err(|_| RpgError::InvalidCurrencyAmount)?;
+
+        let value = if captures[2].is_empty() {
+            smallest.value
+        } else {
+            denominations
+                .iter()
+                .find(|denomination| denomination.name == captures[2])
+                .ok_or(RpgError::InvalidCurrencyAmount)?
+                .value
+        };
+
+        total = count
+            .checked_mul(value)
+            .and_then(|value| total.checked_add(value))
+            .ok_or(RpgError::InvalidCurrencyAmount)?;
+    }
+
+    Ok(if negative { -total } else { total })
+}
+
+/// Formats an amount in the smallest denomination using the largest coins possible, e.g. "2gp 5sp"
+pub fn format_amount(amount: i64, denominations: &[Denomination]) -> String {
+    let Some(smallest) = denominations.last() else {
+        return amount.to_string();
+    };
+
+    let mut remaining = amount.abs();
+    let mut parts = vec![];
+
+    for denomination in denominations {
+        let count = remaining / denomination.value;
+
+        if count > 0 {
+            parts.push(format!("{count}{}", denomination.name));
+            remaining -= count * denomination.value;
+        }
+    }
+
+    if parts.is_empty() {
+        return format!("0{}", smallest.name);
+    }
+
+    let formatted = parts.join(" ");
+
+    if amount < 0 {
+        format!("-{formatted}")
+    } else {
+        formatted
+    }
+}
+
+pub fn denominations_for_campaign(campaign_id: Option<i32>) -> Vec<Denomination> {
+    campaign_id
+        .and_then(|campaign_id| db::campaigns::get(campaign_id).ok())
+        .and_then(|campaign| campaign.currencies)
+        .and_then(|currencies| parse_denominations(&currencies).ok())
+        .unwrap_or_else(|| {
+            parse_denominations(DEFAULT_DENOMINATIONS).expect("Default denominations are valid")
+        })
+}
+
+fn character_name(character: &Character) -> String {
+    character.name.clone().unwrap_or("No name?".to_string())
+}
+
+fn ledger_line(entry: &LedgerEntry, denominations: &[Denomination], show_character: bool) -> String {
+    let character = if show_character {
+        db::characters::get(entry.character_id)
+            .map(|character| format!("{}: ", character_name(&character)))
+            .unwrap_or_default()
+    } else {
+        "".to_string()
+    };
+
+    let counterparty = entry
+        .counterparty_id
+        .and_then(|id| db::characters::get(id).ok())
+        .map(|counterparty| {
+            let direction = if entry.amount < 0 { "to" } else { "from" };
+            format!(" {direction} {}", character_name(&counterparty))
+        })
+        .unwrap_or_default();
+
+    let reason = entry
+        .reason
+        .as_ref()
+        .map(|reason| format!(" - {reason}"))
+        .unwrap_or_default();
+
+    let sign = if entry.amount >= 0 { "+" } else { "" };
+
+    format!(
+        "<t:{}:R> {character}{sign}{} ({}{counterparty}){reason}",
+        entry.created_at.timestamp(),
+        format_amount(entry.amount, denominations),
+        entry.kind
+    )
+}
+
+/// Pay another player's selected character
+#[poise::command(slash_command, prefix_command)]
+pub async fn pay(
+    ctx: Context<'_>,
+    user: User,
+    #[description = "e.g. 30gp or 2gp 5sp"] amount: String,
+    reason: Option<String>,
+) -> Result<(), Error> {
+    let payer = get_user_character(&ctx)
+        .await?
+        .ok_or(RpgError::NoCharacterSelected)?;
+
+    let recipient = db::users::get(user.id.get())
+        .ok()
+        .and_then(|user| user.selected_character)
+        .and_then(|id| db::characters::get(id).ok())
+        .ok_or(RpgError::RecipientHasNoCharacter)?;
+
+    if db::character_owners::is_owner(&recipient, ctx.author().id.get())? {
+        return Err(Box::new(RpgError::CannotPaySelf));
+    }
+
+    if recipient.campaign_id != payer.campaign_id {
+        return Err(Box::new(RpgError::RecipientNotInCampaign));
+    }
+
+    let denominations = denominations_for_campaign(payer.campaign_id);
+    let amount = parse_amount(&amount, &denominations)?;
+
+    if amount <= 0 {
+        return Err(Box::new(RpgError::InvalidCurrencyAmount));
+    }
+
+    let author_id = ctx.author().id.to_string();
+
+    let entries = [
+        NewLedgerEntry {
+            character_id: payer.id,
+            campaign_id: payer.campaign_id,
+            amount: -amount,
+            kind: "payment".to_string(),
+            reason: reason.clone(),
+            counterparty_id: Some(recipient.id),
+            author_id: author_id.clone(),
+        },
+        NewLedgerEntry {
+            character_id: recipient.id,
+            campaign_id: recipient.campaign_id,
+            amount,
+            kind: "payment".to_string(),
+            reason,
+            counterparty_id: Some(payer.id),
+            author_id,
+        },
+    ];
+
+    let payer_balance = db::ledger::balance(payer.id)?;
+    let recipient_balance = db::ledger::balance(recipient.id)?;
+
+    if !db::ledger::transfer(payer.id, amount, &entries)? {
+        return Err(Box::new(RpgError::InsufficientFunds));
+    }
+
+    let batch = undo::record(
+        ctx.author().id,
+        &[
+            undo::Change::new(
+                payer.id,
+                undo::Resource::Currency,
+                payer_balance,
+                payer_balance - amount,
+            ),
+            undo::Change::new(
+                recipient.id,
+                undo::Resource::Currency,
+                recipient_balance,
+                recipient_balance + amount,
+            ),
+        ],
+    )?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "{} paid {} to {} ({})",
+                character_name(&payer),
+                format_amount(amount, &denominations),
+                character_name(&recipient),
+                user.mention()
+            ))
+            .components(undo::undo_row(batch)?),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands("show", "grant", "loot", "ledger"),
+    subcommand_required
+)]
+pub async fn wallet(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command)]
+pub async fn show(
+    ctx: Context<'_>,
+    #[description = "Defaults to your selected character"] character_id: Option<i32>,
+) -> Result<(), Error> {
+    let character = get_managed_character(&ctx, character_id).await?;
+
+    let denominations = denominations_for_campaign(character.campaign_id);
+    let balance = db::ledger::balance(character.id)?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "💰 **{}**: {}",
+                character_name(&character),
+                format_amount(balance, &denominations)
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Give (or take, with a negative amount) money to a character in your campaign (DM only)
+#[poise::command(slash_command, prefix_command)]
+pub async fn grant(
+    ctx: Context<'_>,
+    character_id: i32,
+    #[description = "e.g. 30gp, or -5gp to take money away"] amount: String,
+    reason: Option<String>,
+) -> Result<(), Error> {
+    let character = db::characters::get(character_id)?;
+
+    let campaign = campaigns::get_dm_campaign(
+        &ctx,
+        Some(character.campaign_id.ok_or(RpgError::NotDungeonMaster)?),
+    )
+    .await?;
+
+    let denominations = denominations_for_campaign(Some(campaign.id));
+    let amount = parse_amount(&amount, &denominations)?;
+
+    let balance = db::ledger::balance(character.id)?;
+
+    db::ledger::create(&[NewLedgerEntry {
+        character_id: character.id,
+        campaign_id: Some(campaign.id),
+        amount,
+        kind: "grant".to_string(),
+        reason,
+        counterparty_id: None,
+        author_id: ctx.author().id.to_string(),
+    }])?;
+
+    let batch = undo::record(
+        ctx.author().id,
+        &[undo::Change::new(
+            character.id,
+            undo::Resource::Currency,
+            balance,
+            balance + amount,
+        )],
+    )?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "{} {} {}",
+                if amount < 0 { "Took" } else { "Gave" },
+                format_amount(amount.abs(), &denominations),
+                character_name(&character)
+            ))
+            .components(undo::undo_row(batch)?),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Split loot evenly between every character in your campaign (DM only)
+#[poise::command(slash_command, prefix_command)]
+pub async fn loot(
+    ctx: Context<'_>,
+    #[description = "The total to split, e.g. 300gp"] amount: String,
+    reason: Option<String>,
+    #[description = "Only needed if you DM more than one campaign"] campaign_id: Option<i32>,
+) -> Result<(), Error> {
+    let campaign = campaigns::get_dm_campaign(&ctx, campaign_id).await?;
+
+    let denominations = denominations_for_campaign(Some(campaign.id));
+    let amount = parse_amount(&amount, &denominations)?;
+
+    if amount <= 0 {
+        return Err(Box::new(RpgError::InvalidCurrencyAmount));
+    }
+
+    let characters = db::characters::get_by_campaign(campaign.id)?;
+
+    if characters.is_empty() {
+        return Err(Box::new(RpgError::NoCampaignCharacters));
+    }
+
+    let share = amount / characters.len() as i64;
+    let left_over = amount - share * characters.len() as i64;
+
+    let author_id = ctx.author().id.to_string();
+
+    let mut changes = vec![];
+
+    for character in &characters {
+        let balance = db::ledger::balance(character.id)?;
+
+        changes.push(undo::Change::new(
+            character.id,
+            undo::Resource::Currency,
+            balance,
+            balance + share,
+        ));
+    }
+
+    let entries: Vec<NewLedgerEntry> = characters
+        .iter()
+        .map(|character| NewLedgerEntry {
+            character_id: character.id,
+            campaign_id: Some(campaign.id),
+            amount: share,
+            kind: "loot".to_string(),
+            reason: reason.clone(),
+            counterparty_id: None,
+            author_id: author_id.clone(),
+        })
+        .collect();
+
+    db::ledger::create(&entries)?;
+
+    let batch = undo::record(ctx.author().id, &changes)?;
+
+    let mut content = format!(
+        "Split {} between {} characters - {} each",
+        format_amount(amount, &denominations),
+        characters.len(),
+        format_amount(share, &denominations)
+    );
+
+    if left_over > 0 {
+        content += &format!(
+            "\n{} couldn't be split evenly and was left over",
+            format_amount(left_over, &denominations)
+        );
+    }
+
+    ctx.send(
+        CreateReply::default()
+            .content(content)
+            .components(undo::undo_row(batch)?),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// See who gained and spent what - a character's entries, or a whole campaign's
+#[poise::command(slash_command, prefix_command)]
+pub async fn ledger(
+    ctx: Context<'_>,
+    #[description = "Defaults to your selected character"] character_id: Option<i32>,
+    #[description = "Show every character in the campaign (DM only)"] whole_campaign: Option<
+        bool,
+    >,
+    #[description = "Only show the last few hours, e.g. 4 for tonight's session"] hours: Option<
+        i64,
+    >,
+) -> Result<(), Error> {
+    let since = hours.map(|hours| chrono::Utc::now() - chrono::Duration::hours(hours));
+
+    let (entries, denominations, show_character) = if whole_campaign.unwrap_or(false) {
+        let campaign_id = match character_id {
+            Some(character_id) => db::characters::get(character_id)?.campaign_id,
+            None => None,
+        };
+
+        let campaign = campaigns::get_dm_campaign(&ctx, campaign_id).await?;
+
+        (
+            db::ledger::get_for_campaign(campaign.id, since)?,
+            denominations_for_campaign(Some(campaign.id)),
+            true,
+        )
+    } else {
+        let character = get_managed_character(&ctx, character_id).await?;
+
+        (
+            db::ledger::get_for_character(character.id, since)?,
+            denominations_for_campaign(character.campaign_id),
+            false,
+        )
+    };
+
+    let shown = &entries[entries.len().saturating_sub(LEDGER_LENGTH)..];
+
+    let content = if shown.is_empty() {
+        "No transactions found".to_string()
+    } else {
+        shown
+            .iter()
+            .map(|entry| ledger_line(entry, &denominations, show_character))
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+
+    ctx.send(CreateReply::default().content(content).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coins() -> Vec<Denomination> {
+        parse_denominations(DEFAULT_DENOMINATIONS).unwrap()
+    }
+
+    #[test]
+    fn denominations_are_sorted_largest_first() {
+        let denominations = parse_denominations("cp 1, gp 100, sp 10").unwrap();
+
+        let names: Vec<&str> = denominations.iter().map(|d| d.name.as_str()).collect();
+
+        assert_eq!(names, vec!["gp", "sp", "cp"]);
+    }
+
+    #[test]
+    fn invalid_denominations() {
+        assert!(parse_denominations("").is_err());
+        assert!(parse_denominations("gp 0").is_err());
+        assert!(parse_denominations("gold").is_err());
+    }
+
+    #[test]
+    fn parse_single_denomination() {
+        assert_eq!(parse_amount("30gp", &coins()).unwrap(), 3000);
+        assert_eq!(parse_amount("30 gp", &coins()).unwrap(), 3000);
+        assert_eq!(parse_amount("5SP", &coins()).unwrap(), 50);
+    }
+
+    #[test]
+    fn parse_mixed_denominations() {
+        assert_eq!(parse_amount("2gp 5sp", &coins()).unwrap(), 250);
+        assert_eq!(parse_amount("1gp, 1sp, 1cp", &coins()).unwrap(), 111);
+        assert_eq!(parse_amount("1gp+5cp", &coins()).unwrap(), 105);
+    }
+
+    #[test]
+    fn parse_bare_and_negative_amounts() {
+        assert_eq!(parse_amount("42", &coins()).unwrap(), 42);
+        assert_eq!(parse_amount("-5gp", &coins()).unwrap(), -500);
+    }
+
+    #[test]
+    fn parse_invalid_amounts() {
+        assert!(parse_amount("", &coins()).is_err());
+        assert!(parse_amount("30pp", &coins()).is_err());
+        assert!(parse_amount("gp", &coins()).is_err());
+        assert!(parse_amount("1.5gp", &coins()).is_err());
+    }
+
+    #[test]
+    fn format_uses_largest_coins() {
+        assert_eq!(format_amount(250, &coins()), "2gp 5sp");
+        assert_eq!(format_amount(3000, &coins()), "30gp");
+        assert_eq!(format_amount(7, &coins()), "7cp");
+        assert_eq!(format_amount(0, &coins()), "0cp");
+        assert_eq!(format_amount(-105, &coins()), "-1gp 5cp");
+    }
+
+    #[test]
+    fn format_round_trips() {
+        for amount in [1, 99, 101, 12345] {
+            let formatted = format_amount(amount, &coins());
+            assert_eq!(parse_amount(&formatted, &coins()).unwrap(), amount);
+        }
+    }
+}
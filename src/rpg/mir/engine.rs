@@ -0,0 +1,257 @@
+use std::fmt;
+
+use crate::common::Error;
+use crate::db;
+use crate::db::models::Character;
+use crate::db::models::Gauge;
+
+use super::super::spells::ManaSpellResource;
+use super::super::spells::Spell;
+use super::super::RpgError;
+use super::gauges::clamp_gauge_value;
+use super::gauges::set_gauge_value;
+use super::gauges::GaugeType;
+use super::undo::Resource;
+
+#[derive(Debug, Clone, Copy, PartialEq, poise::ChoiceParameter)]
+pub enum RestKind {
+    #[name = "short"]
+    Short,
+    #[name = "long"]
+    Long,
+}
+
+/// A single resource changed by the turn/rest engine, used to build summaries and to undo them
+pub struct ResourceChange {
+    pub character_id: i32,
+    pub resource: Resource,
+    pub name: String,
+    pub old: i32,
+    pub new: i32,
+}
+
+impl fmt::Display for ResourceChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} → {} ({:+})",
+            self.name,
+            self.old,
+            self.new,
+            self.new - self.old
+        )
+    }
+}
+
+pub fn format_changes(changes: &[ResourceChange]) -> String {
+    changes
+        .iter()
+        .map(|change| format!("- {change}"))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Where a gauge ends up after a turn of regen or decay, None for gauges that don't change per turn
+fn turn_value(gauge: &Gauge) -> Option<i32> {
+    let delta = match gauge.behaviour() {
+        GaugeType::Regen => gauge.rate_per_turn(),
+        GaugeType::Decay => -gauge.rate_per_turn(),
+        _ => return None,
+    };
+
+    Some(clamp_gauge_value(gauge, gauge.value.saturating_add(delta)))
+}
+
+/// Applies end of turn gauge behaviours (regen and decay) to every gauge a character has
+pub fn apply_turn(character_id: i32) -> Result<Vec<ResourceChange>, Error> {
+    let mut changes = vec![];
+
+    for mut gauge in db::gauges::get_for_character(character_id)? {
+        let old = gauge.value;
+
+        let Some(new) = turn_value(&gauge).filter(|new| *new != old) else {
+            continue;
+        };
+
+        set_gauge_value(&mut gauge, new)?;
+
+        changes.push(ResourceChange {
+            character_id,
+            resource: Resource::Gauge(gauge.id),
+            name: gauge.name.clone(),
+            old,
+            new: gauge.value,
+        });
+    }
+
+    Ok(changes)
+}
+
+/// One active toggle spell's cost being paid at the end of a turn
+pub struct Upkeep {
+    pub spell_name: String,
+    pub cast_time: String,
+    /// The mana taken, or None if there wasn't enough to keep the spell going
+    pub change: Option<ResourceChange>,
+}
+
+/// Takes each active toggle spell's cost out of a character's mana, in the order they were enabled
+/// The character is updated but still needs saving with db::characters::update afterwards
+pub fn apply_upkeep(
+    character: &mut Character,
+    max_mana: Option<i64>,
+    spells: &[Spell<ManaSpellResource>],
+) -> Result<Vec<Upkeep>, Error> {
+    let mut upkeep = vec![];
+
+    for spell in spells {
+        let old = match character.mana {
+            Some(mana) => mana,
+            None => max_mana.ok_or(RpgError::NoMaxEnergy)? as i32,
+        };
+
+        let new = old + spell.cost.as_ref().ok_or(RpgError::NoSpellCost)?.mana;
+
+        let change = if new >= 0 {
+            // So the next toggle is paid for out of what this one left
+            character.mana = Some(new);
+
+            Some(ResourceChange {
+                character_id: character.id,
+                resource: Resource::Mana,
+                name: "Mana".to_string(),
+                old,
+                new,
+            })
+        } else {
+            None
+        };
+
+        upkeep.push(Upkeep {
+            spell_name: spell
+                .name
+                .clone()
+                .unwrap_or("unknown spell name".to_string()),
+            cast_time: spell
+                .cast_time
+                .clone()
+                .unwrap_or("No cast time found".to_string()),
+            change,
+        });
+    }
+
+    Ok(upkeep)
+}
+
+/// Resets gauges that refill on rest - long rests also reset short rest gauges
+pub fn apply_rest(character_id: i32, kind: RestKind) -> Result<Vec<ResourceChange>, Error> {
+    let mut changes = vec![];
+
+    for mut gauge in db::gauges::get_for_character(character_id)? {
+        let resets = matches!(
+            (gauge.behaviour(), kind),
+            (GaugeType::ShortRestReset, _) | (GaugeType::LongRestReset, RestKind::Long)
+        );
+
+        if !resets || gauge.value == gauge.max {
+            continue;
+        }
+
+        let old = gauge.value;
+        let max = gauge.max;
+
+        set_gauge_value(&mut gauge, max)?;
+
+        changes.push(ResourceChange {
+            character_id,
+            resource: Resource::Gauge(gauge.id),
+            name: gauge.name.clone(),
+            old,
+            new: gauge.value,
+        });
+    }
+
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gauge(gauge_type: GaugeType, value: i32, rate: i32) -> Gauge {
+        Gauge {
+            id: uuid::Uuid::new_v4(),
+            icon: None,
+            name: "Ki".to_string(),
+            value,
+            max: 10,
+            player_character_id: 1,
+            gauge_type: gauge_type as i32,
+            colour: None,
+            rate,
+        }
+    }
+
+    fn toggle(mana: i32) -> Spell<ManaSpellResource> {
+        Spell {
+            name: Some(format!("Costs {mana}")),
+            cost: Some(ManaSpellResource { mana }),
+            spell_type: None,
+            cast_time: None,
+            damage: None,
+            healing: None,
+            save_stat: None,
+            targets: None,
+            description: None,
+        }
+    }
+
+    #[test]
+    fn regen_and_decay_move_by_their_rate() {
+        assert_eq!(turn_value(&gauge(GaugeType::Regen, 4, 3)), Some(7));
+        assert_eq!(turn_value(&gauge(GaugeType::Decay, 4, 3)), Some(1));
+        assert_eq!(turn_value(&gauge(GaugeType::Decay, 4, -3)), Some(1));
+    }
+
+    #[test]
+    fn turn_values_stay_within_the_gauge() {
+        assert_eq!(turn_value(&gauge(GaugeType::Regen, 9, 5)), Some(10));
+        assert_eq!(turn_value(&gauge(GaugeType::Decay, 1, 5)), Some(0));
+        assert_eq!(turn_value(&gauge(GaugeType::Regen, i32::MAX, 5)), Some(10));
+    }
+
+    #[test]
+    fn zero_rate_and_other_behaviours_dont_move() {
+        assert_eq!(turn_value(&gauge(GaugeType::Regen, 4, 0)), Some(4));
+        assert_eq!(turn_value(&gauge(GaugeType::Plain, 4, 3)), None);
+        assert_eq!(turn_value(&gauge(GaugeType::Overflow, 4, 3)), None);
+        assert_eq!(turn_value(&gauge(GaugeType::LongRestReset, 4, 3)), None);
+    }
+
+    #[test]
+    fn upkeep_is_paid_out_of_what_the_last_spell_left() {
+        let mut character = Character::new_empty();
+        character.mana = Some(10);
+
+        let spells = [toggle(-4), toggle(-8), toggle(-5)];
+        let upkeep = apply_upkeep(&mut character, Some(100), &spells).unwrap();
+
+        let paid: Vec<Option<(i32, i32)>> = upkeep
+            .iter()
+            .map(|spell| spell.change.as_ref().map(|change| (change.old, change.new)))
+            .collect();
+
+        assert_eq!(paid, vec![Some((10, 6)), None, Some((6, 1))]);
+        assert_eq!(character.mana, Some(1));
+    }
+
+    #[test]
+    fn upkeep_starts_from_a_full_pool() {
+        let mut character = Character::new_empty();
+
+        apply_upkeep(&mut character, Some(20), &[toggle(-5)]).unwrap();
+
+        assert_eq!(character.mana, Some(15));
+        assert!(apply_upkeep(&mut Character::new_empty(), None, &[toggle(-5)]).is_err());
+    }
+}
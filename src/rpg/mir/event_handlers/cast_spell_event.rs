@@ -0,0 +1,156 @@
+use poise::serenity_prelude::ButtonStyle;
+use poise::serenity_prelude::CreateButton;
+use serde::Serialize;
+
+use crate::common;
+use crate::common::Error;
+use crate::create_button_with_callback;
+use crate::db;
+
+use poise::serenity_prelude::CreateInteractionResponseFollowup;
+use poise::serenity_prelude::CreateMessage;
+use poise::serenity_prelude::EditInteractionResponse;
+use serde_json::Value;
+
+use poise::async_trait;
+
+use super::super::super::RpgError;
+use super::super::spell_list;
+use super::super::spell_sheet::SpellSheet;
+use super::super::stat_block::StatBlock;
+use super::super::undo;
+use super::super::CastOutcome;
+
+pub struct CastSpellEvent;
+
+#[derive(Serialize)]
+pub struct CastSpellEventParams {
+    pub character_id: i32,
+    /// Position in the spell list, see spell_list::spell_at
+    pub spell: usize,
+    /// The spell's spell_list::spell_id, checked before casting
+    pub id: String,
+}
+
+impl CastSpellEvent {
+    pub fn create_button(
+        text: &str,
+        params: &CastSpellEventParams,
+        button_style: ButtonStyle,
+    ) -> Result<CreateButton, Error> {
+        return create_button_with_callback::<Self, CastSpellEventParams>(
+            text,
+            params,
+            button_style,
+        );
+    }
+}
+
+/// Casts a spell from the spell list, posting the cast and refreshing the list's mana and toggles
+async fn cast_from_list(
+    ctx: &poise::serenity_prelude::Context,
+    interaction: &poise::serenity_prelude::ComponentInteraction,
+    character_id: i32,
+    index: usize,
+    id: &str,
+) -> Result<(), Error> {
+    let character = db::characters::get(character_id)?;
+
+    if !db::character_owners::is_owner(&character, interaction.user.id.get())? {
+        return Err(Box::new(RpgError::NotCharacterOwner));
+    }
+
+    let stat_block: StatBlock = crate::rpg::get_sheet(Some(ctx), &character).await?;
+    let spell_sheet: SpellSheet = crate::rpg::get_sheet(Some(ctx), &character).await?;
+
+    let (spell_key, spell) = spell_list::spell_at(&spell_sheet, index, id)?;
+
+    let colour =
+        common::get_user_colour(ctx, interaction.guild_id, interaction.user.id).await?;
+
+    match super::super::cast(
+        ctx,
+        interaction.user.id,
+        character,
+        &stat_block,
+        spell_key,
+        spell,
+        colour,
+    )
+    .await?
+    {
+        CastOutcome::Cast(embed, _, batch) => {
+            interaction
+                .channel_id
+                .send_message(
+                    ctx,
+                    CreateMessage::default()
+                        .embed(*embed)
+                        .components(undo::undo_row(batch)?),
+                )
+                .await?;
+        }
+        CastOutcome::Message(message) => {
+            interaction
+                .channel_id
+                .send_message(ctx, CreateMessage::default().content(message))
+                .await?;
+        }
+    }
+
+    let character = db::characters::get(character_id)?;
+
+    let (embed, components) = spell_list::render_page(
+        &character,
+        &stat_block,
+        &spell_sheet,
+        spell_list::page_of(&spell_sheet, spell_key),
+        colour,
+    )
+    .await?;
+
+    interaction
+        .edit_response(
+            ctx,
+            EditInteractionResponse::new()
+                .embed(embed)
+                .components(components),
+        )
+        .await?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl common::EventHandlerTrait for CastSpellEvent {
+    async fn run(
+        &self,
+        ctx: &poise::serenity_prelude::Context,
+        interaction: &poise::serenity_prelude::ComponentInteraction,
+        params: &common::ButtonParams,
+    ) {
+        if let (
+            Some(Value::Number(character_id)),
+            Some(Value::Number(index)),
+            Some(Value::String(id)),
+        ) = (
+            params.get("character_id"),
+            params.get("spell"),
+            params.get("id"),
+        ) {
+            let character_id = character_id.as_i64().expect("Should always be i64") as i32;
+            let index = index.as_u64().expect("Should always be u64") as usize;
+
+            if let Err(e) = cast_from_list(ctx, interaction, character_id, index, id).await {
+                let _ = interaction
+                    .create_followup(
+                        ctx,
+                        CreateInteractionResponseFollowup::default()
+                            .ephemeral(true)
+                            .content(format!("{e}")),
+                    )
+                    .await;
+            }
+        }
+    }
+}
@@ -72,60 +72,56 @@ impl common::EventHandlerTrait for ChangeCharacterEvent {
 
             let comparison_user_id = interaction.user.id;
 
-            if let Some(user_id) = char.user_id.clone() {
-                if &user_id.to_string() == &comparison_user_id.to_string() {
-                    user.selected_character = Some(char_id.as_i64().expect("asdasd") as i32);
-                    db::users::update(&user).expect("I hate this system");
-                    println!("aaa {char_id}");
+            if db::character_owners::is_owner(&char, comparison_user_id.get()).unwrap_or(false) {
+                user.selected_character = Some(char_id.as_i64().expect("asdasd") as i32);
+                db::users::update(&user).expect("I hate this system");
+                println!("aaa {char_id}");
 
-                    let embed = super::super::generate_status_embed(ctx, &char)
-                        .await
-                        .expect("Ffs");
+                let embed = super::super::generate_status_embed(ctx, &char)
+                    .await
+                    .expect("Ffs");
 
-                    let char_id_i32 = char_id.as_i64().expect("Wrong char id") as i32;
+                let char_id_i32 = char_id.as_i64().expect("Wrong char id") as i32;
 
-                    let char = db::characters::get(char_id_i32)
-                        .expect("Character should exist when switching");
+                let char = db::characters::get(char_id_i32)
+                    .expect("Character should exist when switching");
 
-                    let stat_block: StatBlock = crate::rpg::get_sheet(Some(&ctx), &char)
-                        .await
-                        .expect("No stat block?");
-
-                    let default_roll = &stat_block.default_roll.unwrap_or("1d100".to_string());
-
-                    let mut rows = vec![
-                        // CreateActionRow::SelectMenu(select_menu),
-                        super::super::advantage_roll_buttons(default_roll, char_id_i32),
-                    ];
-
-                    let stat_roll_buttons = super::super::stat_roll_buttons(
-                        default_roll,
-                        char_id_i32,
-                        stat_block.stats,
-                    );
-
-                    let char_select_dropdown =
-                        super::super::character_select_dropdown(user_id.parse().unwrap())
-                            .await
-                            .expect("asda");
-
-                    rows.extend(stat_roll_buttons);
-
-                    rows.push(char_select_dropdown);
-
-                    interaction
-                        .edit_followup(
-                            ctx,
-                            waiting_message_id,
-                            CreateInteractionResponseFollowup::default()
-                                .embed(embed)
-                                .content("Switched character. Current status:")
-                                .ephemeral(true)
-                                .components(rows),
-                        )
+                let stat_block: StatBlock = crate::rpg::get_sheet(Some(ctx), &char)
+                    .await
+                    .expect("No stat block?");
+
+                let default_roll =
+                    &super::super::campaigns::default_roll_for(&char, Some(&stat_block));
+
+                let mut rows = vec![
+                    // CreateActionRow::SelectMenu(select_menu),
+                    super::super::advantage_roll_buttons(default_roll, char_id_i32),
+                ];
+
+                let stat_roll_buttons =
+                    super::super::stat_roll_buttons(default_roll, char_id_i32, stat_block.stats);
+
+                let char_select_dropdown =
+                    super::super::character_select_dropdown(comparison_user_id.get())
                         .await
-                        .expect("AAA");
-                }
+                        .expect("asda");
+
+                rows.extend(stat_roll_buttons);
+
+                rows.push(char_select_dropdown);
+
+                interaction
+                    .edit_followup(
+                        ctx,
+                        waiting_message_id,
+                        CreateInteractionResponseFollowup::default()
+                            .embed(embed)
+                            .content("Switched character. Current status:")
+                            .ephemeral(true)
+                            .components(rows),
+                    )
+                    .await
+                    .expect("AAA");
             }
 
             // interaction
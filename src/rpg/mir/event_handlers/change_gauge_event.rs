@@ -0,0 +1,126 @@
+use poise::serenity_prelude::ButtonStyle;
+use poise::serenity_prelude::CreateButton;
+use serde::Serialize;
+
+use crate::common;
+use crate::common::Error;
+use crate::create_button_with_callback;
+use crate::db;
+
+use super::super::undo;
+
+use poise::serenity_prelude::CreateEmbed;
+use poise::serenity_prelude::CreateInteractionResponseFollowup;
+use poise::serenity_prelude::EditMessage;
+use serde_json::Value;
+
+use poise::async_trait;
+
+pub struct ChangeGaugeEvent;
+
+/// Keys are kept short, as button ids are limited to 100 characters and a uuid takes 36 of them
+#[derive(Serialize)]
+pub struct ChangeGaugeEventParams {
+    #[serde(rename = "g")]
+    pub gauge_id: String,
+    #[serde(rename = "c")]
+    pub change: i32,
+}
+
+impl ChangeGaugeEvent {
+    pub fn create_button(
+        text: &str,
+        params: &ChangeGaugeEventParams,
+        button_style: ButtonStyle,
+    ) -> Result<CreateButton, Error> {
+        return create_button_with_callback::<Self, ChangeGaugeEventParams>(
+            text,
+            params,
+            button_style,
+        );
+    }
+}
+
+#[async_trait]
+impl common::EventHandlerTrait for ChangeGaugeEvent {
+    async fn run(
+        &self,
+        ctx: &poise::serenity_prelude::Context,
+        interaction: &poise::serenity_prelude::ComponentInteraction,
+        params: &common::ButtonParams,
+    ) {
+        // Buttons sent before the keys were shortened still use the long ones
+        let gauge_id = params.get("g").or(params.get("gauge_id"));
+        let change = params.get("c").or(params.get("change"));
+
+        if let (Some(Value::String(gauge_id)), Some(Value::Number(change))) = (gauge_id, change) {
+            let channel_id = interaction.message.channel_id;
+            let message_id = interaction.message.id;
+
+            let Ok(gauge_id) = gauge_id.parse::<uuid::Uuid>() else {
+                println!("Invalid gauge id {gauge_id}");
+                return;
+            };
+
+            let placeholder_embed = CreateEmbed::default().description("Loading... Please wait");
+
+            channel_id
+                .edit_message(
+                    &ctx,
+                    message_id,
+                    EditMessage::default().embed(placeholder_embed),
+                )
+                .await
+                .expect("I am so tired");
+
+            let mut gauge = match db::gauges::get(gauge_id) {
+                Ok(gauge) => gauge,
+                Err(e) => {
+                    println!("Gauge {gauge_id} could not be loaded: {e}");
+                    return;
+                }
+            };
+
+            let change = change.as_i64().expect("Should always be i64") as i32;
+
+            let old_value = gauge.value;
+            let new_value = gauge.value + change;
+
+            super::super::gauges::set_gauge_value(&mut gauge, new_value)
+                .expect("Failed to update gauge");
+
+            let char = db::characters::get(gauge.player_character_id)
+                .expect("Gauge should always belong to a character");
+
+            super::super::refresh_status_message(ctx, &interaction.message, &char)
+                .await
+                .expect("I am so tired");
+
+            let confirmation = undo::record(
+                interaction.user.id,
+                &[undo::Change::new(
+                    char.id,
+                    undo::Resource::Gauge(gauge.id),
+                    old_value,
+                    gauge.value,
+                )],
+            )
+            .and_then(undo::undo_row);
+
+            match confirmation {
+                Ok(components) => {
+                    let _ = interaction
+                        .create_followup(
+                            ctx,
+                            CreateInteractionResponseFollowup::default()
+                                .ephemeral(true)
+                                .content(format!("{} {old_value} → {}", gauge.name, gauge.value))
+                                .components(components),
+                        )
+                        .await;
+                }
+                Err(e) => println!("Failed to record gauge change: {e}"),
+            }
+        }
+    }
+}
@@ -8,8 +8,10 @@ use crate::create_button_with_callback;
 use crate::db;
 
 use super::super::RpgError;
+use super::super::undo;
 
 use poise::serenity_prelude::CreateEmbed;
+use poise::serenity_prelude::CreateInteractionResponseFollowup;
 use poise::serenity_prelude::EditMessage;
 use serde_json::Value;
 
@@ -72,21 +74,40 @@ impl common::EventHandlerTrait for ChangeManaEvent {
 
                 println!("Blah");
 
+                let old_mana = char.mana.unwrap_or(0);
+
                 char.mana = Some(
-                    char.mana.unwrap_or(0)
-                        + mana_change.as_i64().expect("Should always be i64") as i32,
+                    old_mana + mana_change.as_i64().expect("Should always be i64") as i32,
                 );
 
                 db::characters::update(&char).expect("Argh");
 
-                let embed = super::super::generate_status_embed(ctx, &char)
-                    .await
-                    .expect("Ffs");
-
-                channel_id
-                    .edit_message(&ctx, message_id, EditMessage::default().embed(embed))
+                super::super::refresh_status_message(ctx, &interaction.message, &char)
                     .await
                     .expect("I am so tired");
+
+                let new_mana = char.mana.unwrap_or(0);
+
+                let confirmation = undo::record(
+                    interaction.user.id,
+                    &[undo::Change::new(char.id, undo::Resource::Mana, old_mana, new_mana)],
+                )
+                .and_then(undo::undo_row);
+
+                match confirmation {
+                    Ok(components) => {
+                        let _ = interaction
+                            .create_followup(
+                                ctx,
+                                CreateInteractionResponseFollowup::default()
+                                    .ephemeral(true)
+                                    .content(format!("Mana {old_mana} → {new_mana}"))
+                                    .components(components),
+                            )
+                            .await;
+                    }
+                    Err(e) => println!("Failed to record mana change: {e}"),
+                }
             }
         }
     }
@@ -0,0 +1,57 @@
+use poise::serenity_prelude::ButtonStyle;
+use poise::serenity_prelude::CreateButton;
+use serde::Serialize;
+
+use crate::common;
+use crate::common::Error;
+use crate::create_button_with_callback;
+use crate::db;
+
+use poise::serenity_prelude::CreateInteractionResponseFollowup;
+use serde_json::Value;
+
+use poise::async_trait;
+
+pub struct InventoryEvent;
+
+#[derive(Serialize)]
+pub struct InventoryEventParams {
+    pub character_id: i32,
+}
+
+impl InventoryEvent {
+    pub fn create_button(text: &str, params: &InventoryEventParams) -> Result<CreateButton, Error> {
+        return create_button_with_callback::<Self, InventoryEventParams>(
+            text,
+            params,
+            ButtonStyle::Secondary,
+        );
+    }
+}
+
+#[async_trait]
+impl common::EventHandlerTrait for InventoryEvent {
+    async fn run(
+        &self,
+        ctx: &poise::serenity_prelude::Context,
+        interaction: &poise::serenity_prelude::ComponentInteraction,
+        params: &common::ButtonParams,
+    ) {
+        if let Some(Value::Number(character_id)) = params.get("character_id") {
+            let character_id = character_id.as_i64().expect("Should always be i64") as i32;
+
+            let content = db::characters::get(character_id)
+                .and_then(|character| super::super::inventory::render_inventory(&character))
+                .unwrap_or_else(|e| format!("Error: {e}"));
+
+            let _ = interaction
+                .create_followup(
+                    ctx,
+                    CreateInteractionResponseFollowup::default()
+                        .ephemeral(true)
+                        .content(content),
+                )
+                .await;
+        }
+    }
+}
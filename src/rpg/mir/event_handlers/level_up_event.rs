@@ -0,0 +1,130 @@
+use poise::serenity_prelude::ButtonStyle;
+use poise::serenity_prelude::CreateButton;
+use serde::Serialize;
+
+use crate::common;
+use crate::common::Error;
+use crate::create_button_with_callback;
+use crate::db;
+
+use poise::serenity_prelude::CreateInteractionResponseFollowup;
+use poise::serenity_prelude::EditMessage;
+use serde_json::Value;
+
+use poise::async_trait;
+
+pub struct LevelUpEvent;
+
+#[derive(Serialize)]
+pub struct LevelUpEventParams {
+    pub character_id: i32,
+}
+
+impl LevelUpEvent {
+    pub fn create_button(
+        text: &str,
+        params: &LevelUpEventParams,
+        button_style: ButtonStyle,
+    ) -> Result<CreateButton, Error> {
+        return create_button_with_callback::<Self, LevelUpEventParams>(
+            text,
+            params,
+            button_style,
+        );
+    }
+}
+
+/// Levels a character up by however many levels their XP allows, returning the level up message
+async fn level_up_from_xp(
+    ctx: &poise::serenity_prelude::Context,
+    interaction: &poise::serenity_prelude::ComponentInteraction,
+    character_id: i32,
+) -> Result<Option<String>, Error> {
+    let character = db::characters::get(character_id)?;
+
+    if !db::character_owners::is_owner(&character, interaction.user.id.get())? {
+        return Ok(None);
+    }
+
+    let pending = super::super::xp::progress_of(ctx, &character)
+        .await?
+        .pending_levels();
+
+    if pending == 0 {
+        return Ok(Some("Already levelled up".to_string()));
+    }
+
+    // Remove the button first so it can't be pressed twice
+    interaction
+        .message
+        .channel_id
+        .edit_message(
+            &ctx,
+            interaction.message.id,
+            EditMessage::default()
+                .content("*Levelling up, please wait...*")
+                .components(vec![]),
+        )
+        .await?;
+
+    let outcome = super::super::level_up::level_up_character(
+        Some(ctx),
+        &character,
+        pending.min(super::super::level_up::MAX_LEVELS as i64) as i32,
+        Some(interaction.user.id.get()),
+    )
+    .await?;
+
+    Ok(Some(outcome.message))
+}
+
+#[async_trait]
+impl common::EventHandlerTrait for LevelUpEvent {
+    async fn run(
+        &self,
+        ctx: &poise::serenity_prelude::Context,
+        interaction: &poise::serenity_prelude::ComponentInteraction,
+        params: &common::ButtonParams,
+    ) {
+        if let Some(Value::Number(character_id)) = params.get("character_id") {
+            let character_id = character_id.as_i64().expect("Should always be i64") as i32;
+
+            match level_up_from_xp(ctx, interaction, character_id).await {
+                Ok(Some(content)) => {
+                    interaction
+                        .message
+                        .channel_id
+                        .edit_message(
+                            &ctx,
+                            interaction.message.id,
+                            EditMessage::default().content(content).components(vec![]),
+                        )
+                        .await
+                        .expect("Failed to edit level up message");
+                }
+                Ok(None) => {
+                    let _ = interaction
+                        .create_followup(
+                            ctx,
+                            CreateInteractionResponseFollowup::default()
+                                .ephemeral(true)
+                                .content("Only the character's owner can level them up"),
+                        )
+                        .await;
+                }
+                Err(e) => {
+                    println!("Failed to level up character {character_id}: {e}");
+
+                    let _ = interaction
+                        .create_followup(
+                            ctx,
+                            CreateInteractionResponseFollowup::default()
+                                .ephemeral(true)
+                                .content(format!("Error: {e}")),
+                        )
+                        .await;
+                }
+            }
+        }
+    }
+}
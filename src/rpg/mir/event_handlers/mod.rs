@@ -22,3 +22,53 @@ pub mod delete_message_event;
 
 pub use delete_message_event::DeleteMessageEvent;
 pub use delete_message_event::DeleteMessageEventParams;
+
+pub mod change_gauge_event;
+
+pub use change_gauge_event::ChangeGaugeEvent;
+pub use change_gauge_event::ChangeGaugeEventParams;
+
+pub mod transfer_character_event;
+
+pub use transfer_character_event::TransferCharacterEvent;
+pub use transfer_character_event::TransferCharacterEventParams;
+
+pub mod party_focus_event;
+
+pub use party_focus_event::PartyFocusEvent;
+pub use party_focus_event::PartyFocusEventParams;
+
+pub mod party_resource_event;
+
+pub use party_resource_event::PartyResourceEvent;
+pub use party_resource_event::PartyResourceEventParams;
+
+pub mod inventory_event;
+
+pub use inventory_event::InventoryEvent;
+pub use inventory_event::InventoryEventParams;
+
+pub mod level_up_event;
+
+pub use level_up_event::LevelUpEvent;
+pub use level_up_event::LevelUpEventParams;
+
+pub mod session_rsvp_event;
+
+pub use session_rsvp_event::SessionRsvpEvent;
+pub use session_rsvp_event::SessionRsvpEventParams;
+
+pub mod cast_spell_event;
+
+pub use cast_spell_event::CastSpellEvent;
+pub use cast_spell_event::CastSpellEventParams;
+
+pub mod spell_list_page_event;
+
+pub use spell_list_page_event::SpellListPageEvent;
+pub use spell_list_page_event::SpellListPageEventParams;
+
+pub mod undo_event;
+
+pub use undo_event::UndoEvent;
+pub use undo_event::UndoEventParams;
@@ -0,0 +1,74 @@
+use poise::serenity_prelude::ButtonStyle;
+use poise::serenity_prelude::CreateButton;
+use poise::serenity_prelude::CreateSelectMenuOption;
+use serde::Serialize;
+
+use crate::common;
+use crate::common::Error;
+use crate::create_button_with_callback;
+use crate::create_select_option_with_callback;
+
+use poise::serenity_prelude::CreateInteractionResponseFollowup;
+use serde_json::Value;
+
+use poise::async_trait;
+
+/// Picks a character on the party dashboard, or just refreshes it when character_id is None
+pub struct PartyFocusEvent;
+
+#[derive(Serialize)]
+pub struct PartyFocusEventParams {
+    pub character_id: Option<i32>,
+}
+
+impl PartyFocusEvent {
+    pub fn create_button(text: &str, params: &PartyFocusEventParams) -> Result<CreateButton, Error> {
+        return create_button_with_callback::<Self, PartyFocusEventParams>(
+            text,
+            params,
+            ButtonStyle::Primary,
+        );
+    }
+
+    pub fn create_select_item(
+        text: &str,
+        params: &PartyFocusEventParams,
+    ) -> Result<CreateSelectMenuOption, Error> {
+        return create_select_option_with_callback::<Self, PartyFocusEventParams>(text, params);
+    }
+}
+
+#[async_trait]
+impl common::EventHandlerTrait for PartyFocusEvent {
+    async fn run(
+        &self,
+        ctx: &poise::serenity_prelude::Context,
+        interaction: &poise::serenity_prelude::ComponentInteraction,
+        params: &common::ButtonParams,
+    ) {
+        let character_id = match params.get("character_id") {
+            Some(Value::Number(character_id)) => character_id.as_i64().map(|id| id as i32),
+            _ => None,
+        };
+
+        let result = super::super::party::update_dashboard(
+            ctx,
+            interaction.message.channel_id,
+            interaction.message.id,
+            interaction.user.id,
+            character_id,
+        )
+        .await;
+
+        if let Err(e) = result {
+            let _ = interaction
+                .create_followup(
+                    ctx,
+                    CreateInteractionResponseFollowup::default()
+                        .ephemeral(true)
+                        .content(format!("Error: {e}")),
+                )
+                .await;
+        }
+    }
+}
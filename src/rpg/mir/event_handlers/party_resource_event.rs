@@ -0,0 +1,125 @@
+use poise::serenity_prelude::ButtonStyle;
+use poise::serenity_prelude::CreateButton;
+use serde::Serialize;
+
+use crate::common;
+use crate::common::Error;
+use crate::create_button_with_callback;
+
+use poise::serenity_prelude::CreateInteractionResponseFollowup;
+use serde_json::Value;
+
+use poise::async_trait;
+
+use super::super::party::PartyResource;
+use super::super::undo;
+
+pub struct PartyResourceEvent;
+
+#[derive(Serialize)]
+pub struct PartyResourceEventParams {
+    pub character_id: i32,
+    pub resource: String,
+    pub change: i32,
+}
+
+impl PartyResourceEvent {
+    pub fn create_button(
+        text: &str,
+        params: &PartyResourceEventParams,
+        button_style: ButtonStyle,
+    ) -> Result<CreateButton, Error> {
+        return create_button_with_callback::<Self, PartyResourceEventParams>(
+            text,
+            params,
+            button_style,
+        );
+    }
+}
+
+#[async_trait]
+impl common::EventHandlerTrait for PartyResourceEvent {
+    async fn run(
+        &self,
+        ctx: &poise::serenity_prelude::Context,
+        interaction: &poise::serenity_prelude::ComponentInteraction,
+        params: &common::ButtonParams,
+    ) {
+        if let (
+            Some(Value::Number(character_id)),
+            Some(Value::String(resource)),
+            Some(Value::Number(change)),
+        ) = (
+            params.get("character_id"),
+            params.get("resource"),
+            params.get("change"),
+        ) {
+            let Some(resource) = PartyResource::from_str(resource) else {
+                println!("Unknown party resource {resource}");
+                return;
+            };
+
+            let character_id = character_id.as_i64().expect("Should always be i64") as i32;
+            let change = change.as_i64().expect("Should always be i64") as i32;
+
+            let channel_id = interaction.message.channel_id;
+            let message_id = interaction.message.id;
+
+            let result = async {
+                let batch = super::super::party::change_resource(
+                    ctx,
+                    message_id,
+                    interaction.user.id,
+                    character_id,
+                    resource,
+                    change,
+                )
+                .await?;
+
+                super::super::party::update_dashboard(
+                    ctx,
+                    channel_id,
+                    message_id,
+                    interaction.user.id,
+                    None,
+                )
+                .await?;
+
+                undo::undo_row(batch)
+            }
+            .await;
+
+            match result {
+                // Only offered when something changed, a change clamped at 0 or max has nothing to undo
+                Ok(components) if !components.is_empty() => {
+                    let _ = interaction
+                        .create_followup(
+                            ctx,
+                            CreateInteractionResponseFollowup::default()
+                                .ephemeral(true)
+                                .content(format!(
+                                    "Changed {} by {change:+}",
+                                    match resource {
+                                        PartyResource::Hp => "HP",
+                                        PartyResource::Mana => "mana",
+                                    }
+                                ))
+                                .components(components),
+                        )
+                        .await;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    let _ = interaction
+                        .create_followup(
+                            ctx,
+                            CreateInteractionResponseFollowup::default()
+                                .ephemeral(true)
+                                .content(format!("Error: {e}")),
+                        )
+                        .await;
+                }
+            }
+        }
+    }
+}
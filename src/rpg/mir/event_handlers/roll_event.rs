@@ -10,6 +10,7 @@ use crate::create_button_with_callback;
 use crate::create_select_option_with_callback;
 use crate::db;
 use crate::rpg::mir::get_roll_channel;
+use crate::rpg::mir::roll_mode::RollMode;
 
 use poise::serenity_prelude::CreateMessage;
 use serde_json::Value;
@@ -22,6 +23,8 @@ pub struct RollEvent;
 pub struct RollEventParams {
     pub dice_string: String,
     pub character_id: i32,
+    #[serde(skip_serializing_if = "RollMode::is_normal")]
+    pub mode: RollMode,
 }
 
 impl RollEvent {
@@ -63,10 +66,17 @@ impl common::EventHandlerTrait for RollEvent {
             )
             .expect("Remove this expect later");
 
+            let mode = params
+                .get("mode")
+                .and_then(|mode| mode.as_str())
+                .and_then(RollMode::from_str)
+                .unwrap_or_default();
+
             let result_or_err = crate::rpg::mir::roll_with_char_sheet(
                 Some(ctx),
                 Some(dice_string.to_string()),
                 &char,
+                mode,
             )
             .await;
 
@@ -0,0 +1,72 @@
+use poise::serenity_prelude::ButtonStyle;
+use poise::serenity_prelude::CreateButton;
+use serde::Serialize;
+
+use crate::common;
+use crate::common::Error;
+use crate::create_button_with_callback;
+
+use poise::serenity_prelude::CreateInteractionResponseFollowup;
+use serde_json::Value;
+
+use poise::async_trait;
+
+use super::super::sessions;
+use super::super::sessions::RsvpStatus;
+
+pub struct SessionRsvpEvent;
+
+#[derive(Serialize)]
+pub struct SessionRsvpEventParams {
+    pub session_id: i32,
+    pub status: String,
+}
+
+impl SessionRsvpEvent {
+    pub fn create_button(
+        text: &str,
+        params: &SessionRsvpEventParams,
+        button_style: ButtonStyle,
+    ) -> Result<CreateButton, Error> {
+        return create_button_with_callback::<Self, SessionRsvpEventParams>(
+            text,
+            params,
+            button_style,
+        );
+    }
+}
+
+#[async_trait]
+impl common::EventHandlerTrait for SessionRsvpEvent {
+    async fn run(
+        &self,
+        ctx: &poise::serenity_prelude::Context,
+        interaction: &poise::serenity_prelude::ComponentInteraction,
+        params: &common::ButtonParams,
+    ) {
+        let (Some(Value::Number(session_id)), Some(Value::String(status))) =
+            (params.get("session_id"), params.get("status"))
+        else {
+            return;
+        };
+
+        let session_id = session_id.as_i64().expect("Should always be i64") as i32;
+
+        let Some(status) = RsvpStatus::from_str(status) else {
+            return;
+        };
+
+        if let Err(e) = sessions::rsvp(ctx, session_id, interaction.user.id.get(), status).await {
+            println!("Failed to RSVP to session {session_id}: {e}");
+
+            let _ = interaction
+                .create_followup(
+                    ctx,
+                    CreateInteractionResponseFollowup::default()
+                        .ephemeral(true)
+                        .content(format!("Error: {e}")),
+                )
+                .await;
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use poise::serenity_prelude::ButtonStyle;
+use poise::serenity_prelude::CreateButton;
+use serde::Serialize;
+
+use crate::common;
+use crate::common::Error;
+use crate::create_button_with_callback;
+use crate::db;
+
+use poise::serenity_prelude::CreateInteractionResponseFollowup;
+use poise::serenity_prelude::EditInteractionResponse;
+use serde_json::Value;
+
+use poise::async_trait;
+
+use super::super::spell_list;
+use super::super::spell_sheet::SpellSheet;
+use super::super::stat_block::StatBlock;
+
+pub struct SpellListPageEvent;
+
+#[derive(Serialize)]
+pub struct SpellListPageEventParams {
+    pub character_id: i32,
+    pub page: usize,
+}
+
+impl SpellListPageEvent {
+    pub fn create_button(
+        text: &str,
+        params: &SpellListPageEventParams,
+        button_style: ButtonStyle,
+    ) -> Result<CreateButton, Error> {
+        return create_button_with_callback::<Self, SpellListPageEventParams>(
+            text,
+            params,
+            button_style,
+        );
+    }
+}
+
+async fn show_page(
+    ctx: &poise::serenity_prelude::Context,
+    interaction: &poise::serenity_prelude::ComponentInteraction,
+    character_id: i32,
+    page: usize,
+) -> Result<(), Error> {
+    let character = db::characters::get(character_id)?;
+
+    let stat_block: StatBlock = crate::rpg::get_sheet(Some(ctx), &character).await?;
+    let spell_sheet: SpellSheet = crate::rpg::get_sheet(Some(ctx), &character).await?;
+
+    let colour =
+        common::get_user_colour(ctx, interaction.guild_id, interaction.user.id).await?;
+
+    let (embed, components) =
+        spell_list::render_page(&character, &stat_block, &spell_sheet, page, colour).await?;
+
+    interaction
+        .edit_response(
+            ctx,
+            EditInteractionResponse::new()
+                .embed(embed)
+                .components(components),
+        )
+        .await?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl common::EventHandlerTrait for SpellListPageEvent {
+    async fn run(
+        &self,
+        ctx: &poise::serenity_prelude::Context,
+        interaction: &poise::serenity_prelude::ComponentInteraction,
+        params: &common::ButtonParams,
+    ) {
+        if let (Some(Value::Number(character_id)), Some(Value::Number(page))) =
+            (params.get("character_id"), params.get("page"))
+        {
+            let character_id = character_id.as_i64().expect("Should always be i64") as i32;
+            let page = page.as_u64().expect("Should always be u64") as usize;
+
+            if let Err(e) = show_page(ctx, interaction, character_id, page).await {
+                let _ = interaction
+                    .create_followup(
+                        ctx,
+                        CreateInteractionResponseFollowup::default()
+                            .ephemeral(true)
+                            .content(format!("{e}")),
+                    )
+                    .await;
+            }
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use poise::serenity_prelude::ButtonStyle;
+use poise::serenity_prelude::CreateButton;
+use serde::Serialize;
+
+use crate::common;
+use crate::common::Error;
+use crate::create_button_with_callback;
+
+use poise::serenity_prelude::CreateInteractionResponseFollowup;
+use poise::serenity_prelude::EditMessage;
+use serde_json::Value;
+
+use poise::async_trait;
+
+pub struct TransferCharacterEvent;
+
+#[derive(Serialize)]
+pub struct TransferCharacterEventParams {
+    pub character_id: i32,
+    pub accept: bool,
+}
+
+impl TransferCharacterEvent {
+    pub fn create_button(
+        text: &str,
+        params: &TransferCharacterEventParams,
+        button_style: ButtonStyle,
+    ) -> Result<CreateButton, Error> {
+        return create_button_with_callback::<Self, TransferCharacterEventParams>(
+            text,
+            params,
+            button_style,
+        );
+    }
+}
+
+#[async_trait]
+impl common::EventHandlerTrait for TransferCharacterEvent {
+    async fn run(
+        &self,
+        ctx: &poise::serenity_prelude::Context,
+        interaction: &poise::serenity_prelude::ComponentInteraction,
+        params: &common::ButtonParams,
+    ) {
+        if let (Some(Value::Number(character_id)), Some(Value::Bool(accept))) =
+            (params.get("character_id"), params.get("accept"))
+        {
+            let character_id = character_id.as_i64().expect("Should always be i64") as i32;
+
+            let result = super::super::ownership::resolve_transfer(
+                character_id,
+                interaction.user.id.get(),
+                *accept,
+            )
+            .await;
+
+            match result {
+                Ok(Some(content)) => {
+                    interaction
+                        .message
+                        .channel_id
+                        .edit_message(
+                            &ctx,
+                            interaction.message.id,
+                            EditMessage::default().content(content).components(vec![]),
+                        )
+                        .await
+                        .expect("Failed to edit transfer message");
+                }
+                Ok(None) => {
+                    let _ = interaction
+                        .create_followup(
+                            ctx,
+                            CreateInteractionResponseFollowup::default()
+                                .ephemeral(true)
+                                .content("This transfer isn't for you"),
+                        )
+                        .await;
+                }
+                Err(e) => {
+                    println!("Failed to transfer character {character_id}: {e}");
+
+                    let _ = interaction
+                        .create_followup(
+                            ctx,
+                            CreateInteractionResponseFollowup::default()
+                                .ephemeral(true)
+                                .content(format!("Error: {e}")),
+                        )
+                        .await;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use poise::serenity_prelude::ButtonStyle;
+use poise::serenity_prelude::CreateButton;
+use serde::Serialize;
+
+use crate::common;
+use crate::common::Error;
+use crate::create_button_with_callback;
+
+use poise::serenity_prelude::CreateInteractionResponseFollowup;
+use poise::serenity_prelude::EditInteractionResponse;
+use serde_json::Value;
+
+use poise::async_trait;
+
+use super::super::undo;
+
+pub struct UndoEvent;
+
+#[derive(Serialize)]
+pub struct UndoEventParams {
+    pub batch_id: String,
+}
+
+impl UndoEvent {
+    pub fn create_button(
+        text: &str,
+        params: &UndoEventParams,
+        button_style: ButtonStyle,
+    ) -> Result<CreateButton, Error> {
+        return create_button_with_callback::<Self, UndoEventParams>(
+            text,
+            params,
+            button_style,
+        );
+    }
+}
+
+#[async_trait]
+impl common::EventHandlerTrait for UndoEvent {
+    async fn run(
+        &self,
+        ctx: &poise::serenity_prelude::Context,
+        interaction: &poise::serenity_prelude::ComponentInteraction,
+        params: &common::ButtonParams,
+    ) {
+        if let Some(Value::String(batch_id)) = params.get("batch_id") {
+            let Ok(batch) = batch_id.parse::<uuid::Uuid>() else {
+                println!("Invalid undo batch id {batch_id}");
+                return;
+            };
+
+            match undo::undo_batch(ctx, batch, interaction.user.id).await {
+                Ok(lines) => {
+                    // The button is the only component on confirmation messages, so it can just go
+                    let _ = interaction
+                        .edit_response(ctx, EditInteractionResponse::new().components(vec![]))
+                        .await;
+
+                    let _ = interaction
+                        .create_followup(
+                            ctx,
+                            CreateInteractionResponseFollowup::default()
+                                .content(undo::describe(&lines)),
+                        )
+                        .await;
+                }
+                Err(e) => {
+                    let _ = interaction
+                        .create_followup(
+                            ctx,
+                            CreateInteractionResponseFollowup::default()
+                                .ephemeral(true)
+                                .content(format!("Error: {e}")),
+                        )
+                        .await;
+                }
+            }
+        }
+    }
+}
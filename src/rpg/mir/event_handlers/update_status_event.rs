@@ -76,12 +76,7 @@ impl common::EventHandlerTrait for UpdateStatusEvent {
 
             println!("{}", char_id);
 
-            let embed = super::super::generate_status_embed(ctx, &char)
-                .await
-                .expect("Ffs");
-
-            channel_id
-                .edit_message(&ctx, message_id, EditMessage::default().embed(embed))
+            super::super::refresh_status_message(ctx, &interaction.message, &char)
                 .await
                 .expect("I am so tired");
         }
@@ -0,0 +1,434 @@
+use poise::serenity_prelude::Attachment;
+use poise::serenity_prelude::CreateAttachment;
+use poise::CreateReply;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::common::Context;
+use crate::common::Error;
+use crate::db;
+use crate::db::models::Character;
+use crate::db::models::NewCondition;
+use crate::db::models::NewGauge;
+use crate::db::models::NewItem;
+use crate::db::models::NewXpAward;
+
+use super::super::get_sheet;
+use super::super::get_user_character;
+use super::super::CharacterSheetable;
+use super::super::RpgError;
+use super::conditions;
+use super::gauges;
+use super::revisions;
+use super::revisions::RevisionSource;
+use super::spell_sheet::SpellSheet;
+use super::spells::ManaSpellResource;
+use super::spells::Spell;
+use super::spells::SpellType;
+use super::stat_block::StatBlock;
+use super::ACTIVE_SPELLS;
+
+/// Bump this whenever the export format changes in a way older imports can't read
+pub const EXPORT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+pub struct ExportedGauge {
+    pub name: String,
+    pub icon: Option<String>,
+    pub colour: Option<String>,
+    pub value: i32,
+    pub max: i32,
+    pub gauge_type: i32,
+    pub rate: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ExportedSpell {
+    pub name: Option<String>,
+    pub mana_per_turn: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ExportedCondition {
+    pub name: String,
+    pub modifier: Option<String>,
+    pub target: Option<String>,
+    pub disadvantage: bool,
+    pub turns_remaining: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ExportedItem {
+    pub name: String,
+    pub quantity: i32,
+    pub weight: Option<f64>,
+    pub equipped_slot: Option<String>,
+    pub stat_bonuses: Option<String>,
+    pub saved_rolls: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CharacterExport {
+    pub version: u32,
+    pub exported_at: String,
+    pub character: Character,
+    pub stat_block: Option<Value>,
+    pub spell_block: Option<Value>,
+    pub saved_rolls: Option<String>,
+    #[serde(default)]
+    pub gauges: Vec<ExportedGauge>,
+    #[serde(default)]
+    pub active_spells: Vec<ExportedSpell>,
+    #[serde(default)]
+    pub conditions: Vec<ExportedCondition>,
+    #[serde(default)]
+    pub items: Vec<ExportedItem>,
+    #[serde(default)]
+    pub xp: i64,
+}
+
+fn parsed_block<T: CharacterSheetable>(sheet: Option<T>) -> Option<Value> {
+    sheet.and_then(|sheet| sheet.sheet_info().deserialized_message.clone())
+}
+
+pub async fn export_character(
+    ctx: &poise::serenity_prelude::Context,
+    character: &Character,
+) -> Result<CharacterExport, Error> {
+    let stat_block: Option<StatBlock> = get_sheet(Some(ctx), character).await.ok();
+    let spell_block: Option<SpellSheet> = get_sheet(Some(ctx), character).await.ok();
+
+    let gauges = db::gauges::get_for_character(character.id)?
+        .into_iter()
+        .map(|gauge| ExportedGauge {
+            name: gauge.name,
+            icon: gauge.icon,
+            colour: gauge.colour,
+            value: gauge.value,
+            max: gauge.max,
+            gauge_type: gauge.gauge_type,
+            rate: gauge.rate,
+        })
+        .collect();
+
+    let conditions = db::conditions::get_for_character(character.id)?
+        .into_iter()
+        .map(|condition| ExportedCondition {
+            name: condition.name,
+            modifier: condition.modifier,
+            target: condition.target,
+            disadvantage: condition.disadvantage,
+            turns_remaining: condition.turns_remaining,
+        })
+        .collect();
+
+    let items = db::items::get_for_character(character.id)?
+        .into_iter()
+        .map(|item| ExportedItem {
+            name: item.name,
+            quantity: item.quantity,
+            weight: item.weight,
+            equipped_slot: item.equipped_slot,
+            stat_bonuses: item.stat_bonuses,
+            saved_rolls: item.saved_rolls,
+        })
+        .collect();
+
+    let active_spells = ACTIVE_SPELLS
+        .lock()
+        .await
+        .get(&character.id)
+        .map(|spells| {
+            spells
+                .iter()
+                .map(|spell| ExportedSpell {
+                    name: spell.name.clone(),
+                    mana_per_turn: spell.cost.as_ref().map(|cost| cost.mana),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(CharacterExport {
+        version: EXPORT_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        character: character.clone(),
+        stat_block: parsed_block(stat_block),
+        spell_block: parsed_block(spell_block),
+        saved_rolls: character.saved_rolls.clone(),
+        gauges,
+        active_spells,
+        conditions,
+        items,
+        xp: db::xp::total(character.id)?,
+    })
+}
+
+/// Creates a new character owned by user_id from an export
+/// Discord message links aren't carried over, so the imported sheets are read from the stored JSON
+pub async fn import_character(
+    export: CharacterExport,
+    user_id: u64,
+    roll_server_id: Option<String>,
+) -> Result<Character, Error> {
+    if export.version != EXPORT_VERSION {
+        return Err(Box::new(RpgError::UnsupportedExportVersion));
+    }
+
+    let stat_block = export
+        .stat_block
+        .or(export
+            .character
+            .stat_block
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok()))
+        .ok_or(RpgError::InvalidCharacterExport)?;
+
+    // Make sure the stat block is usable before anything is saved
+    let stat_block_json = serde_json::to_string(&stat_block)?;
+    StatBlock::from_json(None, &stat_block_json)?;
+
+    let spell_block_json = match export.spell_block {
+        Some(spell_block) => Some(serde_json::to_string(&spell_block)?),
+        None => export.character.spell_block.clone(),
+    };
+
+    let new_character = Character {
+        id: 0,
+        user_id: Some(user_id.to_string()),
+        name: export.character.name.clone(),
+
+        roll_server_id,
+
+        stat_block_hash: None,
+        stat_block: Some(stat_block_json),
+        stat_block_message_id: None,
+        stat_block_channel_id: None,
+
+        spell_block_channel_id: None,
+        spell_block_message_id: None,
+        spell_block: spell_block_json,
+        spell_block_hash: None,
+
+        mana: export.character.mana,
+        mana_readout_channel_id: None,
+        mana_readout_message_id: None,
+
+        saved_rolls: export.saved_rolls.or(export.character.saved_rolls.clone()),
+        stat_block_server_id: None,
+
+        // Campaign ids only mean something on the bot the character was exported from,
+        // here they could belong to anyone's campaign
+        campaign_id: None,
+    };
+
+    // Everything is checked the way the commands that make it would, before any of it is saved
+    let gauges = export
+        .gauges
+        .into_iter()
+        .map(|gauge| {
+            let mut new_gauge = NewGauge {
+                id: uuid::Uuid::new_v4(),
+                icon: gauge.icon,
+                name: gauge.name,
+                value: gauge.value,
+                max: gauge.max,
+                player_character_id: 0,
+                gauge_type: gauge.gauge_type,
+                colour: gauge.colour,
+                rate: gauge.rate,
+            };
+
+            gauges::check_new_gauge(&mut new_gauge)?;
+
+            Ok(new_gauge)
+        })
+        .collect::<Result<Vec<NewGauge>, Error>>()?;
+
+    let conditions = export
+        .conditions
+        .into_iter()
+        .map(|condition| {
+            let mut new_condition = NewCondition {
+                character_id: 0,
+                name: condition.name,
+                modifier: condition.modifier,
+                target: condition.target,
+                disadvantage: condition.disadvantage,
+                turns_remaining: condition.turns_remaining,
+            };
+
+            conditions::check_new_condition(&mut new_condition)?;
+
+            Ok(new_condition)
+        })
+        .collect::<Result<Vec<NewCondition>, Error>>()?;
+
+    let items = export
+        .items
+        .into_iter()
+        .map(|item| NewItem {
+            character_id: None,
+            campaign_id: None,
+            name: item.name,
+            quantity: item.quantity,
+            weight: item.weight,
+            equipped_slot: item.equipped_slot,
+            stat_bonuses: item.stat_bonuses,
+            saved_rolls: item.saved_rolls,
+        })
+        .collect();
+
+    let xp = i32::try_from(export.xp).map_err(|_| RpgError::InvalidCharacterExport)?;
+
+    let xp = (xp != 0).then(|| NewXpAward {
+        character_id: 0,
+        amount: xp,
+        reason: Some("Imported".to_string()),
+        author_id: user_id.to_string(),
+    });
+
+    let character = db::characters::import(&new_character, gauges, conditions, items, xp)?;
+
+    revisions::record_changes(
+        &Character::new_empty(),
+        &character,
+        Some(user_id),
+        RevisionSource::Import,
+    )?;
+
+    if !export.active_spells.is_empty() {
+        let spells = export
+            .active_spells
+            .into_iter()
+            .map(|spell| Spell {
+                name: spell.name,
+                cost: spell.mana_per_turn.map(|mana| ManaSpellResource { mana }),
+                spell_type: Some(SpellType::Toggle),
+                cast_time: None,
+                damage: None,
+                healing: None,
+                save_stat: None,
+                targets: None,
+                description: None,
+            })
+            .collect();
+
+        ACTIVE_SPELLS.lock().await.insert(character.id, spells);
+    }
+
+    Ok(character)
+}
+
+fn export_file_name(character: &Character) -> String {
+    let name: String = character
+        .name
+        .clone()
+        .unwrap_or("character".to_string())
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+
+    format!("{name}.json")
+}
+
+/// Attaches your selected character as a JSON file, for backups or sharing pre-gens
+#[poise::command(slash_command, prefix_command, rename = "export_character")]
+pub async fn export_character_command(ctx: Context<'_>) -> Result<(), Error> {
+    let placeholder = ctx
+        .send(
+            CreateReply::default()
+                .content("*Thinking, please wait...*")
+                .ephemeral(true),
+        )
+        .await?;
+
+    let character = get_user_character(&ctx)
+        .await?
+        .ok_or(RpgError::NoCharacterSelected)?;
+
+    let export = export_character(ctx.serenity_context(), &character).await?;
+
+    let json = serde_json::to_string_pretty(&export)?;
+
+    // Attachments can't be added by editing, so the placeholder is replaced
+    placeholder.delete(ctx).await?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "Exported {} - import it with /import_character",
+                character.name.clone().unwrap_or("No name?".to_string())
+            ))
+            .attachment(CreateAttachment::bytes(
+                json.into_bytes(),
+                export_file_name(&character),
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Creates a new character for you from a /export_character file
+#[poise::command(slash_command, prefix_command, rename = "import_character")]
+pub async fn import_character_command(ctx: Context<'_>, file: Attachment) -> Result<(), Error> {
+    let placeholder = ctx
+        .send(
+            CreateReply::default()
+                .content("*Thinking, please wait...*")
+                .ephemeral(true),
+        )
+        .await?;
+
+    let bytes = file.download().await?;
+
+    let raw: Value = serde_json::from_slice(&bytes).map_err(|_| RpgError::InvalidCharacterExport)?;
+
+    // Check the version first so newer exports give a useful error rather than a parse failure
+    match raw.get("version").and_then(|version| version.as_u64()) {
+        Some(version) if version == EXPORT_VERSION as u64 => {}
+        Some(_) => return Err(Box::new(RpgError::UnsupportedExportVersion)),
+        None => return Err(Box::new(RpgError::InvalidCharacterExport)),
+    }
+
+    let export: CharacterExport =
+        serde_json::from_value(raw).map_err(|_| RpgError::InvalidCharacterExport)?;
+
+    let user_id = ctx.author().id.get();
+
+    let character = import_character(
+        export,
+        user_id,
+        ctx.guild_id().map(|id| id.to_string()),
+    )
+    .await?;
+
+    let mut user = db::users::get_or_create(user_id)?;
+
+    let mut extra_text = "";
+
+    if user.selected_character.is_none() {
+        user.selected_character = Some(character.id);
+        db::users::update(&user)?;
+
+        extra_text = "(and selected as default)";
+    }
+
+    placeholder
+        .edit(
+            ctx,
+            CreateReply::default()
+                .content(format!(
+                    "Imported {} as character id {} {extra_text}",
+                    character.name.clone().unwrap_or("No name?".to_string()),
+                    character.id
+                ))
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
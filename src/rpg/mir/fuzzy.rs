@@ -0,0 +1,177 @@
+/// Most choices discord will show in an autocomplete
+pub static MAX_SUGGESTIONS: usize = 25;
+
+/// Lowercases and joins words with underscores, the way spell and stat keys are stored
+pub fn normalise(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == '_' || c == '-')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>()
+        .join("_")
+}
+
+/// Optimal string alignment distance - edits needed to turn one string into the other,
+/// where swapping two neighbouring letters counts as a single typo
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = (a[i - 1] != b[j - 1]) as usize;
+
+            rows[i][j] = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                rows[i][j] = rows[i][j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+/// Typos tolerated in a query, so short queries don't match everything
+fn max_typos(length: usize) -> usize {
+    match length {
+        0..=2 => 0,
+        3..=5 => 1,
+        6..=9 => 2,
+        _ => 3,
+    }
+}
+
+/// How well a candidate matches a query - lower is better, None if it doesn't match
+fn score(query: &str, candidate: &str) -> Option<usize> {
+    let candidate = normalise(candidate);
+
+    if query.is_empty() || candidate == query {
+        return Some(0);
+    }
+    if candidate.starts_with(query) {
+        return Some(1);
+    }
+    if candidate.contains(query) {
+        return Some(2);
+    }
+
+    // Compared against the start of the candidate too, so a half typed name with a typo still matches
+    let start: String = candidate.chars().take(query.chars().count()).collect();
+    let typos = distance(query, &candidate).min(distance(query, &start));
+
+    (typos <= max_typos(query.chars().count())).then_some(3 + typos)
+}
+
+/// Like score, but only for exact names or typos of a whole name - no partial matches
+fn typo_score(query: &str, candidate: &str) -> Option<usize> {
+    let candidate = normalise(candidate);
+
+    if candidate == query {
+        return Some(0);
+    }
+
+    let typos = distance(query, &candidate);
+
+    (typos <= max_typos(query.chars().count())).then_some(3 + typos)
+}
+
+fn scored<'a>(
+    query: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+    score: fn(&str, &str) -> Option<usize>,
+) -> Vec<(usize, &'a str)> {
+    let query = normalise(query);
+
+    let mut matches: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .filter_map(|candidate| score(&query, candidate).map(|score| (score, candidate)))
+        .collect();
+
+    matches.sort_by(|(a_score, a), (b_score, b)| a_score.cmp(b_score).then(a.cmp(b)));
+
+    matches
+}
+
+/// Candidates matching a partially typed query, best first
+pub fn rank<'a>(query: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    scored(query, candidates, score)
+        .into_iter()
+        .map(|(_, candidate)| candidate)
+        .take(MAX_SUGGESTIONS)
+        .collect()
+}
+
+/// The one candidate a query means, allowing for typos -
+/// None if nothing is close enough or several candidates are equally close
+pub fn resolve<'a>(query: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    best(scored(query, candidates, score))
+}
+
+/// The one candidate a query names exactly or with a typo - partially typed names don't count
+pub fn resolve_typo<'a>(
+    query: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    best(scored(query, candidates, typo_score))
+}
+
+fn best(matches: Vec<(usize, &str)>) -> Option<&str> {
+    match matches.as_slice() {
+        [(_, only)] => Some(only),
+        [(best, candidate), (next, _), ..] if best < next || *best == 0 => Some(candidate),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static SPELLS: [&str; 4] = ["fireball", "firebolt", "invisibility", "cure_wounds"];
+
+    #[test]
+    fn test_exact_and_spaced_names() {
+        assert_eq!(resolve("Fireball", SPELLS), Some("fireball"));
+        assert_eq!(resolve("cure wounds", SPELLS), Some("cure_wounds"));
+    }
+
+    #[test]
+    fn test_typos() {
+        assert_eq!(resolve("firebal", SPELLS), Some("fireball"));
+        assert_eq!(resolve("invisiblity", SPELLS), Some("invisibility"));
+        assert_eq!(resolve("cuer wounds", SPELLS), Some("cure_wounds"));
+    }
+
+    #[test]
+    fn test_ambiguous_and_unknown() {
+        assert_eq!(resolve("fire", SPELLS), None);
+        assert_eq!(resolve("teleport", SPELLS), None);
+    }
+
+    #[test]
+    fn test_typos_only() {
+        assert_eq!(resolve_typo("fireball", SPELLS), Some("fireball"));
+        assert_eq!(resolve_typo("fierball", SPELLS), Some("fireball"));
+        assert_eq!(resolve_typo("invis", SPELLS), None);
+        assert_eq!(resolve_typo("wounds", SPELLS), None);
+    }
+
+    #[test]
+    fn test_rank() {
+        assert_eq!(rank("fire", SPELLS), vec!["fireball", "firebolt"]);
+        assert_eq!(rank("", SPELLS).len(), SPELLS.len());
+        assert_eq!(rank("wound", SPELLS), vec!["cure_wounds"]);
+    }
+}
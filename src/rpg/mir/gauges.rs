@@ -0,0 +1,445 @@
+use poise::serenity_prelude::ButtonStyle;
+use poise::serenity_prelude::CreateActionRow;
+use poise::ChoiceParameter;
+use poise::CreateReply;
+
+use crate::common::Context;
+use crate::common::Error;
+use crate::db;
+use crate::db::models::Gauge;
+use crate::db::models::NewGauge;
+
+use super::super::get_user_character;
+use super::super::RpgError;
+use super::color_matcher;
+use super::event_handlers::ChangeGaugeEvent;
+use super::event_handlers::ChangeGaugeEventParams;
+use super::undo;
+use super::BAR_LENGTH;
+
+/// How a gauge behaves when turns end and characters rest, stored in Gauges.GaugeType
+#[derive(Debug, Clone, Copy, PartialEq, poise::ChoiceParameter)]
+pub enum GaugeType {
+    #[name = "Plain"]
+    Plain = 0,
+    #[name = "Regenerates per turn"]
+    Regen = 1,
+    #[name = "Decays per turn"]
+    Decay = 2,
+    #[name = "Resets on short rest"]
+    ShortRestReset = 3,
+    #[name = "Resets on long rest"]
+    LongRestReset = 4,
+    #[name = "Can overflow max"]
+    Overflow = 5,
+}
+
+impl From<i32> for GaugeType {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => GaugeType::Regen,
+            2 => GaugeType::Decay,
+            3 => GaugeType::ShortRestReset,
+            4 => GaugeType::LongRestReset,
+            5 => GaugeType::Overflow,
+            _ => GaugeType::Plain,
+        }
+    }
+}
+
+impl Gauge {
+    pub fn behaviour(&self) -> GaugeType {
+        GaugeType::from(self.gauge_type)
+    }
+
+    /// Regen/decay amount per turn - a rate of 0 leaves the gauge where it is
+    pub fn rate_per_turn(&self) -> i32 {
+        self.rate.abs()
+    }
+}
+
+/// Evaluate a modifier expression against a current value
+/// 'n' is replaced with the current value (e.g. "n-20", "n*2"),
+/// expressions starting with + or - are treated as relative ("-20" is the same as "n-20")
+/// and anything else sets the value outright
+pub fn eval_modifier(expression: &str, current: i32) -> Result<i32, Error> {
+    let expression = expression.trim();
+
+    let expression = if expression.starts_with('+') || expression.starts_with('-') {
+        format!("n{expression}")
+    } else {
+        expression.to_string()
+    };
+
+    Ok(crate::formula::evaluate_with(&expression, &[("n", current as f64)])? as i32)
+}
+
+/// Keeps a value within what a gauge can hold - overflow gauges can go past their max
+pub fn clamp_gauge_value(gauge: &Gauge, value: i32) -> i32 {
+    clamp_to(gauge.behaviour(), gauge.max, value)
+}
+
+fn clamp_to(behaviour: GaugeType, max: i32, value: i32) -> i32 {
+    if behaviour == GaugeType::Overflow {
+        value.max(0)
+    } else {
+        value.clamp(0, max.max(0))
+    }
+}
+
+/// Checks a gauge before it's created - its max has to be positive, and its value is kept within what it can hold
+pub fn check_new_gauge(gauge: &mut NewGauge) -> Result<(), Error> {
+    if gauge.max <= 0 {
+        return Err(Box::new(RpgError::InvalidGaugeMax));
+    }
+
+    gauge.value = clamp_to(GaugeType::from(gauge.gauge_type), gauge.max, gauge.value);
+
+    Ok(())
+}
+
+/// Sets a gauge's value (clamped between 0 and the gauge's max unless it can overflow) and saves it
+pub fn set_gauge_value(gauge: &mut Gauge, value: i32) -> Result<(), Error> {
+    gauge.value = clamp_gauge_value(gauge, value);
+
+    db::gauges::update(gauge)?;
+
+    Ok(())
+}
+
+pub fn gauge_bar_line(gauge: &Gauge) -> String {
+    gauge_bar_line_of_length(gauge, BAR_LENGTH as usize)
+}
+
+pub fn gauge_bar_line_of_length(gauge: &Gauge, length: usize) -> String {
+    // Match the color from the Colour field to find the closest emoji
+    let bar_emoji = color_matcher::get_closest_color_emoji(gauge.colour.as_deref());
+
+    let bar = crate::common::draw_bar(
+        gauge.value.min(gauge.max),
+        gauge.max,
+        length,
+        bar_emoji,
+        "⬛",
+    );
+
+    // Display icon if available, otherwise just show the bar
+    let display_icon = gauge.icon.as_deref().unwrap_or("");
+
+    format!("{} {} ``{} / {}``", display_icon, bar, gauge.value, gauge.max)
+}
+
+/// How much a single press of a gauge's nudge button changes it by
+fn gauge_step(gauge: &Gauge) -> i32 {
+    (gauge.max / 10).max(1)
+}
+
+/// Rows of -/+ buttons for each gauge, two gauges per row
+pub fn gauge_buttons(gauges: &[Gauge]) -> Vec<CreateActionRow> {
+    let buttons: Vec<_> = gauges
+        .iter()
+        .flat_map(|gauge| {
+            let label = gauge.icon.clone().unwrap_or(gauge.name.clone());
+            let step = gauge_step(gauge);
+
+            [-step, step].map(|change| {
+                ChangeGaugeEvent::create_button(
+                    &format!("{label}{change:+}"),
+                    &ChangeGaugeEventParams {
+                        gauge_id: gauge.id.to_string(),
+                        change,
+                    },
+                    ButtonStyle::Secondary,
+                )
+                .expect("How fail")
+            })
+        })
+        .collect();
+
+    buttons
+        .chunks(4)
+        .map(|chunk| CreateActionRow::Buttons(chunk.to_vec()))
+        .collect()
+}
+
+async fn get_gauge_of_sender(ctx: &Context<'_>, name: &str) -> Result<Gauge, Error> {
+    let character = get_user_character(ctx)
+        .await?
+        .ok_or(RpgError::NoCharacterSelected)?;
+
+    db::gauges::get_by_name(character.id, name).map_err(|_| RpgError::GaugeNotFound.into())
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands("add", "set", "modify", "configure", "remove", "list"),
+    subcommand_required
+)]
+pub async fn gauge(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command)]
+#[allow(clippy::too_many_arguments)]
+pub async fn add(
+    ctx: Context<'_>,
+    name: String,
+    max: i32,
+    icon: Option<String>,
+    colour: Option<String>,
+    gauge_type: Option<GaugeType>,
+    #[description = "Amount regenerated or decayed per turn"] rate: Option<i32>,
+    value: Option<i32>,
+) -> Result<(), Error> {
+    let character = get_user_character(&ctx)
+        .await?
+        .ok_or(RpgError::NoCharacterSelected)?;
+
+    let mut new_gauge = NewGauge {
+        id: uuid::Uuid::new_v4(),
+        icon,
+        name,
+        value: value.unwrap_or(max),
+        max,
+        player_character_id: character.id,
+        gauge_type: gauge_type.unwrap_or(GaugeType::Plain) as i32,
+        colour,
+        rate: rate.unwrap_or(1),
+    };
+
+    check_new_gauge(&mut new_gauge)?;
+
+    if db::gauges::get_by_name(character.id, &new_gauge.name).is_ok() {
+        return Err(Box::new(RpgError::GaugeAlreadyExists));
+    }
+
+    let gauge = db::gauges::create(&new_gauge)?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "Added gauge **{}**\n{}",
+                gauge.name,
+                gauge_bar_line(&gauge)
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Confirms a change to a gauge, recording it so it can be undone with the ↩️ button
+async fn reply_with_gauge_change(ctx: Context<'_>, old_value: i32, gauge: &Gauge) -> Result<(), Error> {
+    let batch = undo::record(
+        ctx.author().id,
+        &[undo::Change::new(
+            gauge.player_character_id,
+            undo::Resource::Gauge(gauge.id),
+            old_value,
+            gauge.value,
+        )],
+    )?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "Modified {}. Change: {}\n{}",
+                gauge.name,
+                gauge.value - old_value,
+                gauge_bar_line(gauge)
+            ))
+            .reply(true)
+            .components(undo::undo_row(batch)?),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command)]
+pub async fn set(ctx: Context<'_>, name: String, value: i32) -> Result<(), Error> {
+    let mut gauge = get_gauge_of_sender(&ctx, &name).await?;
+
+    let old_value = gauge.value;
+
+    set_gauge_value(&mut gauge, value)?;
+
+    reply_with_gauge_change(ctx, old_value, &gauge).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command, rename = "mod")]
+pub async fn modify(ctx: Context<'_>, name: String, expression: String) -> Result<(), Error> {
+    let mut gauge = get_gauge_of_sender(&ctx, &name).await?;
+
+    let old_value = gauge.value;
+
+    let new_value = eval_modifier(&expression, gauge.value)?;
+
+    set_gauge_value(&mut gauge, new_value)?;
+
+    reply_with_gauge_change(ctx, old_value, &gauge).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command)]
+pub async fn configure(
+    ctx: Context<'_>,
+    name: String,
+    gauge_type: GaugeType,
+    #[description = "Amount regenerated or decayed per turn"] rate: Option<i32>,
+) -> Result<(), Error> {
+    let mut gauge = get_gauge_of_sender(&ctx, &name).await?;
+
+    gauge.gauge_type = gauge_type as i32;
+
+    if let Some(rate) = rate {
+        gauge.rate = rate;
+    }
+
+    db::gauges::update(&gauge)?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "{} is now: {}",
+                gauge.name,
+                describe_behaviour(&gauge)
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+pub fn describe_behaviour(gauge: &Gauge) -> String {
+    match gauge.behaviour() {
+        GaugeType::Regen => format!("regenerates {} per turn", gauge.rate_per_turn()),
+        GaugeType::Decay => format!("decays {} per turn", gauge.rate_per_turn()),
+        other => other.name().to_lowercase(),
+    }
+}
+
+#[poise::command(slash_command, prefix_command)]
+pub async fn remove(ctx: Context<'_>, name: String) -> Result<(), Error> {
+    let gauge = get_gauge_of_sender(&ctx, &name).await?;
+
+    db::gauges::delete(gauge.id)?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!("Removed gauge **{}**", gauge.name))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command)]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    let character = get_user_character(&ctx)
+        .await?
+        .ok_or(RpgError::NoCharacterSelected)?;
+
+    let gauges = db::gauges::get_for_character(character.id)?;
+
+    let content = if gauges.is_empty() {
+        "No gauges - add one with /gauge add".to_string()
+    } else {
+        gauges
+            .iter()
+            .map(|gauge| {
+                format!(
+                    "**{}** ({})\n{}",
+                    gauge.name,
+                    describe_behaviour(gauge),
+                    gauge_bar_line(gauge)
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n\n")
+    };
+
+    ctx.send(
+        CreateReply::default()
+            .content(content)
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Discord rejects buttons with longer custom ids
+    static MAX_CUSTOM_ID_LENGTH: usize = 100;
+
+    fn gauge(max: i32) -> Gauge {
+        Gauge {
+            id: uuid::Uuid::new_v4(),
+            icon: None,
+            name: "A gauge with a rather long name".to_string(),
+            value: max,
+            max,
+            player_character_id: i32::MAX,
+            gauge_type: GaugeType::Plain as i32,
+            colour: None,
+            rate: 0,
+        }
+    }
+
+    #[test]
+    fn button_ids_fit_within_discords_limit() {
+        for max in [1, 99, 100, 1000, i32::MAX] {
+            for row in gauge_buttons(&[gauge(max)]) {
+                let row = serde_json::to_value(&row).unwrap();
+
+                for button in row["components"].as_array().unwrap() {
+                    let custom_id = button["custom_id"].as_str().unwrap();
+
+                    assert!(
+                        custom_id.len() <= MAX_CUSTOM_ID_LENGTH,
+                        "{custom_id} is {} characters",
+                        custom_id.len()
+                    );
+                }
+            }
+        }
+    }
+
+    fn new_gauge(value: i32, max: i32, gauge_type: GaugeType) -> NewGauge {
+        NewGauge {
+            id: uuid::Uuid::new_v4(),
+            icon: None,
+            name: "Imported".to_string(),
+            value,
+            max,
+            player_character_id: 0,
+            gauge_type: gauge_type as i32,
+            colour: None,
+            rate: 1,
+        }
+    }
+
+    #[test]
+    fn new_gauges_are_checked() {
+        assert!(check_new_gauge(&mut new_gauge(5, 0, GaugeType::Plain)).is_err());
+
+        let mut plain = new_gauge(50, 10, GaugeType::Plain);
+        check_new_gauge(&mut plain).unwrap();
+        assert_eq!(plain.value, 10);
+
+        let mut overflow = new_gauge(50, 10, GaugeType::Overflow);
+        check_new_gauge(&mut overflow).unwrap();
+        assert_eq!(overflow.value, 50);
+    }
+}
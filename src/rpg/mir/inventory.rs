@@ -0,0 +1,555 @@
+use poise::CreateReply;
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::common::Context;
+use crate::common::Error;
+use crate::db;
+use crate::db::models::Character;
+use crate::db::models::Item;
+use crate::db::models::NewItem;
+
+use super::super::get_user_character;
+use super::super::RpgError;
+use super::ownership::get_managed_character;
+use super::stat_block::parse_stat_list;
+use super::stat_block::StatBlock;
+
+/// Where an item is kept - a character's inventory or a campaign's party stash
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ItemOwner {
+    Character(i32),
+    Stash(i32),
+}
+
+impl ItemOwner {
+    fn find(&self, name: &str) -> Result<Item, Error> {
+        match self {
+            ItemOwner::Character(character_id) => {
+                db::items::find_for_character(*character_id, name.trim())
+            }
+            ItemOwner::Stash(campaign_id) => db::items::find_in_stash(*campaign_id, name.trim()),
+        }
+        .map_err(|_| Box::new(RpgError::ItemNotFound) as Error)
+    }
+
+    fn items(&self) -> Result<Vec<Item>, Error> {
+        match self {
+            ItemOwner::Character(character_id) => db::items::get_for_character(*character_id),
+            ItemOwner::Stash(campaign_id) => db::items::get_for_campaign(*campaign_id),
+        }
+    }
+}
+
+/// The properties an item keeps when it's moved, split or merged
+struct ItemDetails {
+    name: String,
+    weight: Option<f64>,
+    stat_bonuses: Option<String>,
+    saved_rolls: Option<String>,
+}
+
+impl ItemDetails {
+    fn of(item: &Item) -> Self {
+        Self {
+            name: item.name.clone(),
+            weight: item.weight,
+            stat_bonuses: item.stat_bonuses.clone(),
+            saved_rolls: item.saved_rolls.clone(),
+        }
+    }
+}
+
+fn stash_of(character: &Character) -> Result<ItemOwner, Error> {
+    character
+        .campaign_id
+        .map(ItemOwner::Stash)
+        .ok_or(Box::new(RpgError::NoCampaign))
+}
+
+/// Adds items to an owner, stacking them onto an unequipped item with the same name if there is one
+fn give_item(owner: ItemOwner, details: ItemDetails, quantity: i32) -> Result<Item, Error> {
+    if let Ok(mut existing) = owner.find(&details.name) {
+        if existing.equipped_slot.is_none() {
+            existing.quantity += quantity;
+            db::items::update(&existing)?;
+
+            return Ok(existing);
+        }
+    }
+
+    let (character_id, campaign_id) = match owner {
+        ItemOwner::Character(character_id) => (Some(character_id), None),
+        ItemOwner::Stash(campaign_id) => (None, Some(campaign_id)),
+    };
+
+    db::items::create(&NewItem {
+        character_id,
+        campaign_id,
+        name: details.name,
+        quantity,
+        weight: details.weight,
+        equipped_slot: None,
+        stat_bonuses: details.stat_bonuses,
+        saved_rolls: details.saved_rolls,
+    })
+}
+
+/// Takes some of a stack away, removing the item once none are left
+fn take_item(item: &mut Item, quantity: i32) -> Result<(), Error> {
+    if quantity > item.quantity {
+        return Err(Box::new(RpgError::NotEnoughItems));
+    }
+
+    item.quantity -= quantity;
+
+    if item.quantity == 0 {
+        db::items::delete(item.id)
+    } else {
+        db::items::update(item)
+    }
+}
+
+fn moved_quantity(item: &Item, quantity: Option<i32>) -> i32 {
+    quantity.unwrap_or(item.quantity).max(1)
+}
+
+fn stat_bonuses_of(item: &Item) -> Map<String, Value> {
+    item.stat_bonuses
+        .as_deref()
+        .and_then(|bonuses| serde_json::from_str(bonuses).ok())
+        .unwrap_or_default()
+}
+
+/// Adds a bonus to the stat with the same name (case insensitive), if the block has one
+fn add_bonus(stats: Option<&mut Value>, stat: &str, bonus: i64) -> bool {
+    let Some(stats) = stats.and_then(|stats| stats.as_object_mut()) else {
+        return false;
+    };
+
+    for (key, value) in stats.iter_mut() {
+        if key.eq_ignore_ascii_case(stat) {
+            if let Some(current) = value.as_i64() {
+                *value = Value::from(current + bonus);
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+/// Applies equipped items to a roll: their stat bonuses are added to the stat block,
+/// and their saved rolls are returned along with the character's own
+pub fn apply_equipment(character: &Character, stat_block: &mut StatBlock) -> Option<String> {
+    let equipped = db::items::get_equipped(character.id).unwrap_or_default();
+
+    let mut saved_rolls = character.saved_rolls.clone().unwrap_or_default();
+
+    for item in &equipped {
+        for (stat, bonus) in stat_bonuses_of(item) {
+            let Some(bonus) = bonus.as_i64() else {
+                continue;
+            };
+
+            if !add_bonus(stat_block.stats.as_mut(), &stat, bonus) {
+                add_bonus(stat_block.special_stats.as_mut(), &stat, bonus);
+            }
+        }
+
+        if let Some(item_rolls) = &item.saved_rolls {
+            saved_rolls = format!("{saved_rolls}\n{item_rolls}");
+        }
+    }
+
+    if saved_rolls.trim().is_empty() {
+        None
+    } else {
+        Some(saved_rolls)
+    }
+}
+
+fn describe_item(item: &Item) -> String {
+    let mut line = if item.quantity == 1 {
+        item.name.clone()
+    } else {
+        format!("{}x {}", item.quantity, item.name)
+    };
+
+    if let Some(weight) = item.weight {
+        line += &format!(" ({} lb)", weight * item.quantity as f64);
+    }
+
+    let bonuses = stat_bonuses_of(item)
+        .iter()
+        .map(|(stat, bonus)| format!("{stat} {bonus:+}"))
+        .collect::<Vec<String>>();
+
+    if !bonuses.is_empty() {
+        line += &format!(" [{}]", bonuses.join(", "));
+    }
+
+    if let Some(saved_rolls) = &item.saved_rolls {
+        let names = saved_rolls
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, _)| name.trim().to_string())
+            .collect::<Vec<String>>();
+
+        if !names.is_empty() {
+            line += &format!(" - unlocks {}", names.join(", "));
+        }
+    }
+
+    line
+}
+
+fn render_items(items: &[Item]) -> String {
+    let mut equipped = vec![];
+    let mut carried = vec![];
+
+    for item in items {
+        match &item.equipped_slot {
+            Some(slot) => equipped.push(format!("- **{slot}**: {}", describe_item(item))),
+            None => carried.push(format!("- {}", describe_item(item))),
+        }
+    }
+
+    let total_weight: f64 = items
+        .iter()
+        .filter_map(|item| item.weight.map(|weight| weight * item.quantity as f64))
+        .sum();
+
+    let mut sections = vec![];
+
+    if !equipped.is_empty() {
+        sections.push(format!("Equipped:\n{}", equipped.join("\n")));
+    }
+    if !carried.is_empty() {
+        sections.push(format!("Carried:\n{}", carried.join("\n")));
+    }
+    if sections.is_empty() {
+        sections.push("Nothing here".to_string());
+    }
+    if total_weight > 0.0 {
+        sections.push(format!("Total weight: {total_weight} lb"));
+    }
+
+    sections.join("\n\n")
+}
+
+/// A character's inventory, for /inventory show and the status embed's inventory button
+pub fn render_inventory(character: &Character) -> Result<String, Error> {
+    let items = db::items::get_for_character(character.id)?;
+
+    Ok(format!(
+        "🎒 **{}**\n{}",
+        character.name.clone().unwrap_or("No name?".to_string()),
+        render_items(&items)
+    ))
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands("show", "add", "remove", "equip", "unequip", "give", "stash", "take"),
+    subcommand_required
+)]
+pub async fn inventory(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Show a character's inventory, or their campaign's party stash
+#[poise::command(slash_command, prefix_command)]
+pub async fn show(
+    ctx: Context<'_>,
+    #[description = "Show the party stash instead"] party_stash: Option<bool>,
+    #[description = "Defaults to your selected character"] character_id: Option<i32>,
+) -> Result<(), Error> {
+    let character = get_managed_character(&ctx, character_id).await?;
+
+    let content = if party_stash.unwrap_or(false) {
+        let stash = stash_of(&character)?;
+        format!("💰 **Party stash**\n{}", render_items(&stash.items()?))
+    } else {
+        render_inventory(&character)?
+    };
+
+    ctx.send(CreateReply::default().content(content).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Add an item to your inventory (or the party stash)
+#[poise::command(slash_command, prefix_command)]
+#[allow(clippy::too_many_arguments)]
+pub async fn add(
+    ctx: Context<'_>,
+    name: String,
+    #[description = "Defaults to 1"] quantity: Option<i32>,
+    #[description = "Weight of one of these items"] weight: Option<f64>,
+    #[description = "Bonuses while equipped, e.g. str 2, dex -1"] stat_bonuses: Option<String>,
+    #[description = "Saved rolls unlocked while equipped, e.g. longsword: 1d8+str"]
+    saved_rolls: Option<String>,
+    #[description = "Add it to the party stash instead"] party_stash: Option<bool>,
+    #[description = "Defaults to your selected character"] character_id: Option<i32>,
+) -> Result<(), Error> {
+    let character = get_managed_character(&ctx, character_id).await?;
+
+    let owner = if party_stash.unwrap_or(false) {
+        stash_of(&character)?
+    } else {
+        ItemOwner::Character(character.id)
+    };
+
+    let stat_bonuses = stat_bonuses
+        .map(|bonuses| parse_stat_list(&bonuses))
+        .transpose()?
+        .filter(|bonuses| !bonuses.is_empty())
+        .map(|bonuses| serde_json::to_string(&bonuses))
+        .transpose()?;
+
+    // Saved rolls can be separated by commas here, as slash command options can't hold new lines
+    let saved_rolls = saved_rolls
+        .map(|rolls| {
+            rolls
+                .split(',')
+                .map(|roll| roll.trim())
+                .filter(|roll| roll.contains(':'))
+                .collect::<Vec<&str>>()
+                .join("\n")
+        })
+        .filter(|rolls| !rolls.is_empty());
+
+    let item = give_item(
+        owner,
+        ItemDetails {
+            name: name.trim().to_string(),
+            weight,
+            stat_bonuses,
+            saved_rolls,
+        },
+        quantity.unwrap_or(1).max(1),
+    )?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!("Added {}", describe_item(&item)))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command)]
+pub async fn remove(
+    ctx: Context<'_>,
+    name: String,
+    #[description = "Defaults to all of them"] quantity: Option<i32>,
+    #[description = "Remove it from the party stash instead"] party_stash: Option<bool>,
+    #[description = "Defaults to your selected character"] character_id: Option<i32>,
+) -> Result<(), Error> {
+    let character = get_managed_character(&ctx, character_id).await?;
+
+    let owner = if party_stash.unwrap_or(false) {
+        stash_of(&character)?
+    } else {
+        ItemOwner::Character(character.id)
+    };
+
+    let mut item = owner.find(&name)?;
+    let quantity = moved_quantity(&item, quantity);
+
+    take_item(&mut item, quantity)?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!("Removed {quantity}x {}", item.name))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Equip an item into a slot (e.g. main hand) - anything already in the slot is unequipped
+#[poise::command(slash_command, prefix_command)]
+pub async fn equip(
+    ctx: Context<'_>,
+    name: String,
+    slot: String,
+    #[description = "Defaults to your selected character"] character_id: Option<i32>,
+) -> Result<(), Error> {
+    let character = get_managed_character(&ctx, character_id).await?;
+
+    let owner = ItemOwner::Character(character.id);
+    let slot = slot.trim().to_lowercase();
+
+    for mut equipped in db::items::get_equipped(character.id)? {
+        if equipped.equipped_slot.as_deref() == Some(slot.as_str()) {
+            let quantity = equipped.quantity;
+
+            take_item(&mut equipped, quantity)?;
+            give_item(owner, ItemDetails::of(&equipped), quantity)?;
+        }
+    }
+
+    let mut item = owner.find(&name)?;
+
+    // Only one of a stack is equipped, the rest stay carried
+    if item.quantity > 1 {
+        take_item(&mut item, 1)?;
+
+        item = db::items::create(&NewItem {
+            character_id: Some(character.id),
+            campaign_id: None,
+            name: item.name.clone(),
+            quantity: 1,
+            weight: item.weight,
+            equipped_slot: None,
+            stat_bonuses: item.stat_bonuses.clone(),
+            saved_rolls: item.saved_rolls.clone(),
+        })?;
+    }
+
+    item.equipped_slot = Some(slot.clone());
+    db::items::update(&item)?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!("Equipped {} ({slot})", item.name))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command)]
+pub async fn unequip(
+    ctx: Context<'_>,
+    name: String,
+    #[description = "Defaults to your selected character"] character_id: Option<i32>,
+) -> Result<(), Error> {
+    let character = get_managed_character(&ctx, character_id).await?;
+
+    let owner = ItemOwner::Character(character.id);
+
+    let mut item = db::items::get_equipped(character.id)?
+        .into_iter()
+        .find(|item| item.name.eq_ignore_ascii_case(name.trim()))
+        .ok_or(RpgError::ItemNotFound)?;
+
+    let quantity = item.quantity;
+
+    take_item(&mut item, quantity)?;
+    give_item(owner, ItemDetails::of(&item), quantity)?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!("Unequipped {}", item.name))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Moves items between two owners, unequipping them on the way
+async fn move_items(
+    ctx: Context<'_>,
+    from: ItemOwner,
+    to: ItemOwner,
+    name: &str,
+    quantity: Option<i32>,
+    destination: &str,
+) -> Result<(), Error> {
+    let mut item = from.find(name)?;
+    let quantity = moved_quantity(&item, quantity);
+
+    take_item(&mut item, quantity)?;
+    give_item(to, ItemDetails::of(&item), quantity)?;
+
+    ctx.send(CreateReply::default().content(format!(
+        "Moved {quantity}x {} to {destination}",
+        item.name
+    )))
+    .await?;
+
+    Ok(())
+}
+
+/// Give items to another character
+#[poise::command(slash_command, prefix_command)]
+pub async fn give(
+    ctx: Context<'_>,
+    name: String,
+    character_id: i32,
+    #[description = "Defaults to all of them"] quantity: Option<i32>,
+) -> Result<(), Error> {
+    let character = get_user_character(&ctx)
+        .await?
+        .ok_or(RpgError::NoCharacterSelected)?;
+
+    let recipient = db::characters::get(character_id)?;
+
+    if character.campaign_id.is_none() || recipient.campaign_id != character.campaign_id {
+        return Err(Box::new(RpgError::RecipientNotInCampaign));
+    }
+
+    move_items(
+        ctx,
+        ItemOwner::Character(character.id),
+        ItemOwner::Character(recipient.id),
+        &name,
+        quantity,
+        &recipient.name.unwrap_or("No name?".to_string()),
+    )
+    .await
+}
+
+/// Put items in your campaign's party stash
+#[poise::command(slash_command, prefix_command)]
+pub async fn stash(
+    ctx: Context<'_>,
+    name: String,
+    #[description = "Defaults to all of them"] quantity: Option<i32>,
+) -> Result<(), Error> {
+    let character = get_user_character(&ctx)
+        .await?
+        .ok_or(RpgError::NoCharacterSelected)?;
+
+    move_items(
+        ctx,
+        ItemOwner::Character(character.id),
+        stash_of(&character)?,
+        &name,
+        quantity,
+        "the party stash",
+    )
+    .await
+}
+
+/// Take items out of your campaign's party stash
+#[poise::command(slash_command, prefix_command)]
+pub async fn take(
+    ctx: Context<'_>,
+    name: String,
+    #[description = "Defaults to all of them"] quantity: Option<i32>,
+) -> Result<(), Error> {
+    let character = get_user_character(&ctx)
+        .await?
+        .ok_or(RpgError::NoCharacterSelected)?;
+
+    move_items(
+        ctx,
+        stash_of(&character)?,
+        ItemOwner::Character(character.id),
+        &name,
+        quantity,
+        &character.name.clone().unwrap_or("No name?".to_string()),
+    )
+    .await
+}
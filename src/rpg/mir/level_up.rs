@@ -0,0 +1,597 @@
+use poise::serenity_prelude::EditMessage;
+use poise::CreateReply;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::common::Context;
+use crate::common::Error;
+use crate::db;
+use crate::db::models::Character;
+use crate::db::models::NewLevelUp;
+use crate::dice;
+
+use super::super::get_user_character;
+use super::super::CharacterSheetable;
+use super::super::RpgError;
+use super::revisions;
+use super::revisions::BlockKind;
+use super::revisions::RevisionSource;
+use super::sessions;
+use super::sessions::SessionEventKind;
+use super::stat_block::StatBlock;
+
+/// A roll made once per level, and where its result is added on the stat block
+/// Defined as a list under "level_up_rolls" on the stat block or the campaign, e.g.
+/// [{"name": "Energy", "emoji": "⚡️", "expression": "1d4", "target": "energy_pool"}]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LevelUpRoll {
+    pub name: String,
+    #[serde(default = "default_roll_emoji")]
+    pub emoji: String,
+    pub expression: String,
+    /// Dot separated path into the stat block, e.g. "energy_pool" or "stats.str"
+    #[serde(default)]
+    pub target: Option<String>,
+}
+
+/// The most levels gained at once, also keeps the level loop from overflowing
+pub static MAX_LEVELS: i32 = 20;
+
+fn default_roll_emoji() -> String {
+    "🎲".to_string()
+}
+
+/// A single roll's result, as stored in level_ups.rolls
+#[derive(Serialize, Deserialize)]
+pub struct RolledGain {
+    pub name: String,
+    pub emoji: String,
+    pub result: f64,
+}
+
+/// Mir's original per level dice, used for sheets that predate level_up_rolls
+static LEGACY_LEVEL_UP_DICE: [(&str, &str, &str, &str); 3] = [
+    ("energy_die_per_level", "Energy", "⚡️", "energy_pool"),
+    ("magic_die_per_level", "Magic", "🐇", "special_stats.magic_points"),
+    ("training_die_per_level", "Training", "🏋", "special_stats.training_points"),
+];
+
+fn parse_level_up_rolls(value: &Value) -> Result<Vec<LevelUpRoll>, Error> {
+    let rolls: Vec<LevelUpRoll> =
+        serde_json::from_value(value.clone()).map_err(|_| RpgError::InvalidLevelUpRolls)?;
+
+    if rolls.is_empty() {
+        return Err(Box::new(RpgError::NoLevelUpRolls));
+    }
+
+    Ok(rolls)
+}
+
+/// The rolls made per level - the sheet's own list takes priority, then the campaign's,
+/// then the legacy energy/magic/training dice
+fn level_up_rolls(stats: &Value, character: &Character) -> Result<Vec<LevelUpRoll>, Error> {
+    if let Some(rolls) = stats.get("level_up_rolls").filter(|v| !v.is_null()) {
+        return parse_level_up_rolls(rolls);
+    }
+
+    let campaign_rolls = character
+        .campaign_id
+        .and_then(|id| db::campaigns::get(id).ok())
+        .and_then(|campaign| campaign.level_up_rolls);
+
+    if let Some(rolls) = campaign_rolls {
+        return parse_level_up_rolls(&serde_json::from_str(&rolls)?);
+    }
+
+    let legacy_rolls: Vec<LevelUpRoll> = LEGACY_LEVEL_UP_DICE
+        .iter()
+        .filter_map(|(key, name, emoji, target)| {
+            stats
+                .get(*key)
+                .and_then(|v| v.as_str())
+                .map(|expression| LevelUpRoll {
+                    name: name.to_string(),
+                    emoji: emoji.to_string(),
+                    expression: expression.to_string(),
+                    target: Some(target.to_string()),
+                })
+        })
+        .collect();
+
+    if legacy_rolls.is_empty() {
+        return Err(Box::new(RpgError::NoLevelUpRolls));
+    }
+
+    Ok(legacy_rolls)
+}
+
+/// Adds an amount to the number at a dot separated path, creating it (and any parent objects) if missing
+/// Anything already in the way that isn't an object or a number is an error rather than being overwritten
+fn add_to_path(stat_block: &mut Value, path: &str, amount: f64) -> Result<(), Error> {
+    let mut current = stat_block;
+
+    for key in path.split('.') {
+        if current.is_null() {
+            *current = Value::Object(serde_json::Map::new());
+        }
+
+        current = current
+            .as_object_mut()
+            .ok_or(RpgError::InvalidLevelUpTarget)?
+            .entry(key.to_string())
+            .or_insert(Value::Null);
+    }
+
+    let total = match current {
+        Value::Null => amount,
+        Value::Number(number) => number.as_f64().unwrap_or(0.0) + amount,
+        _ => return Err(Box::new(RpgError::InvalidLevelUpTarget)),
+    };
+
+    *current = if total.fract() == 0.0 {
+        Value::from(total as i64)
+    } else {
+        Value::from(total)
+    };
+
+    Ok(())
+}
+
+pub struct LevelUpOutcome {
+    pub message: String,
+    pub character: Character,
+}
+
+/// Rolls every level up die for each level gained, adds the results to the stored stat block,
+/// increments the level and records each level in the level_ups history table
+pub async fn level_up_character(
+    ctx: Option<&poise::serenity_prelude::Context>,
+    character: &Character,
+    num_levels: i32,
+    author_id: Option<u64>,
+) -> Result<LevelUpOutcome, Error> {
+    if !(1..=MAX_LEVELS).contains(&num_levels) {
+        return Err(Box::new(RpgError::InvalidLevelCount));
+    }
+
+    let stat_block: StatBlock = super::super::get_sheet(ctx, character).await?;
+
+    let previous_json = stat_block
+        .sheet_info
+        .jsonified_message
+        .clone()
+        .expect("Stat block should always generate json");
+
+    let mut stats: Value = serde_json::from_str(&previous_json)?;
+
+    let rolls = level_up_rolls(&stats, character)?;
+
+    let starting_level = stats.get("level").and_then(|v| v.as_i64()).unwrap_or(0);
+
+    let per_level = rolls
+        .iter()
+        .map(|roll| format!("{}: {}", roll.name, roll.expression))
+        .collect::<Vec<String>>()
+        .join(" \\| ");
+
+    let mut message = format!(
+        "Per Level: \n{per_level}\n\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\nRolls:"
+    );
+
+    let mut totals: Vec<f64> = vec![0.0; rolls.len()];
+    let mut history = vec![];
+
+    let batch_id = uuid::Uuid::new_v4();
+    let mut previous_stat_block = Some(previous_json.clone());
+
+    for i in 1..num_levels + 1 {
+        let mut gains = vec![];
+
+        for (roll, total) in rolls.iter().zip(totals.iter_mut()) {
+            let result = dice::eval_roll(&roll.expression)?.result;
+
+            *total += result;
+
+            if let Some(target) = &roll.target {
+                add_to_path(&mut stats, target, result)?;
+            }
+
+            gains.push(RolledGain {
+                name: roll.name.clone(),
+                emoji: roll.emoji.clone(),
+                result,
+            });
+        }
+
+        let level = starting_level + i as i64;
+        stats["level"] = Value::from(level);
+
+        message = format!("{message}\n{i}.  {}", format_gains(&gains));
+
+        history.push(NewLevelUp {
+            character_id: character.id,
+            batch_id,
+            level: level as i32,
+            rolls: serde_json::to_string(&gains)?,
+            previous_stat_block: previous_stat_block.take(),
+        });
+
+        previous_stat_block = Some(serde_json::to_string(&stats)?);
+    }
+
+    let total_gains: Vec<RolledGain> = rolls
+        .iter()
+        .zip(totals)
+        .map(|(roll, result)| RolledGain {
+            name: roll.name.clone(),
+            emoji: roll.emoji.clone(),
+            result,
+        })
+        .collect();
+
+    message = format!(
+        "{message}\n\n**Total**:\n    {}\n\nLevel {starting_level} → {}",
+        format_gains(&total_gains),
+        starting_level + num_levels as i64
+    );
+
+    let mut character = character.clone();
+    character.stat_block = Some(serde_json::to_string(&stats)?);
+
+    db::characters::update(&character)?;
+    db::level_ups::create(&history)?;
+
+    revisions::record(
+        character.id,
+        BlockKind::Stat,
+        Some(&previous_json),
+        character.stat_block.as_deref().unwrap_or_default(),
+        author_id,
+        RevisionSource::LevelUp,
+    )?;
+
+    sessions::log_event(
+        &character,
+        SessionEventKind::LevelUp,
+        &format!("Level {starting_level} → {}", starting_level + num_levels as i64),
+        Some((starting_level + num_levels as i64) as f64),
+    );
+
+    Ok(LevelUpOutcome { message, character })
+}
+
+fn format_gains(gains: &[RolledGain]) -> String {
+    gains
+        .iter()
+        .map(|gain| format!("{} {}", gain.emoji, gain.result))
+        .collect::<Vec<String>>()
+        .join("    ")
+}
+
+/// Rebuilds the character sheet message text (in the format the readme describes) from a stat block
+pub fn render_sheet_text(stat_block: &Value) -> String {
+    let value_of = |key: &str| -> String {
+        match stat_block.get(key) {
+            Some(Value::String(s)) => s.to_string(),
+            Some(Value::Null) | None => "".to_string(),
+            Some(other) => other.to_string(),
+        }
+    };
+
+    let sections: [&[(&str, &str)]; 4] = [
+        &[("Name", "name"), ("Level", "level")],
+        &[
+            ("Actions", "actions"),
+            ("Reactions", "reactions"),
+            ("Speed", "speed"),
+            ("Armour", "armour"),
+        ],
+        &[
+            ("HP", "hp"),
+            ("Current Hp", "current_hp"),
+            ("HPR", "hpr"),
+            ("Energy Pool", "energy_pool"),
+        ],
+        &[
+            ("Energy Die Per Lev", "energy_die_per_level"),
+            ("Magic Die Per Lev", "magic_die_per_level"),
+            ("Training Die Per Lev", "training_die_per_level"),
+        ],
+    ];
+
+    let mut blocks: Vec<String> = sections
+        .iter()
+        .map(|section| {
+            section
+                .iter()
+                .filter(|(_, key)| stat_block.get(*key).is_some())
+                .map(|(label, key)| format!("{label} : {}", value_of(key)))
+                .collect::<Vec<String>>()
+                .join("\n")
+        })
+        .filter(|block| !block.is_empty())
+        .collect();
+
+    if let Some(Ok(rolls)) = stat_block
+        .get("level_up_rolls")
+        .map(|rolls| serde_json::from_value::<Vec<LevelUpRoll>>(rolls.clone()))
+    {
+        // Named like the legacy lines so the sheet reads the same when it's imported again
+        let roll_lines = rolls
+            .iter()
+            .map(|roll| format!("{} Die Per Lev : {}", roll.name, roll.expression))
+            .filter(|line| !blocks.iter().any(|block| block.lines().any(|shown| shown == line)))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        if !roll_lines.is_empty() {
+            blocks.push(roll_lines);
+        }
+    }
+
+    // Special stats duplicate every number on the sheet, so only keep the ones not already shown
+    if let Some(Value::Object(special_stats)) = stat_block.get("special_stats") {
+        let extra = special_stats
+            .iter()
+            .filter(|(key, _)| stat_block.get(key.as_str()).is_none())
+            .map(|(key, value)| {
+                format!(
+                    "{} : {}",
+                    crate::common::capitalize_first_letter(&key.replace('_', " ")),
+                    value
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        if !extra.is_empty() {
+            blocks.push(extra);
+        }
+    }
+
+    if let Some(Value::Object(stats)) = stat_block.get("stats") {
+        let stat_lines = stats
+            .iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| format!("{} - {}", crate::common::capitalize_first_letter(key), value))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        blocks.push(format!("Stats:\n{stat_lines}"));
+    }
+
+    blocks.join("\n\n")
+}
+
+/// Edits the sheet message if the bot posted it, otherwise returns the text for the player to paste in
+async fn update_sheet_message(
+    ctx: &Context<'_>,
+    character: &mut Character,
+) -> Result<Option<String>, Error> {
+    let stat_block: Value = serde_json::from_str(
+        character
+            .stat_block
+            .as_ref()
+            .ok_or(RpgError::NoCharacterSheetData)?,
+    )?;
+
+    let sheet_text = render_sheet_text(&stat_block);
+
+    if let Ok(mut message) = StatBlock::get_sheet_message(ctx.serenity_context(), character).await
+    {
+        if message.author.id == ctx.framework().bot_id {
+            message
+                .edit(ctx, EditMessage::default().content(&sheet_text))
+                .await?;
+
+            character.stat_block_hash = Some(crate::common::hash(&sheet_text));
+            db::characters::update(character)?;
+
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(sheet_text))
+}
+
+#[poise::command(slash_command, prefix_command)]
+pub async fn level_up(
+    ctx: Context<'_>,
+    #[description = "How many levels to gain, from 1 to 20"] num_levels: i32,
+    #[description = "Rewrite the character sheet text with the new values"] update_sheet: Option<
+        bool,
+    >,
+) -> Result<(), Error> {
+    let msg = ctx.say("*Thinking, please wait...*").await?;
+
+    let character = get_user_character(&ctx)
+        .await?
+        .ok_or(RpgError::NoCharacterSelected)?;
+
+    let outcome = level_up_character(
+        Some(ctx.serenity_context()),
+        &character,
+        num_levels,
+        Some(ctx.author().id.get()),
+    )
+    .await?;
+
+    msg.edit(ctx, CreateReply::default().content(outcome.message))
+        .await?;
+
+    if update_sheet.unwrap_or(false) {
+        let mut character = outcome.character;
+
+        let reply = match update_sheet_message(&ctx, &mut character).await? {
+            None => "Updated your character sheet message".to_string(),
+            Some(sheet_text) => format!(
+                "I can't edit your sheet message, here's the updated text to paste in:\n```{sheet_text}```"
+            ),
+        };
+
+        ctx.send(CreateReply::default().content(reply).ephemeral(true))
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command)]
+pub async fn undo_level_up(ctx: Context<'_>) -> Result<(), Error> {
+    let mut character = get_user_character(&ctx)
+        .await?
+        .ok_or(RpgError::NoCharacterSelected)?;
+
+    let batch = db::level_ups::get_latest_batch(character.id)?;
+
+    let (Some(first), Some(last)) = (batch.first(), batch.last()) else {
+        return Err(Box::new(RpgError::NoLevelUpToUndo));
+    };
+
+    // The stat block this level up saved, anything changed since would be lost by restoring the snapshot
+    let levelled_up = db::revisions::get_first_since(
+        character.id,
+        BlockKind::Stat.as_str(),
+        RevisionSource::LevelUp.as_str(),
+        last.created_at,
+    )?;
+
+    if levelled_up.map(|revision| revision.content) != character.stat_block {
+        return Err(Box::new(RpgError::StatBlockChangedSinceLevelUp));
+    }
+
+    let before = character.clone();
+
+    character.stat_block = first.previous_stat_block.clone();
+    db::characters::update(&character)?;
+
+    revisions::record_changes(
+        &before,
+        &character,
+        Some(ctx.author().id.get()),
+        RevisionSource::Rollback,
+    )?;
+
+    db::level_ups::delete_batch(first.batch_id)?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "Undid level up to level {} (back to level {})",
+                last.level,
+                first.level - 1
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command)]
+pub async fn level_history(ctx: Context<'_>) -> Result<(), Error> {
+    let character = get_user_character(&ctx)
+        .await?
+        .ok_or(RpgError::NoCharacterSelected)?;
+
+    let history = db::level_ups::get_for_character(character.id)?;
+
+    // Only the most recent levels, to stay within discord's message length limit
+    let shown = &history[history.len().saturating_sub(20)..];
+
+    let content = if shown.is_empty() {
+        "No level ups recorded yet".to_string()
+    } else {
+        shown
+            .iter()
+            .map(|level_up| {
+                let gains: Vec<RolledGain> =
+                    serde_json::from_str(&level_up.rolls).unwrap_or_default();
+
+                format!(
+                    "**Level {}** ({}): {}",
+                    level_up.level,
+                    level_up.created_at.format("%Y-%m-%d"),
+                    format_gains(&gains)
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+
+    ctx.send(CreateReply::default().content(content).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::json;
+
+    use super::*;
+
+    /// Reads back the "Label : value" lines of a rendered sheet
+    fn parse_labels(text: &str) -> BTreeMap<String, String> {
+        text.lines()
+            .filter_map(|line| line.split_once(" : "))
+            .map(|(label, value)| (label.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn level_up_rolls_render_with_the_legacy_labels() {
+        let stat_block = json!({
+            "name": "Ordis",
+            "level": 3,
+            "level_up_rolls": [
+                {"name": "Energy", "expression": "1d4", "target": "energy_pool"},
+                {"name": "Magic", "expression": "2d6"},
+                {"name": "Training", "expression": "1d8"},
+            ],
+        });
+
+        let labels = parse_labels(&render_sheet_text(&stat_block));
+
+        assert_eq!(labels.get("Energy Die Per Lev").map(String::as_str), Some("1d4"));
+        assert_eq!(labels.get("Magic Die Per Lev").map(String::as_str), Some("2d6"));
+        assert_eq!(labels.get("Training Die Per Lev").map(String::as_str), Some("1d8"));
+    }
+
+    #[test]
+    fn rolls_matching_legacy_keys_are_only_rendered_once() {
+        let stat_block = json!({
+            "energy_die_per_level": "1d4",
+            "level_up_rolls": [
+                {"name": "Energy", "expression": "1d4"},
+                {"name": "Soul", "expression": "1d10"},
+            ],
+        });
+
+        let text = render_sheet_text(&stat_block);
+
+        assert_eq!(text.matches("Energy Die Per Lev : 1d4").count(), 1);
+        assert_eq!(
+            parse_labels(&text).get("Soul Die Per Lev").map(String::as_str),
+            Some("1d10")
+        );
+    }
+
+    #[test]
+    fn level_up_gains_only_add_to_numbers() {
+        let mut stat_block = json!({ "hp": 10, "stats": { "str": 5 }, "name": "Ordis" });
+
+        add_to_path(&mut stat_block, "hp", 4.0).unwrap();
+        add_to_path(&mut stat_block, "stats.str", 1.0).unwrap();
+        add_to_path(&mut stat_block, "stats.dex", 2.0).unwrap();
+
+        assert_eq!(stat_block["hp"], json!(14));
+        assert_eq!(stat_block["stats"], json!({ "str": 6, "dex": 2 }));
+
+        assert!(add_to_path(&mut stat_block, "hp.max", 1.0).is_err());
+        assert!(add_to_path(&mut stat_block, "name", 1.0).is_err());
+        assert_eq!(stat_block["hp"], json!(14));
+    }
+}
@@ -1,22 +1,49 @@
+pub mod actions;
+pub mod autocomplete;
+pub mod campaigns;
 mod color_matcher;
+pub mod conditions;
+pub mod currency;
+pub mod engine;
+pub mod export;
+mod fuzzy;
+pub mod gauges;
+pub mod inventory;
+pub mod level_up;
+pub mod npcs;
+pub mod ownership;
+pub mod party;
+pub mod recaps;
+pub mod rest;
+pub mod revisions;
+pub mod roll_mode;
 pub mod saved_rolls;
+pub mod sessions;
+pub mod spell_list;
 pub mod spell_sheet;
 pub mod stat_block;
+pub mod status_card;
+pub mod undo;
 pub mod web;
+pub mod xp;
 
 use crate::common::Data;
 
 use lazy_static::lazy_static;
 use poise::serenity_prelude::ButtonStyle;
 use poise::serenity_prelude::ChannelId;
+use poise::serenity_prelude::Colour;
 use poise::serenity_prelude::CreateActionRow;
+use poise::serenity_prelude::CreateAttachment;
 use poise::serenity_prelude::CreateButton;
 use poise::serenity_prelude::CreateEmbed;
 use poise::serenity_prelude::CreateEmbedFooter;
 use poise::serenity_prelude::CreateSelectMenu;
 use poise::serenity_prelude::CreateSelectMenuOption;
+use poise::serenity_prelude::EditAttachments;
 use poise::serenity_prelude::EditMessage;
 use poise::serenity_prelude::GuildId;
+use poise::serenity_prelude::UserId;
 use poise::Command;
 use poise::Modal;
 use tokio::sync::Mutex;
@@ -28,6 +55,10 @@ use super::spells::Spell;
 use super::spells::SpellResource;
 use super::spells::SpellType;
 
+use actions::ActionCost;
+use roll_mode::RollMode;
+use roll_mode::StackingRule;
+use sessions::SessionEventKind;
 use spell_sheet::SpellSheet;
 use stat_block::StatBlock;
 
@@ -73,7 +104,17 @@ pub fn register_events(event_system: &mut MutexGuard<ButtonEventSystem>) {
     event_system.register_handler(ChangeCharacterEvent);
     event_system.register_handler(UpdateStatusEvent);
     event_system.register_handler(ChangeManaEvent);
+    event_system.register_handler(event_handlers::ChangeGaugeEvent);
     event_system.register_handler(event_handlers::DeleteMessageEvent);
+    event_system.register_handler(event_handlers::TransferCharacterEvent);
+    event_system.register_handler(event_handlers::PartyFocusEvent);
+    event_system.register_handler(event_handlers::PartyResourceEvent);
+    event_system.register_handler(event_handlers::InventoryEvent);
+    event_system.register_handler(event_handlers::LevelUpEvent);
+    event_system.register_handler(event_handlers::SessionRsvpEvent);
+    event_system.register_handler(event_handlers::CastSpellEvent);
+    event_system.register_handler(event_handlers::SpellListPageEvent);
+    event_system.register_handler(event_handlers::UndoEvent);
 }
 
 #[poise::command(slash_command, prefix_command)]
@@ -180,19 +221,14 @@ pub async fn edit_saved_rolls(ctx: ApplicationContext<'_>) -> Result<(), Error>
 
 static BAR_LENGTH: i32 = 16;
 
-pub async fn generate_status_embed(
-    ctx: &poise::serenity_prelude::Context,
-    character: &Character,
-) -> Result<CreateEmbed, Error> {
-    let character_name = character.name.clone().unwrap_or("No name?".to_string());
-
+fn character_link(character: &Character) -> String {
     let web_domain =
         std::env::var("WEB_DOMAIN").unwrap_or_else(|_| "http://localhost:3000".to_string());
 
     let site_url = format!("[View on OW3N]({}/characters/{})", web_domain, character.id);
 
     // Determine the link to show based on whether Discord IDs are present
-    let character_link = if let (Some(channel_id), Some(_message_id)) = (
+    if let (Some(channel_id), Some(_message_id)) = (
         &character.stat_block_channel_id,
         &character.stat_block_message_id,
     ) {
@@ -200,7 +236,60 @@ pub async fn generate_status_embed(
         format!("{site_url} | <#{}>", channel_id)
     } else {
         site_url
-    };
+    }
+}
+
+/// The status embed, with the emoji bars swapped for a rendered card when `as_card` is set
+/// Falls back to the emoji bars if the card can't be rendered
+pub async fn generate_status_message(
+    ctx: &poise::serenity_prelude::Context,
+    character: &Character,
+    as_card: bool,
+) -> Result<(CreateEmbed, Option<CreateAttachment>), Error> {
+    if as_card {
+        match status_card::attachment_for(ctx, character).await {
+            Ok(card) => {
+                let embed = CreateEmbed::default()
+                    .title(character.name.clone().unwrap_or("No name?".to_string()))
+                    .description(character_link(character))
+                    .image(format!("attachment://{}", status_card::STATUS_CARD_FILENAME));
+
+                return Ok((embed, Some(card)));
+            }
+            Err(e) => println!("Failed to render status card for {}: {e}", character.id),
+        }
+    }
+
+    Ok((generate_status_embed(ctx, character).await?, None))
+}
+
+/// Edits a status message to show a character's current status, keeping it as a card if it was one
+pub async fn refresh_status_message(
+    ctx: &poise::serenity_prelude::Context,
+    message: &poise::serenity_prelude::Message,
+    character: &Character,
+) -> Result<(), Error> {
+    let (embed, card) =
+        generate_status_message(ctx, character, status_card::is_card_message(message)).await?;
+
+    let mut edit = EditMessage::default().embed(embed);
+
+    if let Some(card) = card {
+        edit = edit.attachments(EditAttachments::new().add(card));
+    }
+
+    message.channel_id.edit_message(ctx, message.id, edit).await?;
+
+    Ok(())
+}
+
+pub async fn generate_status_embed(
+    ctx: &poise::serenity_prelude::Context,
+    character: &Character,
+) -> Result<CreateEmbed, Error> {
+    let character_name = character.name.clone().unwrap_or("No name?".to_string());
+
+    let character_link = character_link(character);
 
     println!("Get stat block");
     let stat_block: StatBlock = super::get_sheet(Some(&ctx), character).await?;
@@ -210,21 +299,10 @@ pub async fn generate_status_embed(
 
     // Build gauge bars dynamically
     let mut gauge_bars = String::new();
-    println!("{} gauges for {}", gauges.iter().count(), character.id);
+    println!("{} gauges for {}", gauges.len(), character.id);
     for gauge in gauges {
         println!("Gauge {}", gauge.name);
-        // Match the color from the Colour field to find the closest emoji
-        let bar_emoji = color_matcher::get_closest_color_emoji(gauge.colour.as_deref());
-
-        let bar =
-            crate::common::draw_bar(gauge.value, gauge.max, BAR_LENGTH as usize, bar_emoji, "⬛");
-
-        // Display icon if available, otherwise just show the bar
-        let display_icon = gauge.icon.as_deref().unwrap_or("");
-        gauge_bars.push_str(&format!(
-            "{} {} ``{} / {}\n\n``",
-            display_icon, bar, gauge.value, gauge.max
-        ));
+        gauge_bars.push_str(&format!("{}\n\n", gauges::gauge_bar_line(&gauge)));
     }
 
     let mut active_spells_content: String = "".to_string();
@@ -257,6 +335,14 @@ pub async fn generate_status_embed(
             active_spells_content + &format!("\nNet mana change: {total_mana_diff} per turn");
     }
 
+    let actions_content = actions::economy_of(character.id, &stat_block)
+        .map(|economy| economy.to_string())
+        .unwrap_or_default();
+
+    let conditions_content = conditions::summary(character.id)
+        .map(|summary| format!("Conditions:\n{summary}"))
+        .unwrap_or_default();
+
     let mut stats_message = "".to_string();
 
     if let Some(stats) = stat_block.stats {
@@ -294,7 +380,11 @@ pub async fn generate_status_embed(
 
 {gauge_bars}
 
+{actions_content}
+
 {active_spells_content}
+
+{conditions_content}
                 "
         ))
         .footer(CreateEmbedFooter::new(stats_message));
@@ -407,6 +497,9 @@ pub async fn status_admin(ctx: Context<'_>, character_id: i32) -> Result<(), Err
         ]),
     ];
 
+    let gauges = db::gauges::get_for_character(character.id).unwrap_or_default();
+    rows.extend(gauges::gauge_buttons(&gauges).into_iter().take(2));
+
     rows.push(CreateActionRow::Buttons(vec![
         UpdateStatusEvent::create_button(
             " Refresh ",
@@ -432,12 +525,13 @@ pub async fn status_admin(ctx: Context<'_>, character_id: i32) -> Result<(), Err
     Ok(())
 }
 
-fn roll_button(text: &str, dice_string: &str, character_id: i32) -> CreateButton {
+fn roll_button(text: &str, dice_string: &str, character_id: i32, mode: RollMode) -> CreateButton {
     RollEvent::create_button(
         text,
         &RollEventParams {
             dice_string: dice_string.to_string(),
-            character_id: character_id,
+            character_id,
+            mode,
         },
         ButtonStyle::Secondary,
     )
@@ -450,6 +544,7 @@ fn roll_option(text: &str, dice_string: &str, character_id: i32) -> CreateSelect
         &RollEventParams {
             dice_string: dice_string.to_string(),
             character_id: character_id,
+            mode: RollMode::Normal,
         },
     )
     .expect("How fail")
@@ -467,19 +562,19 @@ fn select_character_option(text: &str, user_id: u64, character_id: i32) -> Creat
 }
 
 pub fn advantage_roll_buttons(base_dice_string: &str, character_id: i32) -> CreateActionRow {
-    println!("{base_dice_string}");
-
     CreateActionRow::Buttons(vec![
         roll_button(
             "🎲 disadvantage",
-            &format!("min({base_dice_string},{base_dice_string})"),
+            base_dice_string,
             character_id,
+            RollMode::Disadvantage,
         ),
-        roll_button("🎲", &format!("{base_dice_string}"), character_id),
+        roll_button("🎲", base_dice_string, character_id, RollMode::Normal),
         roll_button(
             "🎲 advantage",
-            &format!("max({base_dice_string},{base_dice_string})"),
+            base_dice_string,
             character_id,
+            RollMode::Advantage,
         ),
     ])
 }
@@ -601,7 +696,12 @@ pub fn stat_roll_buttons(
                 .map(|(e, _)| *e)
                 .unwrap_or("🎲");
             // Use original key in the button formula so it matches what's in the stat block
-            roll_button(emoji, &format!("{base_dice_string}+{original}"), character_id)
+            roll_button(
+                emoji,
+                &format!("{base_dice_string}+{original}"),
+                character_id,
+                RollMode::Normal,
+            )
         })
         .collect();
 
@@ -628,17 +728,7 @@ pub async fn character_select_dropdown(user_id: u64) -> Result<CreateActionRow,
     for character in characters {
         let name = character.name.unwrap_or("Test".to_string());
 
-        character_options.push(select_character_option(
-            &name,
-            character
-                .user_id
-                .expect(
-                    "No user id for character should not be possible as it is fetched by user id",
-                )
-                .parse()
-                .unwrap(),
-            character.id,
-        ));
+        character_options.push(select_character_option(&name, user_id, character.id));
     }
 
     let character_dropdown = CreateSelectMenu::new(
@@ -653,7 +743,11 @@ pub async fn character_select_dropdown(user_id: u64) -> Result<CreateActionRow,
 }
 
 #[poise::command(slash_command, prefix_command)]
-pub async fn status(ctx: Context<'_>, permanent: Option<bool>) -> Result<(), Error> {
+pub async fn status(
+    ctx: Context<'_>,
+    permanent: Option<bool>,
+    #[description = "Show your status as an image instead of emoji bars"] image: Option<bool>,
+) -> Result<(), Error> {
     let ephemeral = !permanent.unwrap_or(false);
 
     let placeholder = CreateReply::default()
@@ -674,11 +768,7 @@ pub async fn status(ctx: Context<'_>, permanent: Option<bool>) -> Result<(), Err
         .await?
         .ok_or(RpgError::NoCharacterSheet)?;
 
-    let default_roll = if stat_block.default_roll.as_deref().unwrap_or("").is_empty() {
-        "1d100"
-    } else {
-        stat_block.default_roll.as_ref().unwrap()
-    };
+    let default_roll = &campaigns::default_roll_for(&character, Some(&stat_block));
 
     let stats_dict = stat_block.stats;
 
@@ -691,9 +781,22 @@ pub async fn status(ctx: Context<'_>, permanent: Option<bool>) -> Result<(), Err
         rows.extend(stat_roll_buttons(default_roll, character_id, stats_dict));
     }
 
+    let inventory_button = event_handlers::InventoryEvent::create_button(
+        "🎒",
+        &event_handlers::InventoryEventParams {
+            character_id: character.id,
+        },
+    )?;
+
     if ephemeral {
         rows.push(character_select_dropdown(ctx.author().id.get()).await?);
+        rows.push(CreateActionRow::Buttons(vec![inventory_button]));
     } else {
+        // Discord allows at most 5 rows, one of which is kept for the refresh/delete buttons
+        let gauges = db::gauges::get_for_character(character_id).unwrap_or_default();
+        let free_rows = 4usize.saturating_sub(rows.len());
+        rows.extend(gauges::gauge_buttons(&gauges).into_iter().take(free_rows));
+
         rows.push(CreateActionRow::Buttons(vec![
             UpdateStatusEvent::create_button(
                 "♻️",
@@ -701,6 +804,7 @@ pub async fn status(ctx: Context<'_>, permanent: Option<bool>) -> Result<(), Err
                     character_id: character.id,
                 },
             )?,
+            inventory_button,
             event_handlers::DeleteMessageEvent::create_button(
                 "🗑️",
                 &event_handlers::DeleteMessageEventParams {},
@@ -708,17 +812,36 @@ pub async fn status(ctx: Context<'_>, permanent: Option<bool>) -> Result<(), Err
         ]));
     }
 
-    let embed = generate_status_embed(ctx.serenity_context(), &character).await?;
+    let (embed, card) =
+        generate_status_message(ctx.serenity_context(), &character, image.unwrap_or(false))
+            .await?;
 
-    placeholder_message
-        .edit(
-            ctx,
-            CreateReply::default()
-                .content("")
-                .components(rows)
-                .embed(embed),
-        )
-        .await?;
+    match card {
+        // Slash command responses can't gain attachments when edited, so the card is sent fresh
+        Some(card) => {
+            placeholder_message.delete(ctx).await?;
+
+            ctx.send(
+                CreateReply::default()
+                    .components(rows)
+                    .embed(embed)
+                    .attachment(card)
+                    .ephemeral(ephemeral),
+            )
+            .await?;
+        }
+        None => {
+            placeholder_message
+                .edit(
+                    ctx,
+                    CreateReply::default()
+                        .content("")
+                        .components(rows)
+                        .embed(embed),
+                )
+                .await?;
+        }
+    }
 
     Ok(())
 }
@@ -757,11 +880,7 @@ pub async fn set_mana(ctx: Context<'_>, mana: i32) -> Result<(), Error> {
 
     let modified_character = set_mana_internal(ctx, character, mana).await?;
 
-    ctx.reply(format!(
-        "Modified energy. Change: {}",
-        modified_character.mana.unwrap_or(0) - old_mana
-    ))
-    .await?;
+    reply_with_mana_change(ctx, old_mana, &modified_character).await?;
 
     let stat_block: StatBlock = super::get_sheet_of_sender(&ctx)
         .await?
@@ -800,6 +919,35 @@ fn get_mana_bar_message(
     );
 }
 
+/// Confirms a change to a character's mana, recording it so it can be undone with the ↩️ button
+async fn reply_with_mana_change(
+    ctx: Context<'_>,
+    old_mana: i32,
+    modified_character: &Character,
+) -> Result<(), Error> {
+    let new_mana = modified_character.mana.unwrap_or(0);
+
+    let batch = undo::record(
+        ctx.author().id,
+        &[undo::Change::new(
+            modified_character.id,
+            undo::Resource::Mana,
+            old_mana,
+            new_mana,
+        )],
+    )?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!("Modified energy. Change: {}", new_mana - old_mana))
+            .reply(true)
+            .components(undo::undo_row(batch)?),
+    )
+    .await?;
+
+    Ok(())
+}
+
 async fn set_mana_internal(
     _ctx: Context<'_>, // No longer needed, cba to remove from existing function calls
     character: Character,
@@ -831,11 +979,7 @@ pub async fn add_mana(ctx: Context<'_>, modifier: i32) -> Result<(), Error> {
 
     let modified_character = set_mana_internal(ctx, character, calc_result).await?;
 
-    ctx.reply(format!(
-        "Modified energy. Change: {}",
-        modified_character.mana.unwrap_or(0) - old_mana
-    ))
-    .await?;
+    reply_with_mana_change(ctx, old_mana, &modified_character).await?;
 
     let stat_block: StatBlock = super::get_sheet_of_sender(&ctx)
         .await?
@@ -869,11 +1013,7 @@ pub async fn sub_mana(ctx: Context<'_>, modifier: i32) -> Result<(), Error> {
 
     // db::characters::update(db_connection, &character)?;
 
-    ctx.reply(format!(
-        "Modified energy. Change: {}",
-        modified_character.mana.unwrap_or(0) - old_mana
-    ))
-    .await?;
+    reply_with_mana_change(ctx, old_mana, &modified_character).await?;
 
     let stat_block: StatBlock = super::get_sheet_of_sender(&ctx)
         .await?
@@ -908,17 +1048,11 @@ pub async fn mod_mana(ctx: Context<'_>, modifier: String) -> Result<(), Error> {
 
     let old_mana = character.mana.unwrap_or(0);
 
-    let expression = modifier.replace("n", &character.mana.unwrap_or(0).to_string());
-
-    let calc_result = meval::eval_str(expression)? as i32;
+    let calc_result = gauges::eval_modifier(&modifier, old_mana)?;
 
     let modified_character = set_mana_internal(ctx, character, calc_result).await?;
 
-    ctx.reply(format!(
-        "Modified energy. Change: {}",
-        modified_character.mana.unwrap_or(0) - old_mana
-    ))
-    .await?;
+    reply_with_mana_change(ctx, old_mana, &modified_character).await?;
 
     let stat_block: StatBlock = super::get_sheet_of_sender(&ctx)
         .await?
@@ -945,215 +1079,357 @@ pub async fn end_turn(ctx: Context<'_>) -> Result<(), Error> {
         .ephemeral(true);
     let placeholder_message = ctx.send(placeholder).await?;
 
-    let character = get_user_character(&ctx)
+    let mut character = get_user_character(&ctx)
         .await?
         .ok_or(RpgError::NoCharacterSheet)?;
 
-    let active_spells_map = ACTIVE_SPELLS.lock().await;
+    let mut changes = vec![];
 
-    if let Some(active_spells) = active_spells_map.get(&character.id) {
+    let active_spells = ACTIVE_SPELLS.lock().await.get(&character.id).cloned();
+
+    if let Some(active_spells) = active_spells {
         let stat_block: StatBlock = super::get_sheet_of_sender(&ctx)
             .await?
             .ok_or(RpgError::NoCharacterSheet)?;
 
-        let max_mana = stat_block.energy_pool;
+        let upkeep = engine::apply_upkeep(&mut character, stat_block.energy_pool, &active_spells)?;
 
-        for spell in active_spells.into_iter() {
-            let mut name = "unknown spell name";
+        db::characters::update(&character)?;
 
-            if let Some(spell_name) = &spell.name {
-                name = spell_name;
-            }
+        let caster = character.name.clone().unwrap_or("Unknown name".to_string());
 
-            let mut cur_mana = max_mana.ok_or(RpgError::NoMaxEnergy)? as i32;
+        for spell in &upkeep {
+            match &spell.change {
+                Some(change) => {
+                    ctx.reply(format!(
+                        "{caster} casts **{}**: (Cast time: {})\n",
+                        spell.spell_name, spell.cast_time
+                    ))
+                    .await?;
 
-            if let Some(mana) = character.mana {
-                cur_mana = mana;
+                    changes.push(undo::Change::from(change));
+                }
+                None => {
+                    ctx.say(format!("Spell {} failed due to lack of mana", spell.spell_name))
+                        .await?;
+                }
             }
+        }
+    }
 
-            let new_mana = cur_mana + spell.cost.as_ref().ok_or(RpgError::NoSpellCost)?.mana;
+    let gauge_changes = engine::apply_turn(character.id)?;
+    changes.extend(gauge_changes.iter().map(undo::Change::from));
 
-            if new_mana >= 0 {
-                let modified_char = set_mana_internal(ctx, character.clone(), new_mana).await?;
+    let ended_conditions = conditions::tick(character.id)?;
+    let actions_refreshed = actions::reset(character.id)?;
 
-                let cast_time = &spell
-                    .cast_time
-                    .as_ref()
-                    .and_then(|s| Some(s.to_string()))
-                    .unwrap_or("No cast time found".to_string());
+    let mut summary = if gauge_changes.is_empty() {
+        "Turn ended".to_string()
+    } else {
+        format!("Turn ended\n{}", engine::format_changes(&gauge_changes))
+    };
 
-                ctx.reply(format!(
-                    "{} casts **{name}**: (Cast time: {cast_time})\n",
-                    &modified_char
-                        .name
-                        .as_ref()
-                        .unwrap_or(&"Unknown name".to_string())
-                ))
-                .await?;
+    if !ended_conditions.is_empty() {
+        summary += &format!("\nNo longer {}", ended_conditions.join(", "));
+    }
 
-                // ctx.say(format!("Casting spell {}", name)).await?;
-            } else {
-                ctx.say(format!("Spell {} failed due to lack of mana", name))
-                    .await?;
-            }
+    if actions_refreshed {
+        summary += "\nActions and reactions are back";
+    }
+
+    let batch = undo::record(ctx.author().id, &changes)?;
+
+    placeholder_message
+        .edit(
+            ctx,
+            CreateReply::default()
+                .content(summary)
+                .components(undo::undo_row(batch)?),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Describes a cast in one embed, rolling the spell's damage and healing with the caster's stats
+/// A roll that fails is shown in the embed rather than stopping the cast
+async fn spell_cast_embed(
+    ctx: &poise::serenity_prelude::Context,
+    character: &Character,
+    spell: &Spell<ManaSpellResource>,
+    title: String,
+    colour: Colour,
+) -> CreateEmbed {
+    let mut description = spell
+        .description
+        .as_ref()
+        .map(|description| format!("*{description}*"))
+        .unwrap_or_default();
+
+    for (label, expression) in [("💥 Damage", &spell.damage), ("💚 Healing", &spell.healing)] {
+        if let Some(expression) = expression {
+            let message = match roll_with_char_sheet(
+                Some(ctx),
+                Some(expression.to_string()),
+                character,
+                RollMode::Normal,
+            )
+            .await
+            {
+                Ok(result) => result.message,
+                Err(e) => format!("Couldn't roll `{expression}`: {e}"),
+            };
+
+            description += &format!("\n\n**{label}**\n{message}");
         }
+    }
 
-        placeholder_message
-            .edit(ctx, CreateReply::default().content("Turn ended"))
-            .await?;
-    } else {
-        placeholder_message
-            .edit(ctx, CreateReply::default().content("No active spells"))
-            .await?;
+    let cast_time = spell
+        .cast_time
+        .clone()
+        .unwrap_or("No cast time found".to_string());
+
+    let mut embed = CreateEmbed::default()
+        .title(title)
+        .colour(colour)
+        .description(description)
+        .field("Cast time", cast_time, true);
+
+    if let Some(cost) = &spell.cost {
+        embed = embed.field("Cost", cost.to_string(), true);
+    }
+    if let Some(targets) = spell.targets {
+        embed = embed.field("Targets", targets.to_string(), true);
+    }
+    if let Some(save_stat) = &spell.save_stat {
+        embed = embed.field("Save", save_stat.to_uppercase(), true);
     }
 
-    Ok(())
+    embed
 }
-#[poise::command(slash_command, prefix_command)]
-pub async fn cast_spell(ctx: Context<'_>, spell_name: String) -> Result<(), Error> {
-    let placeholder = CreateReply::default().content("*Thinking, please wait...*");
-    let placeholder_message = ctx.send(placeholder).await?;
 
-    let stat_block: StatBlock = super::get_sheet_of_sender(&ctx)
-        .await?
-        .ok_or(RpgError::NoCharacterSheet)?;
+/// What casting a spell did, for /cast_spell and the spell list buttons to report
+pub enum CastOutcome {
+    /// The spell was cast or a toggle enabled - the character is given back when its mana changed,
+    /// along with the batch to undo it with
+    Cast(Box<CreateEmbed>, Option<Box<Character>>, Option<uuid::Uuid>),
+    /// The spell was disabled or couldn't be cast
+    Message(String),
+}
 
-    let spell_sheet: SpellSheet = super::get_sheet_of_sender(&ctx)
-        .await?
-        .ok_or(RpgError::NoCharacterSheet)?;
+/// A character's mana, which is a full pool until it's first changed
+pub fn current_mana(character: &Character, stat_block: &StatBlock) -> Result<i32, Error> {
+    Ok(character
+        .mana
+        .unwrap_or(stat_block.energy_pool.ok_or(RpgError::NoMaxEnergy)? as i32))
+}
 
-    let max_mana = stat_block.energy_pool;
+/// Whether a character has the mana to cast a spell, or to keep a toggle up for a turn
+pub fn can_afford(spell: &Spell<ManaSpellResource>, mana: i32) -> bool {
+    spell.cost.as_ref().is_none_or(|cost| mana + cost.mana >= 0)
+}
 
-    let spells = spell_sheet.spells.ok_or(RpgError::NoSpellSheet)?;
+pub async fn is_spell_active(character_id: i32, spell: &Spell<ManaSpellResource>) -> bool {
+    ACTIVE_SPELLS
+        .lock()
+        .await
+        .get(&character_id)
+        .is_some_and(|active_spells| active_spells.iter().any(|active| active.name == spell.name))
+}
+
+/// Casts a spell for a character - single spells take their cost straight away,
+/// toggles are switched on or off and take their cost at the end of each turn
+/// Either way the cast time's actions are used up
+pub async fn cast(
+    ctx: &poise::serenity_prelude::Context,
+    caster_id: UserId,
+    mut character: Character,
+    stat_block: &StatBlock,
+    spell_key: &str,
+    spell: &Spell<ManaSpellResource>,
+    colour: Colour,
+) -> Result<CastOutcome, Error> {
+    let spell_name = common::capitalize_first_letter(&spell_key.replace('_', " "));
+
+    let caster = character.name.clone().unwrap_or("Unknown name".to_string());
+
+    let mana = current_mana(&character, stat_block)?;
+
+    let action_cost = spell
+        .cast_time
+        .as_deref()
+        .map(ActionCost::from_cast_time)
+        .unwrap_or(ActionCost::Free);
+
+    match spell.spell_type.as_ref().unwrap_or(&SpellType::Unknown) {
+        SpellType::Single => {
+            let new_mana = mana + spell.cost.clone().ok_or(RpgError::NoSpellCost)?.mana;
+
+            if new_mana < 0 {
+                return Ok(CastOutcome::Message(format!(
+                    "Failed to cast **{spell_name}** (not enough mana)"
+                )));
+            }
 
-    if let Some(spell) = spells.get(&spell_name) {
-        // let spell_cost = spell.cost.clone().ok_or(RpgError::NoSpellCost)?;
+            let embed = spell_cast_embed(
+                ctx,
+                &character,
+                spell,
+                format!("{caster} casts {spell_name}"),
+                colour,
+            )
+            .await;
 
-        // let spell_cost_string = spell_cost.to_string();
+            let economy = actions::spend(character.id, stat_block, action_cost)?;
 
-        // let mut spell_cost_word = "Cost";
-        // if spell_cost.mana > 0 {
-        //     spell_cost_word = "Gain";
-        // }
+            sessions::log_event(&character, SessionEventKind::Spell, &spell_name, None);
 
-        let spell_type = spell.spell_type.as_ref().unwrap_or(&SpellType::Unknown);
+            let embed = embed.field("Left this turn", economy.to_string(), false);
 
-        let cast_time = &spell
-            .cast_time
-            .as_ref()
-            .and_then(|s| Some(s.to_string()))
-            .unwrap_or("No cast time found".to_string());
+            character.mana = Some(new_mana);
+            db::characters::update(&character)?;
 
-        let spell_name = common::capitalize_first_letter(&spell_name);
+            let batch = undo::record(
+                caster_id,
+                &[undo::Change::new(character.id, undo::Resource::Mana, mana, new_mana)],
+            )?;
 
-        let character = get_user_character(&ctx)
-            .await?
-            .ok_or(RpgError::NoCharacterSheet)?;
+            Ok(CastOutcome::Cast(Box::new(embed), Some(Box::new(character)), batch))
+        }
+        SpellType::Toggle => {
+            {
+                let mut active_spells_map = ACTIVE_SPELLS.lock().await;
 
-        let mana = character
-            .mana
-            .unwrap_or(max_mana.ok_or(RpgError::NoMaxEnergy)? as i32);
-
-        let mut modified_char: Option<Character> = None;
-
-        match spell_type {
-            SpellType::Single => {
-                let new_mana = mana + spell.cost.clone().ok_or(RpgError::NoSpellCost)?.mana;
-
-                if new_mana >= 0 {
-                    modified_char = Some(set_mana_internal(ctx, character, new_mana).await?);
-
-                    placeholder_message
-                        .edit(
-                            ctx,
-                            CreateReply::default().content(format!(
-                                "{} casts **{spell_name}**: (Cast time: {cast_time})\n",
-                                &modified_char
-                                    .as_ref()
-                                    .expect("Just set this")
-                                    .name
-                                    .as_ref()
-                                    .unwrap_or(&"Unknown name".to_string())
-                            )),
-                        )
-                        .await?;
-                } else {
-                    placeholder_message
-                        .edit(
-                            ctx,
-                            CreateReply::default().content(format!(
-                                "Failed to cast **{spell_name}** (not enough mana)\n",
-                            )),
-                        )
-                        .await?;
+                let active_spells = active_spells_map.entry(character.id).or_default();
+
+                if active_spells.iter().any(|active| active.name == spell.name) {
+                    active_spells.retain(|active| active.name != spell.name);
+
+                    return Ok(CastOutcome::Message(format!(
+                        "{caster} disabled spell **{spell_name}**"
+                    )));
+                }
+
+                if !can_afford(spell, mana) {
+                    return Ok(CastOutcome::Message(format!(
+                        "Failed to enable **{spell_name}** (not enough mana)"
+                    )));
                 }
             }
-            SpellType::Toggle => {
-                let mut active_spells_map = ACTIVE_SPELLS.lock().await;
 
-                let character_id = character.id;
+            let embed = spell_cast_embed(
+                ctx,
+                &character,
+                spell,
+                format!("{caster} enabled {spell_name}"),
+                colour,
+            )
+            .await;
 
-                if !active_spells_map.contains_key(&character_id) {
-                    println!("Inserting new active spell list for {}", ctx.author().name);
-                    active_spells_map.insert(character_id, Vec::new());
-                }
+            let economy = actions::spend(character.id, stat_block, action_cost)?;
 
-                let mut is_spell_active: bool = false;
+            let embed = embed.field("Left this turn", economy.to_string(), false);
 
-                let active_spells = active_spells_map.get(&character.id).expect("Just set this");
+            let mut active_spells_map = ACTIVE_SPELLS.lock().await;
+            let active_spells = active_spells_map.entry(character.id).or_default();
 
-                for active_spell in active_spells {
-                    if &spell.name == &active_spell.name {
-                        let msg = format!(
-                            "{} disabled spell {}",
-                            &ctx.author().name,
-                            &spell.name.clone().unwrap_or("Unnamed spell".to_string())
-                        );
-                        ctx.reply(msg).await?;
+            if !active_spells.iter().any(|active| active.name == spell.name) {
+                active_spells.push(spell.clone());
+            }
 
-                        is_spell_active = true;
-                    }
-                }
+            drop(active_spells_map);
+
+            sessions::log_event(&character, SessionEventKind::Spell, &spell_name, None);
 
-                if !is_spell_active {
-                    let active_spells_mut = active_spells_map
-                        .get_mut(&character.id)
-                        .expect("Just set this");
+            Ok(CastOutcome::Cast(Box::new(embed), None, None))
+        }
+        SpellType::Summon => Ok(CastOutcome::Message(
+            "Summon spells are currently not supported".to_string(),
+        )),
+        _ => Ok(CastOutcome::Message("Unknown spell type".to_string())),
+    }
+}
+
+/// Cast a spell from your spell sheet, rolling its damage or healing
+#[poise::command(slash_command, prefix_command)]
+pub async fn cast_spell(
+    ctx: Context<'_>,
+    #[autocomplete = "autocomplete::spell_name"] spell_name: String,
+) -> Result<(), Error> {
+    let placeholder = CreateReply::default().content("*Thinking, please wait...*");
+    let placeholder_message = ctx.send(placeholder).await?;
+
+    let stat_block: StatBlock = super::get_sheet_of_sender(&ctx)
+        .await?
+        .ok_or(RpgError::NoCharacterSheet)?;
+
+    let spell_sheet: SpellSheet = super::get_sheet_of_sender(&ctx)
+        .await?
+        .ok_or(RpgError::NoCharacterSheet)?;
+
+    let spells = spell_sheet.spells.ok_or(RpgError::NoSpellSheet)?;
+
+    let spell_key = fuzzy::resolve(&spell_name, spells.keys().map(String::as_str));
+
+    if let Some((spell_key, spell)) = spell_key.and_then(|key| spells.get_key_value(key)) {
+        let character = get_user_character(&ctx)
+            .await?
+            .ok_or(RpgError::NoCharacterSheet)?;
 
-                    active_spells_mut.push((*spell).clone());
+        let colour = common::get_author_colour(ctx).await?;
 
-                    let msg = format!(
-                        "{} enabled spell {}",
-                        &ctx.author().name,
-                        &spell.name.clone().unwrap_or("Unnamed spell".to_string())
-                    );
-                    ctx.reply(msg).await?;
+        match cast(
+            ctx.serenity_context(),
+            ctx.author().id,
+            character,
+            &stat_block,
+            spell_key,
+            spell,
+            colour,
+        )
+        .await?
+        {
+            CastOutcome::Cast(embed, modified_char, batch) => {
+                placeholder_message
+                    .edit(ctx, CreateReply::default().content("").embed(*embed))
+                    .await?;
+
+                if let Some(modified_char) = modified_char {
+                    let mana_message_content =
+                        get_mana_bar_message(&stat_block, &modified_char, None);
+
+                    ctx.send(
+                        CreateReply::default()
+                            .content(format!("New mana: \n{mana_message_content}"))
+                            .components(undo::undo_row(batch)?)
+                            .ephemeral(true),
+                    )
+                    .await?;
                 }
             }
-            SpellType::Summon => {
-                ctx.reply("Summon spells are currently not supported")
+            CastOutcome::Message(message) => {
+                placeholder_message
+                    .edit(ctx, CreateReply::default().content(message))
                     .await?;
             }
-            _ => {
-                ctx.reply("Unknown spell type").await?;
-            }
-        }
-
-        if let Some(modified_char) = modified_char {
-            let mana_message_content = get_mana_bar_message(&stat_block, &modified_char, None);
-
-            ctx.send(
-                CreateReply::default()
-                    .content(format!("New mana: \n{mana_message_content}"))
-                    .ephemeral(true),
-            )
-            .await?;
         }
     } else {
-        let mut spell_list_message = "Spell not found. Available Spells: \n".to_string();
+        let close_matches = fuzzy::rank(&spell_name, spells.keys().map(String::as_str));
 
-        for (spell_name, _) in spells {
-            spell_list_message += &format!("- {spell_name} \n");
+        let mut spell_list_message = if close_matches.is_empty() {
+            "Spell not found. Available Spells: \n".to_string()
+        } else {
+            "Spell not found. Did you mean: \n".to_string()
+        };
+
+        if close_matches.is_empty() {
+            for spell_name in spells.keys() {
+                spell_list_message += &format!("- {spell_name} \n");
+            }
+        } else {
+            for spell_name in close_matches {
+                spell_list_message += &format!("- {spell_name} \n");
+            }
         }
 
         let spell_list_reply = CreateReply::default()
@@ -1167,6 +1443,7 @@ pub async fn cast_spell(ctx: Context<'_>, spell_name: String) -> Result<(), Erro
     Ok(())
 }
 
+/// List your spells, with a button to cast or toggle each one
 #[poise::command(slash_command, prefix_command)]
 pub async fn list_spells(ctx: Context<'_>) -> Result<(), Error> {
     let placeholder = CreateReply::default()
@@ -1174,24 +1451,27 @@ pub async fn list_spells(ctx: Context<'_>) -> Result<(), Error> {
         .ephemeral(true);
     let placeholder_message = ctx.send(placeholder).await?;
 
-    let spell_sheet: SpellSheet = super::get_sheet_of_sender(&ctx)
+    let character = get_user_character(&ctx)
         .await?
-        .ok_or(RpgError::NoCharacterSheet)?;
-
-    let spells = spell_sheet.spells.ok_or(RpgError::NoSpellSheet)?;
+        .ok_or(RpgError::NoCharacterSelected)?;
 
-    let mut spell_list_message = "Available Spells: \n".to_string();
+    let stat_block: StatBlock = super::get_sheet(Some(ctx.serenity_context()), &character).await?;
+    let spell_sheet: SpellSheet = super::get_sheet(Some(ctx.serenity_context()), &character).await?;
 
-    for (spell_name, _) in spells {
-        spell_list_message += &format!("- {spell_name} \n");
-    }
+    let colour = common::get_author_colour(ctx).await?;
 
-    let spell_list_reply = CreateReply::default()
-        .content(spell_list_message)
-        .ephemeral(true);
+    let (embed, components) =
+        spell_list::render_page(&character, &stat_block, &spell_sheet, 0, colour).await?;
 
-    placeholder_message.edit(ctx, spell_list_reply).await?;
-    // return Err(Box::new(crate::stat_puller::RpgError::SpellNotFound));
+    placeholder_message
+        .edit(
+            ctx,
+            CreateReply::default()
+                .content("")
+                .embed(embed)
+                .components(components),
+        )
+        .await?;
 
     Ok(())
 }
@@ -1221,80 +1501,131 @@ fn replace_stat(s: &str, stat: &str, stat_mod: &str) -> String {
     }
 }
 
+/// Whether `replace_stat` would swap a stat into an expression
+fn uses_stat(s: &str, stat: &str) -> bool {
+    let lower = stat.to_lowercase();
+    let prefix: String = lower.chars().take(3).collect();
+
+    s.contains(stat) || s.contains(&lower) || (prefix.chars().count() == 3 && s.contains(&prefix))
+}
+
+/// Whether an expression is just a stat's name, or the short form substitution accepts ("str" for "Strength")
+fn is_stat_key(expression: &str, stat_keys: &[String]) -> bool {
+    let expression = fuzzy::normalise(expression);
+
+    stat_keys.iter().any(|stat| {
+        let stat = fuzzy::normalise(stat);
+
+        stat == expression || stat.chars().take(3).collect::<String>() == expression
+    })
+}
+
+/// Replaces d100 with 1d100, d6 with 1d6 etc
+pub fn normalise_dice(dice: &str) -> String {
+    let re = Regex::new(r"(^|[^\d])d(\d+)").unwrap();
+
+    re.replace_all(dice, "${1}1d$2").to_string()
+}
+
+/// Swaps saved roll names, stat names (for their modifiers) and special stats in a dice expression for their values
+pub fn substitute_stats(
+    expression: &str,
+    stat_block: &StatBlock,
+    modifier_formula: Option<&str>,
+    saved_rolls: Option<&str>,
+) -> Result<String, Error> {
+    let mut str_replaced = expression.to_string();
+
+    if let Some(custom_rolls) = saved_rolls {
+        let custom_roll_map: std::collections::HashMap<_, _> = custom_rolls
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .map(|(k, v)| (k.trim(), v.trim()))
+            .collect();
+
+        for (key, value) in &custom_roll_map {
+            str_replaced = str_replaced.replace(key, value);
+        }
+    }
+
+    if let Some(stats_object) = stat_block
+        .stats
+        .as_ref()
+        .and_then(|stats| stats.as_object())
+    {
+        for (stat, value) in stats_object {
+            if let Some(int_value) = value.as_i64() {
+                let stat_mod = if let Some(formula) = modifier_formula {
+                    // Presets (e.g. "5e") are expanded before the formula is evaluated
+                    let formula = PRESET_FORMULAS
+                        .get(formula.trim().to_lowercase().as_str())
+                        .copied()
+                        .unwrap_or(formula);
+
+                    let modifier = crate::formula::evaluate_with(
+                        formula,
+                        &[("stat", int_value as f64)],
+                    )?;
+
+                    // Bracketed so "1d20-str" doesn't become "1d20--1"
+                    if modifier < 0.0 {
+                        format!("({modifier})")
+                    } else {
+                        modifier.to_string()
+                    }
+                } else {
+                    (int_value / 10).to_string()
+                };
+
+                // str_replaced = str_replaced.replace(stat, &stat_mod.to_string());
+                str_replaced = replace_stat(&str_replaced, stat, &stat_mod.to_string());
+            }
+        }
+    }
+    if let Some(special_stats_object) = stat_block
+        .special_stats
+        .as_ref()
+        .and_then(|special_stats| special_stats.as_object())
+    {
+        for (special_stat, value) in special_stats_object {
+            str_replaced = replace_stat(&str_replaced, special_stat, &value.to_string());
+            // println!("special stat replaced: {special_stat}: {value}")
+        }
+    }
+
+    Ok(str_replaced)
+}
+
 pub async fn roll_with_char_sheet(
     ctx: Option<&poise::serenity_prelude::Context>,
     dice_expression: Option<String>,
     character: &Character,
+    mode: RollMode,
 ) -> Result<crate::dice::RollResult, Error> {
     let stat_block_result: Result<StatBlock, Error> = super::get_sheet(ctx, &character).await;
 
-    let mut dice = stat_block_result
-        .as_ref()
-        .ok()
-        .and_then(|x| x.default_roll.as_ref())
-        .cloned()
-        .unwrap_or_else(|| "1d100".to_string());
+    let mut dice = campaigns::default_roll_for(character, stat_block_result.as_ref().ok());
 
     if let Some(roll_expression) = dice_expression {
         dice = roll_expression;
     }
 
-    //Replace d100 with 1d100, d6 with 1d6 etc
-
-    let re = Regex::new(r"(^|[^\d])d(\d+)").unwrap();
-
-    dice = re.replace_all(&dice, "1d$11d$2").to_string();
-
-    // println!("{}", dice);
-
-    let mut str_replaced = dice;
+    let mut str_replaced = normalise_dice(&dice);
+    let mut stat_keys = vec![];
 
     match stat_block_result {
-        Ok(stat_block) => {
-            if let Some(custom_rolls) = &character.saved_rolls {
-                let custom_roll_map: std::collections::HashMap<_, _> = custom_rolls
-                    .lines()
-                    .filter_map(|line| line.split_once(':'))
-                    .map(|(k, v)| (k.trim(), v.trim()))
-                    .collect();
-
-                for (key, value) in &custom_roll_map {
-                    str_replaced = str_replaced.replace(key, &value.to_string());
-                }
-            }
+        Ok(mut stat_block) => {
+            stat_keys = stat_block.stat_keys();
 
-            if let Some(stats_object) = stat_block
-                .stats
-                .as_ref()
-                .and_then(|stats| stats.as_object())
-            {
-                for (stat, value) in stats_object {
-                    if let Some(int_value) = value.as_i64() {
-                        let stat_mod = if let Some(formula) = stat_block.modifier_formula.clone() {
-                            let formula_with_presets: String = PRESET_FORMULAS
-                                .iter()
-                                .fold(formula.to_string(), |acc, (k, v)| acc.replace(k, v));
-
-                            formula_with_presets.replace("stat", &(int_value).to_string())
-                        } else {
-                            (int_value / 10).to_string()
-                        };
-
-                        // str_replaced = str_replaced.replace(stat, &stat_mod.to_string());
-                        str_replaced = replace_stat(&str_replaced, stat, &stat_mod.to_string());
-                    }
-                }
-            }
-            if let Some(special_stats_object) = stat_block
-                .special_stats
-                .as_ref()
-                .and_then(|special_stats| special_stats.as_object())
-            {
-                for (special_stat, value) in special_stats_object {
-                    str_replaced = replace_stat(&str_replaced, special_stat, &value.to_string());
-                    // println!("special stat replaced: {special_stat}: {value}")
-                }
-            }
+            let modifier_formula = campaigns::modifier_formula_for(character, Some(&stat_block));
+            let saved_rolls = inventory::apply_equipment(character, &mut stat_block);
+
+            str_replaced = substitute_stats(
+                &str_replaced,
+                &stat_block,
+                modifier_formula.as_deref(),
+                saved_rolls.as_deref(),
+            )?;
 
             println!("{}", str_replaced);
         }
@@ -1317,14 +1648,30 @@ pub async fn roll_with_char_sheet(
         }
     }
 
-    Ok(dice::eval_roll(&str_replaced)?)
+    let result =
+        conditions::roll_with_conditions(character, &dice, &str_replaced, &stat_keys, mode)?;
+
+    sessions::log_event(
+        character,
+        SessionEventKind::Roll,
+        &dice,
+        Some(result.result),
+    );
+
+    Ok(result)
 }
 
 static ROLL_CHANNEL_FLAG: &str = "rollChannel";
 static ROLL_SERVER_ID_FLAG: &str = "rollServer";
 
 #[poise::command(slash_command, prefix_command)]
-pub async fn roll(ctx: Context<'_>, dice_expression: Option<String>) -> Result<(), Error> {
+pub async fn roll(
+    ctx: Context<'_>,
+    #[autocomplete = "autocomplete::saved_roll"] dice_expression: Option<String>,
+    #[description = "Roll the whole check twice and keep the best or worst"] mode: Option<RollMode>,
+) -> Result<(), Error> {
+    let mode = mode.unwrap_or_default();
+
     let placeholder = CreateReply::default()
         .content("*Thinking, please wait...*")
         .ephemeral(true);
@@ -1355,9 +1702,42 @@ pub async fn roll(ctx: Context<'_>, dice_expression: Option<String>) -> Result<(
             }
         }
 
-        roll_with_char_sheet(Some(ctx.serenity_context()), dice_expression, &character).await?
+        // A bare name that isn't a stat is probably a saved roll, so typos in it are forgiven
+        let saved_rolls = autocomplete::saved_roll_names(&character);
+        let stat_keys = super::get_sheet::<StatBlock>(Some(ctx.serenity_context()), &character)
+            .await
+            .map(|stat_block| stat_block.stat_keys())
+            .unwrap_or_default();
+
+        let dice_expression = dice_expression.map(|expression| {
+            let is_name = expression
+                .chars()
+                .all(|c| c.is_alphabetic() || c == ' ' || c == '_');
+
+            if !is_name || is_stat_key(&expression, &stat_keys) {
+                return expression;
+            }
+
+            match fuzzy::resolve_typo(&expression, saved_rolls.iter().map(String::as_str)) {
+                Some(saved_roll) => saved_roll.to_string(),
+                None => expression,
+            }
+        });
+
+        roll_with_char_sheet(
+            Some(ctx.serenity_context()),
+            dice_expression,
+            &character,
+            mode,
+        )
+        .await?
     } else {
-        crate::dice::eval_roll(&dice_expression.unwrap_or("1d100".to_string()))?
+        let (mode, rolls) = StackingRule::default().resolve(
+            (mode == RollMode::Advantage) as usize,
+            (mode == RollMode::Disadvantage) as usize,
+        );
+
+        roll_mode::roll(&dice_expression.unwrap_or("1d100".to_string()), mode, rolls)?
     };
 
     let channel = if let Some(guild_id) = ctx.guild().map(|g| g.id) {
@@ -1555,7 +1935,10 @@ pub async fn create_character(
 }
 
 #[poise::command(slash_command, prefix_command)]
-pub async fn delete_character(ctx: Context<'_>, character_id: i32) -> Result<(), Error> {
+pub async fn delete_character(
+    ctx: Context<'_>,
+    #[autocomplete = "autocomplete::character_id"] character_id: i32,
+) -> Result<(), Error> {
     let author = &ctx.author();
     let user_id = author.id.get();
 
@@ -1589,7 +1972,10 @@ pub async fn delete_character(ctx: Context<'_>, character_id: i32) -> Result<(),
     Ok(())
 }
 #[poise::command(slash_command, prefix_command)]
-pub async fn select_character(ctx: Context<'_>, character_id: i32) -> Result<(), Error> {
+pub async fn select_character(
+    ctx: Context<'_>,
+    #[autocomplete = "autocomplete::character_id"] character_id: i32,
+) -> Result<(), Error> {
     let placeholder = ctx
         .send(
             CreateReply::default()
@@ -1605,9 +1991,7 @@ pub async fn select_character(ctx: Context<'_>, character_id: i32) -> Result<(),
     let character = db::characters::get(character_id);
 
     if let Ok(char) = character {
-        let comparison_user_id = Some(user.id.clone());
-
-        if char.user_id.eq(&comparison_user_id)
+        if db::character_owners::is_owner(&char, user_id)?
             || common::check_admin(
                 ctx,
                 ctx.guild_id().ok_or(RpgError::NoGuildId)?,
@@ -1705,6 +2089,25 @@ pub async fn characters(ctx: Context<'_>) -> Result<(), Error> {
     let mut character_messages: Vec<String> = vec![];
 
     for character in characters {
+        let shared = if character.user_id.as_deref() == Some(user_id.to_string().as_str()) {
+            let co_owners = db::character_owners::get_for_character(character.id)?;
+
+            if co_owners.is_empty() {
+                "".to_string()
+            } else {
+                format!(
+                    " (shared with {})",
+                    co_owners
+                        .iter()
+                        .map(|id| format!("<@{id}>"))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                )
+            }
+        } else {
+            " (shared with you)".to_string()
+        };
+
         let character_name = character.name.unwrap_or("No name provided".to_string());
         let character_id = character.id.to_string();
         let channel_id = character
@@ -1712,7 +2115,7 @@ pub async fn characters(ctx: Context<'_>) -> Result<(), Error> {
             .unwrap_or("No channel ID".to_string());
 
         character_messages.push(format!(
-            "- **{character_name}**\n-# <#{channel_id}> [#{character_id}]"
+            "- **{character_name}**{shared}\n-# <#{channel_id}> [#{character_id}]"
         ))
     }
 
@@ -1735,83 +2138,6 @@ pub async fn characters(ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
-#[poise::command(slash_command, prefix_command)]
-pub async fn level_up(ctx: Context<'_>, num_levels: i32) -> Result<(), Error> {
-    let original_stat_block_message = ctx
-        .send(
-            CreateReply::default()
-                .content("Thinking... please wait")
-                .ephemeral(true),
-        )
-        .await?;
-
-    let msg = ctx.say("*Thinking, please wait...*").await?;
-
-    let stat_block: StatBlock = super::get_sheet_of_sender(&ctx)
-        .await?
-        .ok_or(RpgError::NoCharacterSheet)?;
-
-    let stats: Value = serde_json::from_str(
-        &stat_block
-            .sheet_info
-            .jsonified_message
-            .expect("Stat block should always generate json"),
-    )?;
-
-    let reply = CreateReply::default()
-        .content(format!(
-            "Original stat block text:\n```{}```",
-            stat_block
-                .sheet_info
-                .original_message
-                .expect("Stat block should always have an original message")
-        ))
-        .ephemeral(true);
-
-    let _ = original_stat_block_message.edit(ctx, reply).await;
-
-    let energy_die = stats
-        .get("energy_die_per_level")
-        .ok_or(RpgError::NoEnergyDie)?
-        .to_string();
-    let magic_die = stats
-        .get("magic_die_per_level")
-        .ok_or(RpgError::NoMagicDie)?
-        .to_string();
-    let training_die = stats
-        .get("training_die_per_level")
-        .ok_or(RpgError::NoTrainingDie)?
-        .to_string();
-
-    let mut energy_die_sum: f64 = 0.0;
-    let mut magic_die_sum: f64 = 0.0;
-    let mut training_die_sum: f64 = 0.0;
-
-    let mut message = format!(
-        "Per Level: \nEnergy: {energy_die} \\| Magic: {magic_die} \\| Training: {training_die}\n\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\\_\nRolls:"
-    );
-
-    for i in 1..num_levels + 1 {
-        let energy_die_result = dice::eval_roll(&energy_die.as_str())?.result;
-        let magic_die_result = dice::eval_roll(&magic_die.as_str())?.result;
-        let training_die_result = dice::eval_roll(&training_die.as_str())?.result;
-
-        energy_die_sum = energy_die_sum + &energy_die_result;
-        magic_die_sum = magic_die_sum + &magic_die_result;
-        training_die_sum = training_die_sum + &training_die_result;
-
-        message = format!(
-            "{message}\n{i}.  ⚡️ {energy_die_result}    🐇 {magic_die_result}    🏋 {training_die_result}"
-        );
-    }
-    message = message.replace('"', "");
-    message = format!("{message}\n\n**Total**:\n    ⚡️ {energy_die_sum}    🐇 {magic_die_sum}    🏋 {training_die_sum}");
-    let reply = CreateReply::default().content(message);
-    msg.edit(ctx, reply).await?;
-
-    return Ok(());
-}
-
 #[poise::command(slash_command)]
 pub async fn pull_stats(ctx: Context<'_>) -> Result<(), Error> {
     let thinking_message = CreateReply::default()
@@ -1899,7 +2225,10 @@ pub async fn unset_roll_channel(ctx: Context<'_>) -> Result<(), Error> {
     slash_command,
     // description_localized = "Pull a single stat from your character sheet"
 )]
-pub async fn pull_stat(ctx: Context<'_>, stat_name: String) -> Result<(), Error> {
+pub async fn pull_stat(
+    ctx: Context<'_>,
+    #[autocomplete = "autocomplete::stat_name"] stat_name: String,
+) -> Result<(), Error> {
     let stat_block_thinking_message = CreateReply::default()
         .content("*Thinking, please wait...*")
         .ephemeral(true);
@@ -1921,7 +2250,28 @@ pub async fn pull_stat(ctx: Context<'_>, stat_name: String) -> Result<(), Error>
 
     // println!("```json\n{}```", response_message);
 
-    let reply = CreateReply::default().content(stats.get(stat_name).unwrap().to_string());
+    let sections = [
+        Some(&stats),
+        stats.get("stats"),
+        stats.get("special_stats"),
+    ];
+
+    let names: Vec<&str> = sections
+        .iter()
+        .flatten()
+        .filter_map(|section| section.as_object())
+        .flat_map(|section| section.keys().map(String::as_str))
+        .collect();
+
+    let stat_key = fuzzy::resolve(&stat_name, names).ok_or(RpgError::StatNotFound)?;
+
+    let value = sections
+        .iter()
+        .flatten()
+        .find_map(|section| section.get(stat_key))
+        .ok_or(RpgError::StatNotFound)?;
+
+    let reply = CreateReply::default().content(format!("{stat_key}: {value}"));
     msg.edit(ctx, reply).await?;
 
     return Ok(());
@@ -1941,14 +2291,40 @@ pub fn commands() -> Vec<Command<crate::common::Data, crate::common::Error>> {
         sub_mana(),
         status(),
         status_admin(),
+        party::party(),
         characters(),
         delete_character(),
         select_character(),
+        ownership::transfer_character(),
+        ownership::share_character(),
+        ownership::unshare_character(),
         create_character(),
         set_spells(),
-        level_up(),
+        level_up::level_up(),
+        level_up::undo_level_up(),
+        level_up::level_history(),
+        xp::xp(),
         roll(),
+        cast_spell(),
+        list_spells(),
         // edit_character(),
         edit_saved_rolls(),
+        gauges::gauge(),
+        end_turn(),
+        actions::actions(),
+        rest::rest(),
+        undo::undo(),
+        campaigns::campaign(),
+        npcs::npc(),
+        sessions::session(),
+        conditions::condition(),
+        inventory::inventory(),
+        currency::pay(),
+        currency::wallet(),
+        export::export_character_command(),
+        export::import_character_command(),
+        revisions::character_history(),
+        revisions::character_rollback(),
+        revisions::edit_stat_block(),
     ];
 }
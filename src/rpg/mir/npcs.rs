@@ -0,0 +1,299 @@
+use poise::CreateReply;
+use regex::Regex;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::common::Context;
+use crate::common::Error;
+use crate::db;
+use crate::db::models::NewNpc;
+use crate::db::models::Npc;
+use crate::dice;
+
+use super::super::CharacterSheetable;
+use super::super::RpgError;
+use super::campaigns;
+use super::gauges;
+use super::stat_block::parse_stat_list;
+use super::stat_block::StatBlock;
+
+/// Most instances a template can be spawned into at once
+static MAX_SPAWN_COUNT: i32 = 20;
+
+/// Gets one of the author's NPCs by name (case insensitive)
+fn get_npc(ctx: &Context<'_>, name: &str) -> Result<Npc, Error> {
+    db::npcs::get_by_name(ctx.author().id.get(), name.trim())
+        .map_err(|_| Box::new(RpgError::NpcNotFound) as Error)
+}
+
+/// An NPC's stat block - instances use their template's
+fn stat_block_of(npc: &Npc) -> Result<StatBlock, Error> {
+    let json = match (&npc.stat_block, npc.template_id) {
+        (Some(json), _) => json.clone(),
+        (None, Some(template_id)) => db::npcs::get(template_id)?
+            .stat_block
+            .ok_or(RpgError::NoCharacterSheetData)?,
+        (None, None) => return Err(Box::new(RpgError::NoCharacterSheetData)),
+    };
+
+    StatBlock::from_json(None, &json)
+}
+
+fn max_hp_of(npc: &Npc) -> Option<i64> {
+    stat_block_of(npc).ok().and_then(|stat_block| stat_block.max_hp)
+}
+
+fn describe_hp(npc: &Npc) -> String {
+    match (npc.current_hp, max_hp_of(npc)) {
+        (Some(hp), Some(max_hp)) => format!("❤️ {hp} / {max_hp}"),
+        (Some(hp), None) => format!("❤️ {hp}"),
+        (None, Some(max_hp)) => format!("❤️ {max_hp} / {max_hp}"),
+        (None, None) => "No HP".to_string(),
+    }
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands("create", "spawn", "roll", "hp", "list", "remove"),
+    subcommand_required
+)]
+pub async fn npc(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Create an NPC, or a template (e.g. Goblin) to spawn copies of
+#[poise::command(slash_command, prefix_command)]
+#[allow(clippy::too_many_arguments)]
+pub async fn create(
+    ctx: Context<'_>,
+    name: String,
+    #[description = "e.g. str 12, dex 14, con 10"] stats: Option<String>,
+    #[description = "Max HP"] hp: Option<i32>,
+    #[description = "Spawn numbered copies of this NPC with /npc spawn"] template: Option<bool>,
+    #[description = "e.g. 5e - defaults to the campaign's"] modifier_formula: Option<String>,
+    #[description = "e.g. 1d20 - defaults to the campaign's"] default_roll: Option<String>,
+    #[description = "Campaign whose roll defaults this NPC uses"] campaign_id: Option<i32>,
+) -> Result<(), Error> {
+    let owner_id = ctx.author().id.get();
+
+    if db::npcs::get_by_name(owner_id, name.trim()).is_ok() {
+        return Err(Box::new(RpgError::NpcAlreadyExists));
+    }
+
+    if let Some(campaign_id) = campaign_id {
+        db::campaigns::get(campaign_id).map_err(|_| RpgError::CampaignNotFound)?;
+    }
+
+    let stats = parse_stat_list(stats.as_deref().unwrap_or(""))?;
+
+    let mut stat_block = json!({
+        "name": name.trim(),
+        "stats": stats,
+    });
+
+    if let Some(hp) = hp {
+        stat_block["hp"] = Value::from(hp);
+    }
+    if let Some(modifier_formula) = modifier_formula {
+        stat_block["modifier_formula"] = Value::from(modifier_formula);
+    }
+    if let Some(default_roll) = default_roll {
+        stat_block["default_roll"] = Value::from(default_roll);
+    }
+
+    let is_template = template.unwrap_or(false);
+
+    let npc = db::npcs::create(&NewNpc {
+        owner_id: owner_id.to_string(),
+        campaign_id,
+        name: name.trim().to_string(),
+        stat_block: Some(serde_json::to_string(&stat_block)?),
+        is_template,
+        template_id: None,
+        current_hp: if is_template { None } else { hp },
+    })?;
+
+    let content = if is_template {
+        format!(
+            "Created template **{}** - spawn copies of it with /npc spawn {}",
+            npc.name, npc.name
+        )
+    } else {
+        format!("Created NPC **{}**", npc.name)
+    };
+
+    ctx.send(CreateReply::default().content(content).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Create numbered copies of a template, each with their own HP
+#[poise::command(slash_command, prefix_command)]
+pub async fn spawn(
+    ctx: Context<'_>,
+    template: String,
+    #[description = "Defaults to 1"] count: Option<i32>,
+) -> Result<(), Error> {
+    let template = get_npc(&ctx, &template)?;
+
+    if !template.is_template {
+        return Err(Box::new(RpgError::NotNpcTemplate));
+    }
+
+    let count = count.unwrap_or(1).clamp(1, MAX_SPAWN_COUNT);
+
+    // Carry on numbering from the last instance, so Goblin #3 is never reused while it's still around
+    let number_pattern = Regex::new(r"#(\d+)$").unwrap();
+    let last_number = db::npcs::get_instances(template.id)?
+        .iter()
+        .filter_map(|instance| {
+            number_pattern
+                .captures(&instance.name)
+                .and_then(|captures| captures[1].parse::<i32>().ok())
+        })
+        .max()
+        .unwrap_or(0);
+
+    let max_hp = max_hp_of(&template).map(|hp| hp as i32);
+
+    let mut names = vec![];
+
+    for number in last_number + 1..=last_number + count {
+        let instance = db::npcs::create(&NewNpc {
+            owner_id: template.owner_id.clone(),
+            campaign_id: template.campaign_id,
+            name: format!("{} #{number}", template.name),
+            stat_block: None,
+            is_template: false,
+            template_id: Some(template.id),
+            current_hp: max_hp,
+        })?;
+
+        names.push(instance.name);
+    }
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!("Spawned {}", names.join(", ")))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Roll for an NPC, using its stats like /roll does for characters
+#[poise::command(slash_command, prefix_command)]
+pub async fn roll(
+    ctx: Context<'_>,
+    name: String,
+    #[description = "Defaults to the NPC's default roll"] dice_expression: Option<String>,
+) -> Result<(), Error> {
+    let npc = get_npc(&ctx, &name)?;
+    let stat_block = stat_block_of(&npc)?;
+
+    let campaign = npc
+        .campaign_id
+        .and_then(|campaign_id| db::campaigns::get(campaign_id).ok());
+
+    let dice = dice_expression
+        .or(stat_block.default_roll.clone())
+        .or(campaign
+            .as_ref()
+            .and_then(|campaign| campaign.default_roll_die.clone()))
+        .filter(|dice| !dice.is_empty())
+        .unwrap_or(campaigns::DEFAULT_ROLL.to_string());
+
+    let modifier_formula = stat_block
+        .modifier_formula
+        .clone()
+        .or(campaign.and_then(|campaign| campaign.stat_modifier_formula))
+        .filter(|formula| !formula.is_empty());
+
+    let str_replaced = super::substitute_stats(
+        &super::normalise_dice(&dice),
+        &stat_block,
+        modifier_formula.as_deref(),
+        None,
+    )?;
+
+    let result = dice::eval_roll(&str_replaced)?;
+
+    dice::output_roll_message(ctx, result.message, npc.name, None).await?;
+
+    Ok(())
+}
+
+/// Change an NPC's HP, e.g. -5, +3 or 12
+#[poise::command(slash_command, prefix_command)]
+pub async fn hp(ctx: Context<'_>, name: String, expression: String) -> Result<(), Error> {
+    let mut npc = get_npc(&ctx, &name)?;
+
+    let max_hp = max_hp_of(&npc).map(|hp| hp as i32);
+    let current = npc
+        .current_hp
+        .or(max_hp)
+        .ok_or(RpgError::NoCharacterSheetData)?;
+
+    let value = gauges::eval_modifier(&expression, current)?;
+
+    npc.current_hp = Some(match max_hp {
+        Some(max_hp) => value.clamp(0, max_hp),
+        None => value.max(0),
+    });
+
+    db::npcs::update(&npc)?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!("**{}**: {}", npc.name, describe_hp(&npc)))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command)]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    let npcs = db::npcs::get_for_owner(ctx.author().id.get())?;
+
+    let content = if npcs.is_empty() {
+        "You don't have any NPCs - make one with /npc create".to_string()
+    } else {
+        npcs.iter()
+            .map(|npc| {
+                if npc.is_template {
+                    format!("**{}** (template)", npc.name)
+                } else {
+                    format!("**{}** {}", npc.name, describe_hp(npc))
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+
+    ctx.send(CreateReply::default().content(content).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Removing a template also removes every copy spawned from it
+#[poise::command(slash_command, prefix_command)]
+pub async fn remove(ctx: Context<'_>, name: String) -> Result<(), Error> {
+    let npc = get_npc(&ctx, &name)?;
+
+    db::npcs::delete(npc.id)?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!("Removed {}", npc.name))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
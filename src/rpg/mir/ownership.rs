@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+use poise::serenity_prelude::ButtonStyle;
+use poise::serenity_prelude::CreateActionRow;
+use poise::serenity_prelude::Mentionable;
+use poise::serenity_prelude::User;
+use poise::CreateReply;
+use tokio::sync::Mutex;
+
+use crate::common;
+use crate::common::Context;
+use crate::common::Error;
+use crate::db;
+use crate::db::models::Character;
+
+use super::super::get_user_character;
+use super::super::RpgError;
+use super::event_handlers::TransferCharacterEvent;
+use super::event_handlers::TransferCharacterEventParams;
+
+/// A transfer waiting on the recipient to accept or decline
+struct PendingTransfer {
+    from_user_id: u64,
+    to_user_id: u64,
+}
+
+lazy_static! {
+    // Keyed by character id - kept out of the button ids, which are limited to 100 characters
+    static ref PENDING_TRANSFERS: Mutex<HashMap<i32, PendingTransfer>> =
+        Mutex::new(HashMap::new());
+}
+
+fn character_name(character: &Character) -> String {
+    character.name.clone().unwrap_or("No name?".to_string())
+}
+
+/// Only a character's main owner (or a server admin) can give it away or share it
+async fn get_owned_character(ctx: &Context<'_>, character_id: i32) -> Result<Character, Error> {
+    let character = db::characters::get(character_id)?;
+
+    let is_main_owner = character.user_id.as_deref() == Some(ctx.author().id.to_string().as_str());
+
+    if is_main_owner {
+        return Ok(character);
+    }
+
+    // Outside a server (e.g. in DMs) nobody is an admin
+    let is_admin = match ctx.guild_id() {
+        Some(guild_id) => common::check_admin(*ctx, guild_id, ctx.author().id).await,
+        None => false,
+    };
+
+    if !is_admin {
+        return Err(Box::new(RpgError::NotCharacterOwner));
+    }
+
+    Ok(character)
+}
+
+/// Your selected character, or another character if you own it or DM its campaign
+pub async fn get_managed_character(
+    ctx: &Context<'_>,
+    character_id: Option<i32>,
+) -> Result<Character, Error> {
+    let Some(character_id) = character_id else {
+        return Ok(get_user_character(ctx)
+            .await?
+            .ok_or(RpgError::NoCharacterSelected)?);
+    };
+
+    let character = db::characters::get(character_id)?;
+    let author_id = ctx.author().id.get();
+
+    let is_dm = character
+        .campaign_id
+        .and_then(|campaign_id| db::campaigns::get(campaign_id).ok())
+        .is_some_and(|campaign| campaign.dungeon_master_id == Some(author_id.to_string()));
+
+    if !is_dm && !db::character_owners::is_owner(&character, author_id)? {
+        return Err(Box::new(RpgError::NotCharacterOwner));
+    }
+
+    Ok(character)
+}
+
+/// Deselects a character for a user who can no longer use it
+fn deselect_for(user_id: u64, character_id: i32) -> Result<(), Error> {
+    if let Ok(user) = db::users::get(user_id) {
+        if user.selected_character == Some(character_id) {
+            db::users::unset_character(&user)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Accepts or declines a pending transfer, returning the message to show in its place
+/// The recipient can accept or decline, the sender can only cancel
+pub async fn resolve_transfer(
+    character_id: i32,
+    responder_id: u64,
+    accept: bool,
+) -> Result<Option<String>, Error> {
+    let mut pending_transfers = PENDING_TRANSFERS.lock().await;
+
+    let Some(transfer) = pending_transfers.get(&character_id) else {
+        return Ok(Some("This transfer has expired".to_string()));
+    };
+
+    let is_recipient = responder_id == transfer.to_user_id;
+    let is_sender = responder_id == transfer.from_user_id;
+
+    // The recipient can accept or decline, the sender can only cancel
+    if !is_recipient && (!is_sender || accept) {
+        return Ok(None);
+    }
+
+    let transfer = pending_transfers
+        .remove(&character_id)
+        .expect("Transfer was just found");
+
+    let mut character = db::characters::get(character_id)?;
+
+    if !accept {
+        return Ok(Some(format!(
+            "Transfer of {} was {}",
+            character_name(&character),
+            if is_recipient { "declined" } else { "cancelled" }
+        )));
+    }
+
+    character.user_id = Some(transfer.to_user_id.to_string());
+    db::characters::update(&character)?;
+
+    // The new owner doesn't need to be a co-owner as well
+    db::character_owners::remove(character_id, transfer.to_user_id)?;
+    deselect_for(transfer.from_user_id, character_id)?;
+
+    Ok(Some(format!(
+        "{} now belongs to {}",
+        character_name(&character),
+        poise::serenity_prelude::UserId::new(transfer.to_user_id).mention()
+    )))
+}
+
+#[poise::command(slash_command, prefix_command)]
+pub async fn transfer_character(
+    ctx: Context<'_>,
+    character_id: i32,
+    #[description = "Who to give the character to"] user: User,
+) -> Result<(), Error> {
+    let character = get_owned_character(&ctx, character_id).await?;
+
+    let from_user_id = character
+        .user_id
+        .as_deref()
+        .and_then(|id| id.parse::<u64>().ok())
+        .unwrap_or(ctx.author().id.get());
+
+    PENDING_TRANSFERS.lock().await.insert(
+        character_id,
+        PendingTransfer {
+            from_user_id,
+            to_user_id: user.id.get(),
+        },
+    );
+
+    let buttons = [(true, "Accept", ButtonStyle::Success), (false, "Decline", ButtonStyle::Danger)]
+        .map(|(accept, label, style)| {
+            TransferCharacterEvent::create_button(
+                label,
+                &TransferCharacterEventParams {
+                    character_id,
+                    accept,
+                },
+                style,
+            )
+            .expect("How fail")
+        });
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "{}, {} wants to give you {}",
+                user.mention(),
+                ctx.author().mention(),
+                character_name(&character)
+            ))
+            .components(vec![CreateActionRow::Buttons(buttons.to_vec())]),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Lets another user select and roll for one of your characters
+#[poise::command(slash_command, prefix_command)]
+pub async fn share_character(ctx: Context<'_>, character_id: i32, user: User) -> Result<(), Error> {
+    let character = get_owned_character(&ctx, character_id).await?;
+
+    if character.user_id.as_deref() == Some(user.id.to_string().as_str()) {
+        return Err(Box::new(RpgError::AlreadyCharacterOwner));
+    }
+
+    db::character_owners::add(character_id, user.id.get())?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "Shared {} with {} - they can now select it with /select_character {character_id}",
+                character_name(&character),
+                user.mention()
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Stops sharing a character - co-owners can also use this to remove themselves
+#[poise::command(slash_command, prefix_command)]
+pub async fn unshare_character(
+    ctx: Context<'_>,
+    character_id: i32,
+    user: User,
+) -> Result<(), Error> {
+    let character = if user.id == ctx.author().id {
+        db::characters::get(character_id)?
+    } else {
+        get_owned_character(&ctx, character_id).await?
+    };
+
+    db::character_owners::remove(character_id, user.id.get())?;
+    deselect_for(user.id.get(), character_id)?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "{} is no longer shared with {}",
+                character_name(&character),
+                user.mention()
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,516 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use lazy_static::lazy_static;
+use poise::serenity_prelude::ButtonStyle;
+use poise::serenity_prelude::ChannelId;
+use poise::serenity_prelude::CreateActionRow;
+use poise::serenity_prelude::CreateEmbed;
+use poise::serenity_prelude::CreateEmbedFooter;
+use poise::serenity_prelude::CreateSelectMenu;
+use poise::serenity_prelude::CreateSelectMenuKind;
+use poise::serenity_prelude::EditMessage;
+use poise::serenity_prelude::MessageId;
+use poise::serenity_prelude::Timestamp;
+use poise::serenity_prelude::UserId;
+use poise::CreateReply;
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use crate::common::draw_bar;
+use crate::common::Context;
+use crate::common::Error;
+use crate::db;
+use crate::db::models::Campaign;
+use crate::db::models::Character;
+
+use super::super::get_sheet;
+use super::super::RpgError;
+use super::campaigns;
+use super::event_handlers::PartyFocusEvent;
+use super::event_handlers::PartyFocusEventParams;
+use super::event_handlers::PartyResourceEvent;
+use super::event_handlers::PartyResourceEventParams;
+use super::gauges;
+use super::revisions::RevisionSource;
+use super::sessions;
+use super::sessions::SessionEventKind;
+use super::stat_block::update_stat_block_values;
+use super::stat_block::StatBlock;
+use super::undo;
+use super::ACTIVE_SPELLS;
+
+/// Bars are shorter than on the status embed so every character fits on one embed
+static PARTY_BAR_LENGTH: usize = 8;
+
+static REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How many times a dashboard refreshes itself before it expires and /party has to be run again
+static REFRESH_COUNT: u32 = 30;
+
+/// Discord's limits on the embeds of a single message
+static MAX_EMBED_FIELDS: usize = 25;
+static MAX_FIELD_VALUE_LENGTH: usize = 1024;
+static MAX_EMBEDS_LENGTH: usize = 6000;
+
+static HP_CHANGES: [i32; 4] = [-10, -5, 5, 10];
+static MANA_CHANGES: [i32; 4] = [-50, -25, 25, 50];
+
+/// Which campaign a dashboard message shows, and which character has been picked from its menu
+#[derive(Clone, Copy)]
+struct Dashboard {
+    campaign_id: i32,
+    focused_character_id: Option<i32>,
+}
+
+lazy_static! {
+    static ref DASHBOARDS: Mutex<HashMap<MessageId, Dashboard>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PartyResource {
+    Hp,
+    Mana,
+}
+
+impl PartyResource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PartyResource::Hp => "hp",
+            PartyResource::Mana => "mana",
+        }
+    }
+
+    pub fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "hp" => Some(PartyResource::Hp),
+            "mana" => Some(PartyResource::Mana),
+            _ => None,
+        }
+    }
+}
+
+fn resource_line(icon: &str, bar_emoji: &str, current: i64, max: i64) -> String {
+    let bar = draw_bar(
+        current.min(max) as i32,
+        max as i32,
+        PARTY_BAR_LENGTH,
+        bar_emoji,
+        "⬛",
+    );
+
+    format!("{icon} {bar} ``{current} / {max}``")
+}
+
+/// A few lines summarising a character's resources for the party dashboard
+async fn party_member_summary(
+    ctx: &poise::serenity_prelude::Context,
+    character: &Character,
+) -> String {
+    let mut lines = vec![];
+
+    match get_sheet::<StatBlock>(Some(ctx), character).await {
+        Ok(stat_block) => {
+            if let (Some(hp), Some(max_hp)) = (stat_block.hp, stat_block.max_hp) {
+                lines.push(resource_line("❤️", "🟥", hp, max_hp));
+            }
+
+            if let Some(energy_pool) = stat_block.energy_pool {
+                let mana = character.mana.map(|mana| mana as i64).unwrap_or(energy_pool);
+                lines.push(resource_line("🪄", "🟦", mana, energy_pool));
+            }
+        }
+        Err(e) => lines.push(format!("Couldn't read stat block: {e}")),
+    }
+
+    for gauge in db::gauges::get_for_character(character.id).unwrap_or_default() {
+        lines.push(gauges::gauge_bar_line_of_length(&gauge, PARTY_BAR_LENGTH));
+    }
+
+    if let Some(active_spells) = ACTIVE_SPELLS.lock().await.get(&character.id) {
+        if !active_spells.is_empty() {
+            let names = active_spells
+                .iter()
+                .map(|spell| spell.name.clone().unwrap_or("Unnamed spell".to_string()))
+                .collect::<Vec<String>>()
+                .join(", ");
+
+            lines.push(format!("✨ {names}"));
+        }
+    }
+
+    let conditions = db::conditions::get_for_character(character.id).unwrap_or_default();
+
+    if !conditions.is_empty() {
+        let names = conditions
+            .iter()
+            .map(|condition| condition.name.clone())
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        lines.push(format!("🩹 {names}"));
+    }
+
+    if lines.is_empty() {
+        "Nothing to show".to_string()
+    } else {
+        lines.join("\n")
+    }
+}
+
+fn truncate_field_value(value: String) -> String {
+    if value.chars().count() <= MAX_FIELD_VALUE_LENGTH {
+        return value;
+    }
+
+    value.chars().take(MAX_FIELD_VALUE_LENGTH - 3).collect::<String>() + "..."
+}
+
+/// How many characters of an embed count towards discord's 6000 character limit
+fn embed_length(embed: &CreateEmbed) -> usize {
+    let Ok(embed) = serde_json::to_value(embed) else {
+        return 0;
+    };
+
+    let text_length = |value: &Value, pointer: &str| {
+        value
+            .pointer(pointer)
+            .and_then(Value::as_str)
+            .map_or(0, |text| text.chars().count())
+    };
+
+    let fields_length: usize = embed
+        .get("fields")
+        .and_then(Value::as_array)
+        .map_or(0, |fields| {
+            fields
+                .iter()
+                .map(|field| text_length(field, "/name") + text_length(field, "/value"))
+                .sum()
+        });
+
+    ["/title", "/description", "/footer/text", "/author/name"]
+        .iter()
+        .map(|pointer| text_length(&embed, pointer))
+        .sum::<usize>()
+        + fields_length
+}
+
+fn resource_buttons(
+    character_id: i32,
+    resource: PartyResource,
+    icon: &str,
+    changes: &[i32],
+) -> CreateActionRow {
+    CreateActionRow::Buttons(
+        changes
+            .iter()
+            .map(|change| {
+                PartyResourceEvent::create_button(
+                    &format!("{icon}{change:+}"),
+                    &PartyResourceEventParams {
+                        character_id,
+                        resource: resource.as_str().to_string(),
+                        change: *change,
+                    },
+                    if *change < 0 {
+                        ButtonStyle::Danger
+                    } else {
+                        ButtonStyle::Success
+                    },
+                )
+                .expect("How fail")
+            })
+            .collect(),
+    )
+}
+
+/// Builds the dashboard embeds (the party, then the picked character's full status) and its components
+async fn render_dashboard(
+    ctx: &poise::serenity_prelude::Context,
+    campaign: &Campaign,
+    focused_character_id: Option<i32>,
+) -> Result<(Vec<CreateEmbed>, Vec<CreateActionRow>), Error> {
+    // Discord allows at most 25 fields per embed and 25 options per menu
+    let characters: Vec<Character> = db::characters::get_by_campaign(campaign.id)?
+        .into_iter()
+        .take(MAX_EMBED_FIELDS)
+        .collect();
+
+    let focused = focused_character_id
+        .and_then(|id| characters.iter().find(|character| character.id == id));
+
+    let status_embed = match focused {
+        Some(character) => Some(super::generate_status_embed(ctx, character).await?),
+        None => None,
+    };
+
+    let mut party_embed = CreateEmbed::default()
+        .title(format!("🗺️ {}", campaign.name))
+        .footer(CreateEmbedFooter::new("Refreshes every minute for half an hour"))
+        .timestamp(Timestamp::now());
+
+    if characters.is_empty() {
+        party_embed = party_embed.description("No characters have joined this campaign yet");
+    }
+
+    // Whatever the status embed leaves of the message's 6000 characters, with room kept for the note below
+    let mut length_left = MAX_EMBEDS_LENGTH
+        .saturating_sub(embed_length(&party_embed) + status_embed.as_ref().map_or(0, embed_length))
+        .saturating_sub(100);
+
+    for (shown, character) in characters.iter().enumerate() {
+        let name = format!(
+            "{} [#{}]",
+            character.name.clone().unwrap_or("No name?".to_string()),
+            character.id
+        );
+        let summary = truncate_field_value(party_member_summary(ctx, character).await);
+
+        let length = name.chars().count() + summary.chars().count();
+
+        if length > length_left {
+            party_embed = party_embed.description(format!(
+                "{} more characters didn't fit, pick them from the menu to see them",
+                characters.len() - shown
+            ));
+            break;
+        }
+
+        length_left -= length;
+        party_embed = party_embed.field(name, summary, false);
+    }
+
+    let mut embeds = vec![party_embed];
+    let mut rows = vec![];
+
+    if !characters.is_empty() {
+        let options = characters
+            .iter()
+            .map(|character| {
+                PartyFocusEvent::create_select_item(
+                    &character.name.clone().unwrap_or("No name?".to_string()),
+                    &PartyFocusEventParams {
+                        character_id: Some(character.id),
+                    },
+                )
+                .expect("How fail")
+            })
+            .collect();
+
+        rows.push(CreateActionRow::SelectMenu(
+            CreateSelectMenu::new("party_dropdown", CreateSelectMenuKind::String { options })
+                .placeholder("Show a character's status..."),
+        ));
+    }
+
+    if let (Some(character), Some(status_embed)) = (focused, status_embed) {
+        embeds.push(status_embed);
+
+        rows.push(resource_buttons(
+            character.id,
+            PartyResource::Hp,
+            "❤️",
+            &HP_CHANGES,
+        ));
+        rows.push(resource_buttons(
+            character.id,
+            PartyResource::Mana,
+            "🪄",
+            &MANA_CHANGES,
+        ));
+    }
+
+    rows.push(CreateActionRow::Buttons(vec![PartyFocusEvent::create_button(
+        " Refresh ",
+        &PartyFocusEventParams { character_id: None },
+    )
+    .expect("How fail")]));
+
+    Ok((embeds, rows))
+}
+
+async fn edit_dashboard(
+    ctx: &poise::serenity_prelude::Context,
+    channel_id: ChannelId,
+    message_id: MessageId,
+    dashboard: Dashboard,
+) -> Result<(), Error> {
+    let campaign = db::campaigns::get(dashboard.campaign_id)?;
+
+    let (embeds, rows) = render_dashboard(ctx, &campaign, dashboard.focused_character_id).await?;
+
+    let edited = channel_id
+        .edit_message(
+            ctx,
+            message_id,
+            EditMessage::default().embeds(embeds).components(rows),
+        )
+        .await;
+
+    // Most likely the message was deleted, so there's nothing left to refresh
+    if let Err(e) = edited {
+        DASHBOARDS.lock().await.remove(&message_id);
+        return Err(Box::new(e));
+    }
+
+    Ok(())
+}
+
+/// Re-renders a dashboard after one of its buttons or its menu was used
+/// Only the campaign's DM can use a dashboard
+pub async fn update_dashboard(
+    ctx: &poise::serenity_prelude::Context,
+    channel_id: ChannelId,
+    message_id: MessageId,
+    user_id: UserId,
+    focus: Option<i32>,
+) -> Result<(), Error> {
+    let mut dashboard = DASHBOARDS
+        .lock()
+        .await
+        .get(&message_id)
+        .copied()
+        .ok_or(RpgError::DashboardExpired)?;
+
+    let campaign = db::campaigns::get(dashboard.campaign_id)?;
+
+    if campaign.dungeon_master_id != Some(user_id.to_string()) {
+        return Err(Box::new(RpgError::NotDungeonMaster));
+    }
+
+    if focus.is_some() {
+        dashboard.focused_character_id = focus;
+        DASHBOARDS.lock().await.insert(message_id, dashboard);
+    }
+
+    edit_dashboard(ctx, channel_id, message_id, dashboard).await
+}
+
+/// Applies a dashboard button's HP or mana change to a character, returning the batch to undo it with
+pub async fn change_resource(
+    ctx: &poise::serenity_prelude::Context,
+    message_id: MessageId,
+    user_id: UserId,
+    character_id: i32,
+    resource: PartyResource,
+    change: i32,
+) -> Result<Option<uuid::Uuid>, Error> {
+    let dashboard = DASHBOARDS
+        .lock()
+        .await
+        .get(&message_id)
+        .copied()
+        .ok_or(RpgError::DashboardExpired)?;
+
+    let campaign = db::campaigns::get(dashboard.campaign_id)?;
+
+    if campaign.dungeon_master_id != Some(user_id.to_string()) {
+        return Err(Box::new(RpgError::NotDungeonMaster));
+    }
+
+    let mut character = db::characters::get(character_id)?;
+    let stat_block: StatBlock = get_sheet(Some(ctx), &character).await?;
+
+    let undo_change = match resource {
+        PartyResource::Hp => {
+            let max_hp = stat_block.max_hp.ok_or(RpgError::NoCharacterSheetData)?;
+            let hp = stat_block.hp.unwrap_or(max_hp);
+
+            let new_hp = (hp + change as i64).clamp(0, max_hp);
+
+            let (kind, amount) = if new_hp < hp {
+                (SessionEventKind::Damage, hp - new_hp)
+            } else {
+                (SessionEventKind::Healing, new_hp - hp)
+            };
+
+            if amount > 0 {
+                sessions::log_event(
+                    &character,
+                    kind,
+                    &format!("HP {hp} → {new_hp}"),
+                    Some(amount as f64),
+                );
+            }
+
+            update_stat_block_values(
+                &mut character,
+                &[("current_hp", Value::from(new_hp))],
+                Some(user_id.get()),
+                RevisionSource::Dashboard,
+            )?;
+
+            undo::Change::new(character.id, undo::Resource::Hp, hp, new_hp)
+        }
+        PartyResource::Mana => {
+            let max_mana = stat_block.energy_pool.ok_or(RpgError::NoMaxEnergy)? as i32;
+            let mana = character.mana.unwrap_or(max_mana);
+
+            let new_mana = (mana + change).clamp(0, max_mana);
+
+            character.mana = Some(new_mana);
+
+            undo::Change::new(character.id, undo::Resource::Mana, mana, new_mana)
+        }
+    };
+
+    db::characters::update(&character)?;
+
+    undo::record(user_id, &[undo_change])
+}
+
+/// Shows every character in your campaign on one dashboard (DM only)
+#[poise::command(slash_command, prefix_command)]
+pub async fn party(
+    ctx: Context<'_>,
+    #[description = "Only needed if you DM more than one campaign"] campaign_id: Option<i32>,
+) -> Result<(), Error> {
+    let placeholder = CreateReply::default().content("*Thinking, please wait...*");
+    let placeholder_message = ctx.send(placeholder).await?;
+
+    let campaign = campaigns::get_dm_campaign(&ctx, campaign_id).await?;
+
+    let (embeds, rows) = render_dashboard(ctx.serenity_context(), &campaign, None).await?;
+
+    let reply = embeds
+        .into_iter()
+        .fold(CreateReply::default().content("").components(rows), |reply, embed| {
+            reply.embed(embed)
+        });
+
+    placeholder_message.edit(ctx, reply).await?;
+
+    let message = placeholder_message.message().await?;
+    let (channel_id, message_id) = (message.channel_id, message.id);
+
+    DASHBOARDS.lock().await.insert(
+        message_id,
+        Dashboard {
+            campaign_id: campaign.id,
+            focused_character_id: None,
+        },
+    );
+
+    let serenity_ctx = ctx.serenity_context().clone();
+
+    tokio::spawn(async move {
+        for _ in 0..REFRESH_COUNT {
+            tokio::time::sleep(REFRESH_INTERVAL).await;
+
+            let Some(dashboard) = DASHBOARDS.lock().await.get(&message_id).copied() else {
+                return;
+            };
+
+            if let Err(e) = edit_dashboard(&serenity_ctx, channel_id, message_id, dashboard).await
+            {
+                println!("Stopped refreshing party dashboard {message_id}: {e}");
+                return;
+            }
+        }
+
+        DASHBOARDS.lock().await.remove(&message_id);
+    });
+
+    Ok(())
+}
@@ -0,0 +1,331 @@
+use std::collections::BTreeMap;
+
+use chrono::DateTime;
+use chrono::Utc;
+use poise::serenity_prelude::ChannelId;
+use poise::serenity_prelude::GetMessages;
+use poise::serenity_prelude::MessageId;
+
+use crate::common::Error;
+use crate::db;
+use crate::db::models::Campaign;
+use crate::db::models::Session;
+use crate::db::models::SessionEvent;
+use crate::llm;
+use crate::llm::Message;
+use crate::llm::Role;
+
+use super::sessions::SessionEventKind;
+
+/// Most chat messages read from the session's channel
+static MAX_CHAT_MESSAGES: usize = 500;
+
+/// Most characters of chat and event log given to the LLM
+static MAX_PROMPT_LOG_LENGTH: usize = 12000;
+
+/// Discord embed descriptions can't be longer than this
+pub static MAX_RECAP_LENGTH: usize = 4000;
+
+/// Milliseconds between the unix epoch and the first discord snowflake
+static DISCORD_EPOCH: i64 = 1420070400000;
+
+static RECAP_PROMPT: &str = r#"
+You write recaps of tabletop RPG sessions for the players, in an entertaining but accurate way
+You will be given statistics, a log of rolls/spells/damage/level ups and the chat from the session
+Respond in discord markdown with these sections:
+**Highlights** - the most memorable moments as a short list
+**Best and worst rolls** - who rolled them and what for
+**Characters** - one or two sentences about what each character did
+Only describe things that appear in what you are given, and keep the whole recap under 3500 characters
+"#;
+
+struct RollRecord {
+    character: String,
+    expression: String,
+    total: f64,
+}
+
+#[derive(Default)]
+struct CharacterSummary {
+    rolls: Vec<(String, f64)>,
+    spells: Vec<String>,
+    damage_taken: f64,
+    healing: f64,
+    level_ups: Vec<String>,
+}
+
+/// Numbers pulled out of a session's events - the recap when no LLM is configured
+struct SessionStats {
+    characters: BTreeMap<String, CharacterSummary>,
+    best_roll: Option<RollRecord>,
+    worst_roll: Option<RollRecord>,
+}
+
+fn character_names(events: &[SessionEvent]) -> BTreeMap<i32, String> {
+    let mut names = BTreeMap::new();
+
+    for event in events {
+        names.entry(event.character_id).or_insert_with(|| {
+            db::characters::get(event.character_id)
+                .ok()
+                .and_then(|character| character.name)
+                .unwrap_or("No name?".to_string())
+        });
+    }
+
+    names
+}
+
+fn build_stats(events: &[SessionEvent], names: &BTreeMap<i32, String>) -> SessionStats {
+    let mut stats = SessionStats {
+        characters: BTreeMap::new(),
+        best_roll: None,
+        worst_roll: None,
+    };
+
+    for event in events {
+        let name = names[&event.character_id].clone();
+        let summary = stats.characters.entry(name.clone()).or_default();
+
+        match SessionEventKind::from_str(&event.kind) {
+            Some(SessionEventKind::Roll) => {
+                let total = event.value.unwrap_or(0.0);
+
+                summary.rolls.push((event.description.clone(), total));
+
+                let record = || RollRecord {
+                    character: name.clone(),
+                    expression: event.description.clone(),
+                    total,
+                };
+
+                if stats.best_roll.as_ref().is_none_or(|best| total > best.total) {
+                    stats.best_roll = Some(record());
+                }
+                if stats.worst_roll.as_ref().is_none_or(|worst| total < worst.total) {
+                    stats.worst_roll = Some(record());
+                }
+            }
+            Some(SessionEventKind::Spell) => summary.spells.push(event.description.clone()),
+            Some(SessionEventKind::Damage) => summary.damage_taken += event.value.unwrap_or(0.0),
+            Some(SessionEventKind::Healing) => summary.healing += event.value.unwrap_or(0.0),
+            Some(SessionEventKind::LevelUp) => summary.level_ups.push(event.description.clone()),
+            None => {}
+        }
+    }
+
+    stats
+}
+
+fn describe_character(summary: &CharacterSummary) -> String {
+    let mut parts = vec![];
+
+    if !summary.rolls.is_empty() {
+        let totals = summary.rolls.iter().map(|(_, total)| *total);
+        let best = totals.clone().fold(f64::MIN, f64::max);
+        let worst = totals.fold(f64::MAX, f64::min);
+
+        parts.push(format!(
+            "{} roll{} (best {best}, worst {worst})",
+            summary.rolls.len(),
+            if summary.rolls.len() == 1 { "" } else { "s" }
+        ));
+    }
+    if !summary.spells.is_empty() {
+        parts.push(format!("cast {}", summary.spells.join(", ")));
+    }
+    if summary.damage_taken > 0.0 {
+        parts.push(format!("took {} damage", summary.damage_taken));
+    }
+    if summary.healing > 0.0 {
+        parts.push(format!("healed {}", summary.healing));
+    }
+    for level_up in &summary.level_ups {
+        parts.push(format!("levelled up ({level_up})"));
+    }
+
+    if parts.is_empty() {
+        "Didn't do anything the bot noticed".to_string()
+    } else {
+        parts.join(", ")
+    }
+}
+
+fn render_stats(stats: &SessionStats) -> String {
+    let count = |f: fn(&CharacterSummary) -> usize| -> usize { stats.characters.values().map(f).sum() };
+
+    let mut lines = vec![
+        "**Highlights**".to_string(),
+        format!(
+            "- {} rolls, {} spells cast and {} level ups",
+            count(|summary| summary.rolls.len()),
+            count(|summary| summary.spells.len()),
+            count(|summary| summary.level_ups.len())
+        ),
+    ];
+
+    if let Some(best) = &stats.best_roll {
+        lines.push(format!(
+            "- Best roll: {} rolled **{}** on {}",
+            best.character, best.total, best.expression
+        ));
+    }
+    if let Some(worst) = &stats.worst_roll {
+        lines.push(format!(
+            "- Worst roll: {} rolled **{}** on {}",
+            worst.character, worst.total, worst.expression
+        ));
+    }
+
+    if !stats.characters.is_empty() {
+        lines.push("\n**Characters**".to_string());
+
+        for (name, summary) in &stats.characters {
+            lines.push(format!("- __{name}__: {}", describe_character(summary)));
+        }
+    }
+
+    lines.join("\n")
+}
+
+fn render_event_log(events: &[SessionEvent], names: &BTreeMap<i32, String>) -> String {
+    events
+        .iter()
+        .map(|event| {
+            let value = event
+                .value
+                .map(|value| format!(" = {value}"))
+                .unwrap_or_default();
+
+            format!(
+                "[{}] {} {}: {}{value}",
+                event.created_at.format("%H:%M"),
+                names[&event.character_id],
+                event.kind,
+                event.description
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Keeps the end of a log, which is cut from the front so the most recent lines survive
+fn keep_last_chars(text: &str, max: usize) -> String {
+    let length = text.chars().count();
+
+    if length <= max {
+        return text.to_string();
+    }
+
+    text.chars().skip(length - max).collect()
+}
+
+pub fn truncate_recap(recap: &str) -> String {
+    if recap.chars().count() <= MAX_RECAP_LENGTH {
+        return recap.to_string();
+    }
+
+    recap.chars().take(MAX_RECAP_LENGTH - 3).collect::<String>() + "..."
+}
+
+fn snowflake_at(time: DateTime<Utc>) -> MessageId {
+    MessageId::new(((time.timestamp_millis() - DISCORD_EPOCH).max(1) << 22) as u64)
+}
+
+/// What players said in the session's channel while it was running
+async fn fetch_chat(
+    ctx: &poise::serenity_prelude::Context,
+    channel_id: ChannelId,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<String>, Error> {
+    let mut after = snowflake_at(from);
+    let end = snowflake_at(to);
+    let mut messages = vec![];
+
+    while messages.len() < MAX_CHAT_MESSAGES {
+        let mut batch = channel_id
+            .messages(ctx, GetMessages::new().after(after).limit(100))
+            .await?;
+
+        if batch.is_empty() {
+            break;
+        }
+
+        batch.sort_by_key(|message| message.id);
+        after = batch.last().expect("Batch isn't empty").id;
+
+        messages.extend(batch.into_iter().filter(|message| {
+            message.id <= end && !message.author.bot && !message.content.is_empty()
+        }));
+
+        if after > end {
+            break;
+        }
+    }
+
+    Ok(messages
+        .into_iter()
+        .take(MAX_CHAT_MESSAGES)
+        .map(|message| format!("{}: {}", message.author.name, message.content))
+        .collect())
+}
+
+/// Writes a recap of everything logged between a session starting and now,
+/// using the LLM if one is configured and a plain statistical summary otherwise
+pub async fn generate_recap(
+    ctx: &poise::serenity_prelude::Context,
+    session: &Session,
+    campaign: &Campaign,
+) -> Result<String, Error> {
+    let now = Utc::now();
+
+    let events = db::sessions::get_events(campaign.id, session.scheduled_at, now)?;
+    let names = character_names(&events);
+    let stats = render_stats(&build_stats(&events, &names));
+
+    if !llm::is_configured(None) {
+        return Ok(stats);
+    }
+
+    let chat = match session.channel_id.parse::<u64>() {
+        Ok(channel_id) => {
+            fetch_chat(ctx, ChannelId::new(channel_id), session.scheduled_at, now)
+                .await
+                .unwrap_or_else(|e| {
+                    println!("Failed to read chat for session {}: {e}", session.id);
+                    vec![]
+                })
+        }
+        Err(_) => vec![],
+    };
+
+    let prompt = format!(
+        "Campaign: {}\n\nStatistics:\n{stats}\n\nEvent log:\n{}\n\nChat:\n{}",
+        campaign.name,
+        keep_last_chars(&render_event_log(&events, &names), MAX_PROMPT_LOG_LENGTH),
+        keep_last_chars(&chat.join("\n"), MAX_PROMPT_LOG_LENGTH)
+    );
+
+    let messages = vec![
+        Message {
+            role: Role::system,
+            content: RECAP_PROMPT.to_string(),
+            name: None,
+        },
+        Message {
+            role: Role::user,
+            content: prompt,
+            name: None,
+        },
+    ];
+
+    match llm::generate_to_string(None, messages).await {
+        Ok(recap) if !recap.trim().is_empty() => Ok(recap),
+        Ok(_) => Ok(stats),
+        Err(e) => {
+            println!("Failed to generate recap for session {}: {e}", session.id);
+            Ok(stats)
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+use poise::serenity_prelude::CreateEmbed;
+use poise::CreateReply;
+use serde_json::Value;
+
+use crate::common::Context;
+use crate::common::Error;
+use crate::db;
+use crate::db::models::Character;
+
+use super::super::get_user_character;
+use super::super::RpgError;
+use super::campaigns;
+use super::conditions;
+use super::engine;
+use super::engine::ResourceChange;
+use super::engine::RestKind;
+use super::revisions::RevisionSource;
+use super::stat_block::update_stat_block_values;
+use super::stat_block::StatBlock;
+use super::undo;
+use super::undo::Resource;
+
+/// Fraction of each pool a rest restores
+struct RestRules {
+    mana: f64,
+    hp: f64,
+    soul: f64,
+    ends_toggle_spells: bool,
+}
+
+/// A game system's short and long rests
+struct SystemRests {
+    short: RestRules,
+    long: RestRules,
+}
+
+const FULL_LONG_REST: RestRules = RestRules {
+    mana: 1.0,
+    hp: 1.0,
+    soul: 1.0,
+    ends_toggle_spells: true,
+};
+
+const MIR_RESTS: SystemRests = SystemRests {
+    short: RestRules {
+        mana: 0.5,
+        hp: 0.0,
+        soul: 0.0,
+        ends_toggle_spells: false,
+    },
+    long: FULL_LONG_REST,
+};
+
+lazy_static! {
+    /// Game systems that aren't listed rest the way the default system does
+    static ref REST_RULES: HashMap<&'static str, SystemRests> = {
+        let mut m = HashMap::new();
+        m.insert("mir", MIR_RESTS);
+        m.insert("deitus", MIR_RESTS);
+        // Short rests in 5e spend hit dice, which we don't track
+        m.insert(
+            "5e",
+            SystemRests {
+                short: RestRules {
+                    mana: 0.0,
+                    hp: 0.0,
+                    soul: 0.0,
+                    ends_toggle_spells: false,
+                },
+                long: FULL_LONG_REST,
+            },
+        );
+        m
+    };
+}
+
+fn rest_rules(game_system: &str, kind: RestKind) -> &'static RestRules {
+    let rests = REST_RULES
+        .get(game_system)
+        .unwrap_or(&REST_RULES[campaigns::DEFAULT_GAME_SYSTEM]);
+
+    match kind {
+        RestKind::Short => &rests.short,
+        RestKind::Long => &rests.long,
+    }
+}
+
+fn restore(current: i64, max: i64, fraction: f64) -> i64 {
+    let restored = current + (max as f64 * fraction).ceil() as i64;
+    restored.min(max).max(current)
+}
+
+pub struct RestSummary {
+    pub changes: Vec<ResourceChange>,
+    pub ended_spells: Vec<String>,
+    pub ended_conditions: Vec<String>,
+}
+
+impl RestSummary {
+    pub fn describe(&self) -> String {
+        let mut lines = vec![];
+
+        if !self.changes.is_empty() {
+            lines.push(engine::format_changes(&self.changes));
+        }
+
+        if !self.ended_spells.is_empty() {
+            lines.push(format!("Ended: {}", self.ended_spells.join(", ")));
+        }
+
+        if !self.ended_conditions.is_empty() {
+            lines.push(format!("No longer {}", self.ended_conditions.join(", ")));
+        }
+
+        if lines.is_empty() {
+            "Nothing to restore".to_string()
+        } else {
+            lines.join("\n")
+        }
+    }
+}
+
+/// Rests a single character, restoring mana, HP, soul and gauges according to their game system
+/// and ending conditions that count down in turns
+pub async fn rest_character(
+    ctx: &poise::serenity_prelude::Context,
+    character: &Character,
+    kind: RestKind,
+    author_id: Option<u64>,
+) -> Result<RestSummary, Error> {
+    let mut character = character.clone();
+
+    let stat_block: StatBlock = super::super::get_sheet(Some(ctx), &character).await?;
+
+    let game_system = campaigns::game_system_for(&character, Some(&stat_block));
+    let rules = rest_rules(&game_system, kind);
+
+    let mut changes = vec![];
+    let mut stat_block_values: Vec<(&str, Value)> = vec![];
+
+    if let Some(max_mana) = stat_block.energy_pool {
+        let old = character.mana.unwrap_or(max_mana as i32);
+        let new = restore(old as i64, max_mana, rules.mana) as i32;
+
+        if new != old {
+            character.mana = Some(new);
+            changes.push(ResourceChange {
+                character_id: character.id,
+                resource: Resource::Mana,
+                name: "Mana".to_string(),
+                old,
+                new,
+            });
+        }
+    }
+
+    let pools = [
+        ("HP", Resource::Hp, "current_hp", stat_block.hp, stat_block.max_hp, rules.hp),
+        ("Soul", Resource::Soul, "current_soul", stat_block.soul, stat_block.max_soul, rules.soul),
+    ];
+
+    for (name, resource, key, current, max, fraction) in pools {
+        if let (Some(current), Some(max)) = (current, max) {
+            let new = restore(current, max, fraction);
+
+            if new != current {
+                stat_block_values.push((key, Value::from(new)));
+                changes.push(ResourceChange {
+                    character_id: character.id,
+                    resource,
+                    name: name.to_string(),
+                    old: current as i32,
+                    new: new as i32,
+                });
+            }
+        }
+    }
+
+    if !stat_block_values.is_empty() {
+        update_stat_block_values(
+            &mut character,
+            &stat_block_values,
+            author_id,
+            RevisionSource::Rest,
+        )?;
+    }
+
+    db::characters::update(&character)?;
+
+    changes.extend(engine::apply_rest(character.id, kind)?);
+
+    let mut ended_spells = vec![];
+
+    if rules.ends_toggle_spells {
+        let mut active_spells_map = super::ACTIVE_SPELLS.lock().await;
+
+        if let Some(active_spells) = active_spells_map.remove(&character.id) {
+            ended_spells = active_spells
+                .into_iter()
+                .map(|spell| spell.name.unwrap_or("Unnamed spell".to_string()))
+                .collect();
+        }
+    }
+
+    let ended_conditions = conditions::end_timed(character.id)?;
+
+    Ok(RestSummary {
+        changes,
+        ended_spells,
+        ended_conditions,
+    })
+}
+
+/// Restore mana, HP, soul and gauges - spell slots are only tracked as gauges that reset on rest
+#[poise::command(slash_command, prefix_command)]
+pub async fn rest(
+    ctx: Context<'_>,
+    kind: RestKind,
+    #[description = "Rest every character in your campaign (DM only)"] party: Option<bool>,
+    #[description = "Only needed if you DM more than one campaign"] campaign_id: Option<i32>,
+) -> Result<(), Error> {
+    let placeholder = CreateReply::default().content("*Thinking, please wait...*");
+    let placeholder_message = ctx.send(placeholder).await?;
+
+    let characters = if party.unwrap_or(false) {
+        let campaign = campaigns::get_dm_campaign(&ctx, campaign_id).await?;
+        db::characters::get_by_campaign(campaign.id)?
+    } else {
+        vec![get_user_character(&ctx)
+            .await?
+            .ok_or(RpgError::NoCharacterSelected)?]
+    };
+
+    let title = match kind {
+        RestKind::Short => "🏕️ Short rest",
+        RestKind::Long => "🛏️ Long rest",
+    };
+
+    let mut embed = CreateEmbed::default().title(title);
+    let mut changes = vec![];
+
+    for character in &characters {
+        let name = character.name.clone().unwrap_or("No name?".to_string());
+
+        let description = match rest_character(ctx.serenity_context(), character, kind, Some(ctx.author().id.get())).await {
+            Ok(summary) => {
+                let description = summary.describe();
+                changes.extend(summary.changes.iter().map(undo::Change::from));
+                description
+            }
+            Err(e) => format!("Could not rest: {e}"),
+        };
+
+        embed = embed.field(name, description, false);
+    }
+
+    // The whole rest is undone together, even for a party
+    let batch = undo::record(ctx.author().id, &changes)?;
+
+    placeholder_message
+        .edit(
+            ctx,
+            CreateReply::default()
+                .content("")
+                .embed(embed)
+                .components(undo::undo_row(batch)?),
+        )
+        .await?;
+
+    Ok(())
+}
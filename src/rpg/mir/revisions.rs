@@ -0,0 +1,464 @@
+use std::collections::BTreeMap;
+
+use poise::serenity_prelude::Mentionable;
+use poise::serenity_prelude::UserId;
+use poise::CreateReply;
+use poise::Modal;
+use serde_json::Value;
+
+use crate::common::Context;
+use crate::common::Error;
+use crate::db;
+use crate::db::models::Character;
+use crate::db::models::StatBlockRevision;
+
+use super::super::get_user_character;
+use super::super::CharacterSheetable;
+use super::super::RpgError;
+use super::spell_sheet::SpellSheet;
+use super::stat_block::StatBlock;
+use super::ApplicationContext;
+
+#[derive(Debug, Clone, Copy, PartialEq, poise::ChoiceParameter)]
+pub enum BlockKind {
+    #[name = "stats"]
+    Stat,
+    #[name = "spells"]
+    Spell,
+}
+
+impl BlockKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlockKind::Stat => "stat",
+            BlockKind::Spell => "spell",
+        }
+    }
+
+    fn content_of(&self, character: &Character) -> Option<String> {
+        match self {
+            BlockKind::Stat => character.stat_block.clone(),
+            BlockKind::Spell => character.spell_block.clone(),
+        }
+    }
+
+    fn set_content(&self, character: &mut Character, content: String) {
+        match self {
+            BlockKind::Stat => character.stat_block = Some(content),
+            BlockKind::Spell => character.spell_block = Some(content),
+        }
+    }
+
+    /// Checks the JSON can actually be loaded as this kind of sheet
+    fn validate(&self, content: &str) -> Result<(), Error> {
+        match self {
+            BlockKind::Stat => StatBlock::from_json(None, content).map(|_| ()),
+            BlockKind::Spell => SpellSheet::from_json(None, content).map(|_| ()),
+        }
+    }
+}
+
+/// Where a revision came from, stored in stat_block_revisions.source
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RevisionSource {
+    Original,
+    Llm,
+    Manual,
+    LevelUp,
+    Rollback,
+    Import,
+    Rest,
+    Dashboard,
+    Undo,
+}
+
+impl RevisionSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RevisionSource::Original => "original",
+            RevisionSource::Llm => "llm",
+            RevisionSource::Manual => "manual",
+            RevisionSource::LevelUp => "level_up",
+            RevisionSource::Rollback => "rollback",
+            RevisionSource::Import => "import",
+            RevisionSource::Rest => "rest",
+            RevisionSource::Dashboard => "dashboard",
+            RevisionSource::Undo => "undo",
+        }
+    }
+}
+
+fn describe_source(source: &str) -> &str {
+    match source {
+        "original" => "Before history was recorded",
+        "llm" => "Re-imported from sheet message",
+        "manual" => "Edited by hand",
+        "level_up" => "Level up",
+        "rollback" => "Rollback",
+        "import" => "Imported",
+        "rest" => "Rest",
+        "dashboard" => "Changed from the party dashboard",
+        "undo" => "Undo",
+        other => other,
+    }
+}
+
+/// Stores a new revision of one of a character's blocks
+/// The first time a block changes, the content it replaced is saved too so it can be rolled back to
+pub fn record(
+    character_id: i32,
+    kind: BlockKind,
+    previous: Option<&str>,
+    content: &str,
+    author_id: Option<u64>,
+    source: RevisionSource,
+) -> Result<(), Error> {
+    if previous == Some(content) {
+        return Ok(());
+    }
+
+    if let Some(previous) = previous {
+        if !db::revisions::has_revisions(character_id, kind.as_str())? {
+            db::revisions::create(
+                character_id,
+                kind.as_str(),
+                previous,
+                RevisionSource::Original.as_str(),
+                None,
+            )?;
+        }
+    }
+
+    db::revisions::create(
+        character_id,
+        kind.as_str(),
+        content,
+        source.as_str(),
+        author_id.map(|id| id.to_string()),
+    )?;
+
+    Ok(())
+}
+
+/// Records a revision for each block that differs between two versions of a character
+pub fn record_changes(
+    before: &Character,
+    after: &Character,
+    author_id: Option<u64>,
+    source: RevisionSource,
+) -> Result<(), Error> {
+    for kind in [BlockKind::Stat, BlockKind::Spell] {
+        if let Some(content) = kind.content_of(after) {
+            record(
+                after.id,
+                kind,
+                kind.content_of(before).as_deref(),
+                &content,
+                author_id,
+                source,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Flattens JSON into dot separated paths (e.g. "stats.str") and their values
+fn flatten(value: &Value, prefix: &str, out: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten(child, &path, out);
+            }
+        }
+        Value::Array(items) => {
+            for (i, child) in items.iter().enumerate() {
+                flatten(child, &format!("{prefix}[{i}]"), out);
+            }
+        }
+        Value::String(s) => {
+            out.insert(prefix.to_string(), s.to_string());
+        }
+        other => {
+            out.insert(prefix.to_string(), other.to_string());
+        }
+    }
+}
+
+/// Lists the fields that were added, removed or changed between two JSON blocks
+pub fn diff_fields(old: Option<&str>, new: &str) -> Result<Vec<String>, Error> {
+    let mut old_fields = BTreeMap::new();
+    let mut new_fields = BTreeMap::new();
+
+    if let Some(old) = old {
+        flatten(&serde_json::from_str(old)?, "", &mut old_fields);
+    }
+    flatten(&serde_json::from_str(new)?, "", &mut new_fields);
+
+    let mut lines = vec![];
+
+    for (path, new_value) in &new_fields {
+        match old_fields.get(path) {
+            None => lines.push(format!("+ {path}: {new_value}")),
+            Some(old_value) if old_value != new_value => {
+                lines.push(format!("~ {path}: {old_value} → {new_value}"))
+            }
+            _ => {}
+        }
+    }
+
+    for (path, old_value) in &old_fields {
+        if !new_fields.contains_key(path) {
+            lines.push(format!("- {path}: {old_value}"));
+        }
+    }
+
+    Ok(lines)
+}
+
+fn describe_revision(revision: &StatBlockRevision) -> String {
+    let author = revision
+        .author_id
+        .as_ref()
+        .and_then(|id| id.parse::<u64>().ok())
+        .map(|id| format!(" by {}", UserId::new(id).mention()))
+        .unwrap_or_default();
+
+    format!(
+        "**#{}** {} block - {}{author} <t:{}:R>",
+        revision.revision,
+        revision.kind,
+        describe_source(&revision.source),
+        revision.created_at.timestamp()
+    )
+}
+
+/// Lines shown when inspecting a single revision, kept within discord's message length limit
+fn truncate_lines(lines: Vec<String>, max_length: usize) -> String {
+    let mut content = String::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        if content.len() + line.len() + 1 > max_length {
+            content += &format!("...and {} more", lines.len() - i);
+            break;
+        }
+        content += line;
+        content += "\n";
+    }
+
+    content
+}
+
+#[poise::command(slash_command, prefix_command)]
+pub async fn character_history(
+    ctx: Context<'_>,
+    #[description = "Show the changes made in this revision"] revision: Option<i32>,
+) -> Result<(), Error> {
+    let character = get_user_character(&ctx)
+        .await?
+        .ok_or(RpgError::NoCharacterSelected)?;
+
+    let content = if let Some(revision) = revision {
+        let revision = db::revisions::get(character.id, revision)
+            .map_err(|_| RpgError::RevisionNotFound)?;
+
+        let previous =
+            db::revisions::get_previous(character.id, &revision.kind, revision.revision)?;
+
+        let changes = diff_fields(
+            previous.as_ref().map(|p| p.content.as_str()),
+            &revision.content,
+        )?;
+
+        let diff = if changes.is_empty() {
+            "No changes".to_string()
+        } else {
+            truncate_lines(changes, 1700)
+        };
+
+        format!("{}\n```diff\n{diff}```", describe_revision(&revision))
+    } else {
+        let revisions = db::revisions::get_for_character(character.id)?;
+
+        // Only the most recent revisions, to stay within discord's message length limit
+        let shown = &revisions[revisions.len().saturating_sub(15)..];
+
+        if shown.is_empty() {
+            "No revisions recorded yet".to_string()
+        } else {
+            shown
+                .iter()
+                .rev()
+                .map(describe_revision)
+                .collect::<Vec<String>>()
+                .join("\n")
+                + "\n\nUse /character_history (revision) to see what changed"
+        }
+    };
+
+    ctx.send(CreateReply::default().content(content).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command)]
+pub async fn character_rollback(ctx: Context<'_>, revision: i32) -> Result<(), Error> {
+    let mut character = get_user_character(&ctx)
+        .await?
+        .ok_or(RpgError::NoCharacterSelected)?;
+
+    let revision =
+        db::revisions::get(character.id, revision).map_err(|_| RpgError::RevisionNotFound)?;
+
+    let kind = match revision.kind.as_str() {
+        "spell" => BlockKind::Spell,
+        _ => BlockKind::Stat,
+    };
+
+    let before = character.clone();
+
+    kind.set_content(&mut character, revision.content.clone());
+    db::characters::update(&character)?;
+
+    record_changes(
+        &before,
+        &character,
+        Some(ctx.author().id.get()),
+        RevisionSource::Rollback,
+    )?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "Rolled your {} block back to revision #{}",
+                revision.kind, revision.revision
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Modal)]
+#[name = "Edit Block"]
+struct EditBlockModal {
+    #[name = "JSON"]
+    #[paragraph]
+    #[max_length = 4000]
+    json: String,
+}
+
+/// Edit the stored JSON of your stat or spell block by hand
+#[poise::command(slash_command)]
+pub async fn edit_stat_block(
+    ctx: ApplicationContext<'_>,
+    #[description = "Defaults to your stat block"] block: Option<BlockKind>,
+) -> Result<(), Error> {
+    let kind = block.unwrap_or(BlockKind::Stat);
+
+    let user = db::users::get_or_create(ctx.author().id.get())?;
+
+    let mut character = db::characters::get(
+        user.selected_character
+            .ok_or(RpgError::NoCharacterSelected)?,
+    )?;
+
+    let current = kind
+        .content_of(&character)
+        .ok_or(RpgError::NoCharacterSheetData)?;
+
+    let current_value: Value = serde_json::from_str(&current)?;
+
+    // Modals can't hold more than 4000 characters, so fall back to compact JSON for big sheets
+    let pretty = serde_json::to_string_pretty(&current_value)?;
+    let json = if pretty.len() <= 4000 { pretty } else { current.clone() };
+
+    if json.len() > 4000 {
+        return Err(Box::new(RpgError::BlockTooLongToEdit));
+    }
+
+    let Some(data) = EditBlockModal::execute_with_defaults(ctx, EditBlockModal { json }).await? else {
+        return Ok(());
+    };
+
+    let edited: Value = serde_json::from_str(&data.json)?;
+    let edited = serde_json::to_string(&edited)?;
+
+    kind.validate(&edited)?;
+
+    let before = character.clone();
+
+    kind.set_content(&mut character, edited);
+    db::characters::update(&character)?;
+
+    record_changes(
+        &before,
+        &character,
+        Some(ctx.author().id.get()),
+        RevisionSource::Manual,
+    )?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!("Updated your {} block", kind.as_str()))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unchanged_blocks_have_no_differences() {
+        let block = r#"{"name": "Ordis", "stats": {"str": 12}}"#;
+
+        assert!(diff_fields(Some(block), block).unwrap().is_empty());
+    }
+
+    #[test]
+    fn added_changed_and_removed_fields() {
+        let old = r#"{"name": "Ordis", "level": 2, "speed": 30}"#;
+        let new = r#"{"name": "Ordis", "level": 3, "armour": 14}"#;
+
+        assert_eq!(
+            diff_fields(Some(old), new).unwrap(),
+            vec!["+ armour: 14", "~ level: 2 → 3", "- speed: 30"]
+        );
+    }
+
+    #[test]
+    fn nested_fields_use_their_full_path() {
+        let old = r#"{"stats": {"str": 12}, "spells": ["light"]}"#;
+        let new = r#"{"stats": {"str": 14, "dex": 10}, "spells": ["light", "bless"]}"#;
+
+        assert_eq!(
+            diff_fields(Some(old), new).unwrap(),
+            vec!["+ spells[1]: bless", "+ stats.dex: 10", "~ stats.str: 12 → 14"]
+        );
+    }
+
+    #[test]
+    fn first_revision_adds_everything() {
+        assert_eq!(
+            diff_fields(None, r#"{"name": "Ordis", "hp": null}"#).unwrap(),
+            vec!["+ hp: null", "+ name: Ordis"]
+        );
+    }
+
+    #[test]
+    fn invalid_json_is_an_error() {
+        assert!(diff_fields(Some("not json"), "{}").is_err());
+        assert!(diff_fields(None, "{").is_err());
+    }
+}
@@ -0,0 +1,210 @@
+use serde::Serialize;
+
+use crate::common::Error;
+use crate::dice;
+use crate::dice::RollResult;
+
+/// Most times a single check is rolled when advantage stacks
+static MAX_ROLLS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, poise::ChoiceParameter)]
+#[serde(rename_all = "lowercase")]
+pub enum RollMode {
+    #[default]
+    #[name = "normal"]
+    Normal,
+    #[name = "advantage"]
+    Advantage,
+    #[name = "disadvantage"]
+    Disadvantage,
+}
+
+impl RollMode {
+    pub fn from_str(mode: &str) -> Option<Self> {
+        match mode.trim().to_lowercase().as_str() {
+            "normal" => Some(RollMode::Normal),
+            "advantage" => Some(RollMode::Advantage),
+            "disadvantage" => Some(RollMode::Disadvantage),
+            _ => None,
+        }
+    }
+
+    pub fn is_normal(&self) -> bool {
+        *self == RollMode::Normal
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            RollMode::Normal => "normal",
+            RollMode::Advantage => "advantage",
+            RollMode::Disadvantage => "disadvantage",
+        }
+    }
+}
+
+/// How a campaign combines several sources of advantage and disadvantage on one roll
+#[derive(Debug, Clone, Copy, PartialEq, Default, poise::ChoiceParameter)]
+pub enum StackingRule {
+    /// Any advantage and any disadvantage cancel out, extra sources do nothing (5e)
+    #[default]
+    #[name = "cancel"]
+    Cancel,
+    /// Sources are counted against each other and whichever has more wins
+    #[name = "net"]
+    Net,
+    /// Like net, but every extra source rolls the check one more time
+    #[name = "stack"]
+    Stack,
+}
+
+impl StackingRule {
+    pub fn from_str(rule: &str) -> Option<Self> {
+        match rule.trim().to_lowercase().as_str() {
+            "cancel" => Some(StackingRule::Cancel),
+            "net" => Some(StackingRule::Net),
+            "stack" => Some(StackingRule::Stack),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StackingRule::Cancel => "cancel",
+            StackingRule::Net => "net",
+            StackingRule::Stack => "stack",
+        }
+    }
+
+    /// The mode a roll ends up with and how many times the check is rolled
+    pub fn resolve(&self, advantages: usize, disadvantages: usize) -> (RollMode, usize) {
+        let net = advantages as i64 - disadvantages as i64;
+
+        let mode = match self {
+            StackingRule::Cancel if advantages > 0 && disadvantages > 0 => RollMode::Normal,
+            _ if net > 0 => RollMode::Advantage,
+            _ if net < 0 => RollMode::Disadvantage,
+            _ => RollMode::Normal,
+        };
+
+        let rolls = match (mode, self) {
+            (RollMode::Normal, _) => 1,
+            (_, StackingRule::Stack) => (net.unsigned_abs() as usize + 1).min(MAX_ROLLS),
+            _ => 2,
+        };
+
+        (mode, rolls)
+    }
+}
+
+/// Rolls the whole expression once per count and keeps the highest (advantage) or lowest (disadvantage),
+/// showing every total with the one that counted highlighted
+pub fn roll(expression: &str, mode: RollMode, rolls: usize) -> Result<RollResult, Error> {
+    if mode == RollMode::Normal || rolls < 2 {
+        return dice::eval_roll(expression);
+    }
+
+    let mut results = vec![];
+
+    for _ in 0..rolls {
+        results.push(dice::eval_roll(expression)?);
+    }
+
+    let kept = results
+        .iter()
+        .enumerate()
+        .fold(0, |kept, (index, result)| {
+            let better = match mode {
+                RollMode::Advantage => result.result > results[kept].result,
+                _ => result.result < results[kept].result,
+            };
+
+            if better {
+                index
+            } else {
+                kept
+            }
+        });
+
+    let totals = results
+        .iter()
+        .enumerate()
+        .map(|(index, result)| {
+            let dice_rolled = dice::join_rolls_to_string(&result.rolls, ", ");
+
+            if index == kept {
+                format!("**__{}__** [{dice_rolled}]", result.result)
+            } else {
+                format!("~~{}~~ [{dice_rolled}]", result.result)
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" / ");
+
+    let mut result = results.swap_remove(kept);
+
+    result.message = format!("Rolled with {}: {totals}\n{}", mode.as_str(), result.message);
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_sources_roll_normally() {
+        for rule in [StackingRule::Cancel, StackingRule::Net, StackingRule::Stack] {
+            assert_eq!(rule.resolve(0, 0), (RollMode::Normal, 1));
+        }
+    }
+
+    #[test]
+    fn cancel_ignores_extra_sources() {
+        assert_eq!(StackingRule::Cancel.resolve(3, 0), (RollMode::Advantage, 2));
+        assert_eq!(StackingRule::Cancel.resolve(0, 2), (RollMode::Disadvantage, 2));
+        assert_eq!(StackingRule::Cancel.resolve(3, 1), (RollMode::Normal, 1));
+    }
+
+    #[test]
+    fn net_counts_sources_against_each_other() {
+        assert_eq!(StackingRule::Net.resolve(3, 1), (RollMode::Advantage, 2));
+        assert_eq!(StackingRule::Net.resolve(1, 2), (RollMode::Disadvantage, 2));
+        assert_eq!(StackingRule::Net.resolve(2, 2), (RollMode::Normal, 1));
+    }
+
+    #[test]
+    fn stack_rolls_once_more_per_extra_source() {
+        assert_eq!(StackingRule::Stack.resolve(1, 0), (RollMode::Advantage, 2));
+        assert_eq!(StackingRule::Stack.resolve(3, 1), (RollMode::Advantage, 3));
+        assert_eq!(StackingRule::Stack.resolve(0, 3), (RollMode::Disadvantage, 4));
+        assert_eq!(StackingRule::Stack.resolve(2, 2), (RollMode::Normal, 1));
+    }
+
+    #[test]
+    fn stack_is_capped() {
+        assert_eq!(StackingRule::Stack.resolve(20, 0), (RollMode::Advantage, MAX_ROLLS));
+        assert_eq!(StackingRule::Stack.resolve(0, 20), (RollMode::Disadvantage, MAX_ROLLS));
+    }
+
+    #[test]
+    fn parse_names() {
+        assert_eq!(RollMode::from_str(" Advantage "), Some(RollMode::Advantage));
+        assert_eq!(RollMode::from_str("adv"), None);
+        assert_eq!(StackingRule::from_str("STACK"), Some(StackingRule::Stack));
+        assert_eq!(StackingRule::from_str("sum"), None);
+    }
+
+    #[test]
+    fn roll_keeps_one_result_per_mode() {
+        let result = roll("5", RollMode::Advantage, 3).unwrap();
+
+        assert_eq!(result.result, 5.0);
+        assert!(result.message.starts_with("Rolled with advantage: "));
+        assert_eq!(result.message.matches("~~5~~").count(), 2);
+        assert_eq!(result.message.matches("**__5__**").count(), 1);
+
+        let result = roll("5", RollMode::Normal, 3).unwrap();
+
+        assert!(!result.message.starts_with("Rolled with"));
+    }
+}
@@ -0,0 +1,578 @@
+use std::time::Duration;
+
+use chrono::DateTime;
+use chrono::FixedOffset;
+use chrono::NaiveDateTime;
+use chrono::TimeZone;
+use chrono::Utc;
+use poise::serenity_prelude::ButtonStyle;
+use poise::serenity_prelude::ChannelId;
+use poise::serenity_prelude::CreateActionRow;
+use poise::serenity_prelude::CreateEmbed;
+use poise::serenity_prelude::CreateEmbedFooter;
+use poise::serenity_prelude::CreateMessage;
+use poise::serenity_prelude::EditMessage;
+use poise::serenity_prelude::MessageId;
+use poise::CreateReply;
+use regex::Regex;
+
+use crate::common::Context;
+use crate::common::Error;
+use crate::db;
+use crate::db::models::Campaign;
+use crate::db::models::Character;
+use crate::db::models::NewSession;
+use crate::db::models::NewSessionEvent;
+use crate::db::models::Session;
+
+use super::super::get_user_character;
+use super::super::RpgError;
+use super::campaigns;
+use super::recaps;
+use super::event_handlers::SessionRsvpEvent;
+use super::event_handlers::SessionRsvpEventParams;
+
+/// How often the database is checked for reminders that are due
+static REMINDER_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+static DEFAULT_REMINDERS: &str = "24h, 1h";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RsvpStatus {
+    Attending,
+    Maybe,
+    Declined,
+}
+
+impl RsvpStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RsvpStatus::Attending => "attending",
+            RsvpStatus::Maybe => "maybe",
+            RsvpStatus::Declined => "declined",
+        }
+    }
+
+    pub fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "attending" => Some(RsvpStatus::Attending),
+            "maybe" => Some(RsvpStatus::Maybe),
+            "declined" => Some(RsvpStatus::Declined),
+            _ => None,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            RsvpStatus::Attending => "✅ Attending",
+            RsvpStatus::Maybe => "❔ Maybe",
+            RsvpStatus::Declined => "❌ Can't make it",
+        }
+    }
+
+    fn style(&self) -> ButtonStyle {
+        match self {
+            RsvpStatus::Attending => ButtonStyle::Success,
+            RsvpStatus::Maybe => ButtonStyle::Secondary,
+            RsvpStatus::Declined => ButtonStyle::Danger,
+        }
+    }
+}
+
+static RSVP_STATUSES: [RsvpStatus; 3] = [
+    RsvpStatus::Attending,
+    RsvpStatus::Maybe,
+    RsvpStatus::Declined,
+];
+
+/// Things that happen to characters during a session, logged for recaps
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SessionEventKind {
+    Roll,
+    Spell,
+    Damage,
+    Healing,
+    LevelUp,
+}
+
+impl SessionEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionEventKind::Roll => "roll",
+            SessionEventKind::Spell => "spell",
+            SessionEventKind::Damage => "damage",
+            SessionEventKind::Healing => "healing",
+            SessionEventKind::LevelUp => "level_up",
+        }
+    }
+
+    pub fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "roll" => Some(SessionEventKind::Roll),
+            "spell" => Some(SessionEventKind::Spell),
+            "damage" => Some(SessionEventKind::Damage),
+            "healing" => Some(SessionEventKind::Healing),
+            "level_up" => Some(SessionEventKind::LevelUp),
+            _ => None,
+        }
+    }
+}
+
+/// Logs something a character did for session recaps - characters without a campaign aren't logged
+/// Failures are only printed, so logging never gets in the way of the action itself
+pub fn log_event(
+    character: &Character,
+    kind: SessionEventKind,
+    description: &str,
+    value: Option<f64>,
+) {
+    let Some(campaign_id) = character.campaign_id else {
+        return;
+    };
+
+    let result = db::sessions::log_event(&NewSessionEvent {
+        campaign_id,
+        character_id: character.id,
+        kind: kind.as_str().to_string(),
+        description: description.to_string(),
+        value,
+    });
+
+    if let Err(e) = result {
+        println!("Failed to log {} for character {}: {e}", kind.as_str(), character.id);
+    }
+}
+
+/// Parses a session's start time - a discord timestamp (<t:1700000000:F>), a unix timestamp,
+/// an RFC 3339 date or "2026-10-25 19:00" in the given UTC offset
+pub fn parse_datetime(input: &str, utc_offset_hours: Option<i32>) -> Result<DateTime<Utc>, Error> {
+    let input = input.trim();
+
+    let timestamp_pattern = Regex::new(r"^(?:<t:)?(\d{9,})(?::\w)?>?$").unwrap();
+
+    if let Some(captures) = timestamp_pattern.captures(input) {
+        let seconds: i64 = captures[1].parse()?;
+        return Utc
+            .timestamp_opt(seconds, 0)
+            .single()
+            .ok_or(Box::new(RpgError::InvalidSessionTime));
+    }
+
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(input) {
+        return Ok(datetime.with_timezone(&Utc));
+    }
+
+    let offset = FixedOffset::east_opt(utc_offset_hours.unwrap_or(0) * 3600)
+        .ok_or(RpgError::InvalidSessionTime)?;
+
+    for format in ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M", "%d/%m/%Y %H:%M"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(input, format) {
+            return offset
+                .from_local_datetime(&naive)
+                .single()
+                .map(|datetime| datetime.with_timezone(&Utc))
+                .ok_or(Box::new(RpgError::InvalidSessionTime));
+        }
+    }
+
+    Err(Box::new(RpgError::InvalidSessionTime))
+}
+
+/// Parses reminder offsets like "24h, 1h, 30m, 2d"
+pub fn parse_offsets(offsets: &str) -> Result<Vec<chrono::Duration>, Error> {
+    let offset_pattern = Regex::new(r"^(\d+)\s*([mhd])$").unwrap();
+
+    offsets
+        .split(',')
+        .map(|offset| offset.trim().to_lowercase())
+        .filter(|offset| !offset.is_empty())
+        .map(|offset| {
+            let captures = offset_pattern
+                .captures(&offset)
+                .ok_or(RpgError::InvalidReminderOffsets)?;
+
+            let amount: i64 = captures[1].parse()?;
+
+            Ok(match &captures[2] {
+                "m" => chrono::Duration::minutes(amount),
+                "h" => chrono::Duration::hours(amount),
+                _ => chrono::Duration::days(amount),
+            })
+        })
+        .collect()
+}
+
+fn session_title(session: &Session, campaign: &Campaign) -> String {
+    session.title.clone().unwrap_or(campaign.name.clone())
+}
+
+/// Users who said they're coming to a session
+pub fn attendees(session_id: i32) -> Result<Vec<u64>, Error> {
+    Ok(db::sessions::get_rsvps(session_id)?
+        .into_iter()
+        .filter(|rsvp| rsvp.status == RsvpStatus::Attending.as_str())
+        .filter_map(|rsvp| rsvp.user_id.parse::<u64>().ok())
+        .collect())
+}
+
+fn session_embed(session: &Session, campaign: &Campaign) -> Result<CreateEmbed, Error> {
+    let rsvps = db::sessions::get_rsvps(session.id)?;
+    let timestamp = session.scheduled_at.timestamp();
+
+    let mut embed = CreateEmbed::default()
+        .title(format!("📅 {}", session_title(session, campaign)))
+        .description(format!("<t:{timestamp}:F> (<t:{timestamp}:R>)"))
+        .footer(CreateEmbedFooter::new(format!(
+            "Session #{} - {}",
+            session.id, campaign.name
+        )));
+
+    if session.cancelled {
+        return Ok(embed.description("~~Cancelled~~"));
+    }
+
+    for status in RSVP_STATUSES {
+        let users: Vec<String> = rsvps
+            .iter()
+            .filter(|rsvp| rsvp.status == status.as_str())
+            .map(|rsvp| format!("<@{}>", rsvp.user_id))
+            .collect();
+
+        embed = embed.field(
+            format!("{} ({})", status.label(), users.len()),
+            if users.is_empty() {
+                "-".to_string()
+            } else {
+                users.join("\n")
+            },
+            true,
+        );
+    }
+
+    Ok(embed)
+}
+
+fn rsvp_buttons(session_id: i32) -> Result<CreateActionRow, Error> {
+    let buttons = RSVP_STATUSES
+        .iter()
+        .map(|status| {
+            SessionRsvpEvent::create_button(
+                status.label(),
+                &SessionRsvpEventParams {
+                    session_id,
+                    status: status.as_str().to_string(),
+                },
+                status.style(),
+            )
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    Ok(CreateActionRow::Buttons(buttons))
+}
+
+/// Re-renders a session's announcement, e.g. after someone RSVPs
+async fn refresh_announcement(
+    ctx: &poise::serenity_prelude::Context,
+    session: &Session,
+) -> Result<(), Error> {
+    let Some(message_id) = &session.message_id else {
+        return Ok(());
+    };
+
+    let campaign = db::campaigns::get(session.campaign_id)?;
+
+    let components = if session.cancelled {
+        vec![]
+    } else {
+        vec![rsvp_buttons(session.id)?]
+    };
+
+    ChannelId::new(session.channel_id.parse()?)
+        .edit_message(
+            ctx,
+            MessageId::new(message_id.parse()?),
+            EditMessage::default()
+                .embed(session_embed(session, &campaign)?)
+                .components(components),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Records an RSVP from one of a session's buttons
+pub async fn rsvp(
+    ctx: &poise::serenity_prelude::Context,
+    session_id: i32,
+    user_id: u64,
+    status: RsvpStatus,
+) -> Result<(), Error> {
+    let session = db::sessions::get(session_id)?;
+
+    if session.cancelled {
+        return Err(Box::new(RpgError::SessionCancelled));
+    }
+
+    db::sessions::set_rsvp(session.id, user_id, status.as_str())?;
+
+    refresh_announcement(ctx, &session).await
+}
+
+async fn send_due_reminders(ctx: &poise::serenity_prelude::Context) -> Result<(), Error> {
+    let now = Utc::now();
+
+    for reminder in db::sessions::get_due_reminders(now)? {
+        // Marked first so a reminder that can't be posted isn't retried forever
+        db::sessions::mark_reminder_sent(reminder.id)?;
+
+        let session = db::sessions::get(reminder.session_id)?;
+
+        if session.cancelled || session.scheduled_at <= now {
+            continue;
+        }
+
+        let campaign = db::campaigns::get(session.campaign_id)?;
+
+        let mentions = db::sessions::get_rsvps(session.id)?
+            .into_iter()
+            .filter(|rsvp| rsvp.status != RsvpStatus::Declined.as_str())
+            .map(|rsvp| format!("<@{}>", rsvp.user_id))
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        let content = format!(
+            "⏰ **{}** starts <t:{}:R>! {mentions}",
+            session_title(&session, &campaign),
+            session.scheduled_at.timestamp()
+        );
+
+        let channel_id = ChannelId::new(session.channel_id.parse()?);
+
+        if let Err(e) = channel_id
+            .send_message(ctx, CreateMessage::default().content(content))
+            .await
+        {
+            println!("Failed to send reminder for session {}: {e}", session.id);
+        }
+    }
+
+    Ok(())
+}
+
+/// Posts session reminders as they become due - started once when the bot starts
+/// Reminders are read from the database, so none are lost when the bot restarts
+pub async fn run_reminders(ctx: poise::serenity_prelude::Context) {
+    loop {
+        if let Err(e) = send_due_reminders(&ctx).await {
+            println!("Failed to send session reminders: {e}");
+        }
+
+        tokio::time::sleep(REMINDER_POLL_INTERVAL).await;
+    }
+}
+
+/// The given campaign, your selected character's campaign, or the campaign you DM
+async fn get_campaign(ctx: &Context<'_>, campaign_id: Option<i32>) -> Result<Campaign, Error> {
+    if let Some(campaign_id) = campaign_id {
+        return db::campaigns::get(campaign_id).map_err(|_| RpgError::CampaignNotFound.into());
+    }
+
+    let character_campaign = get_user_character(ctx)
+        .await?
+        .and_then(|character| character.campaign_id);
+
+    match character_campaign {
+        Some(campaign_id) => db::campaigns::get(campaign_id),
+        None => campaigns::get_dm_campaign(ctx, None).await,
+    }
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands("schedule", "list", "cancel", "end", "recap"),
+    subcommand_required
+)]
+pub async fn session(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Schedule a session, posting an announcement players can RSVP to (DM only)
+#[poise::command(slash_command, prefix_command)]
+pub async fn schedule(
+    ctx: Context<'_>,
+    #[description = "e.g. 2026-10-25 19:00, or a discord timestamp"] datetime: String,
+    title: Option<String>,
+    #[description = "Your UTC offset in hours, e.g. -5 - defaults to UTC"] utc_offset: Option<i32>,
+    #[description = "How long before the session to remind everyone, e.g. 24h, 1h"]
+    reminders: Option<String>,
+    #[description = "Only needed if you DM more than one campaign"] campaign_id: Option<i32>,
+) -> Result<(), Error> {
+    let campaign = campaigns::get_dm_campaign(&ctx, campaign_id).await?;
+
+    if campaign.archived {
+        return Err(Box::new(RpgError::CampaignArchived));
+    }
+
+    let scheduled_at = parse_datetime(&datetime, utc_offset)?;
+    let now = Utc::now();
+
+    if scheduled_at <= now {
+        return Err(Box::new(RpgError::InvalidSessionTime));
+    }
+
+    let reminder_times: Vec<DateTime<Utc>> =
+        parse_offsets(reminders.as_deref().unwrap_or(DEFAULT_REMINDERS))?
+            .into_iter()
+            .map(|offset| scheduled_at - offset)
+            .filter(|remind_at| *remind_at > now)
+            .collect();
+
+    let mut session = db::sessions::create(
+        &NewSession {
+            campaign_id: campaign.id,
+            title,
+            scheduled_at,
+            channel_id: ctx.channel_id().to_string(),
+            created_by: ctx.author().id.to_string(),
+        },
+        &reminder_times,
+    )?;
+
+    let reply = ctx
+        .send(
+            CreateReply::default()
+                .embed(session_embed(&session, &campaign)?)
+                .components(vec![rsvp_buttons(session.id)?]),
+        )
+        .await?;
+
+    session.message_id = Some(reply.message().await?.id.to_string());
+    db::sessions::update(&session)?;
+
+    Ok(())
+}
+
+/// Upcoming sessions for your campaign
+#[poise::command(slash_command, prefix_command)]
+pub async fn list(ctx: Context<'_>, campaign_id: Option<i32>) -> Result<(), Error> {
+    let campaign = get_campaign(&ctx, campaign_id).await?;
+
+    let sessions = db::sessions::get_upcoming(campaign.id, Utc::now())?;
+
+    let content = if sessions.is_empty() {
+        format!("No sessions scheduled for **{}**", campaign.name)
+    } else {
+        sessions
+            .iter()
+            .map(|session| {
+                format!(
+                    "**#{}** {} - <t:{}:F> ({} attending)",
+                    session.id,
+                    session_title(session, &campaign),
+                    session.scheduled_at.timestamp(),
+                    attendees(session.id).map(|users| users.len()).unwrap_or(0)
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+
+    ctx.send(CreateReply::default().content(content).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Cancel a session - its reminders won't be sent (DM only)
+#[poise::command(slash_command, prefix_command)]
+pub async fn cancel(ctx: Context<'_>, session_id: i32) -> Result<(), Error> {
+    let mut session = db::sessions::get(session_id).map_err(|_| RpgError::SessionNotFound)?;
+
+    campaigns::get_dm_campaign(&ctx, Some(session.campaign_id)).await?;
+
+    session.cancelled = true;
+    db::sessions::update(&session)?;
+
+    refresh_announcement(ctx.serenity_context(), &session).await?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!("Cancelled session #{}", session.id))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+fn recap_embed(session: &Session, campaign: &Campaign, recap: &str) -> CreateEmbed {
+    CreateEmbed::default()
+        .title(format!("📜 {} - recap", session_title(session, campaign)))
+        .description(recaps::truncate_recap(recap))
+        .footer(CreateEmbedFooter::new(format!(
+            "Session #{} - {}",
+            session.id, campaign.name
+        )))
+}
+
+/// End a session and post a recap of what happened (DM only)
+#[poise::command(slash_command, prefix_command)]
+pub async fn end(
+    ctx: Context<'_>,
+    #[description = "Defaults to the session that started most recently"] session_id: Option<i32>,
+    #[description = "Only needed if you DM more than one campaign"] campaign_id: Option<i32>,
+) -> Result<(), Error> {
+    let mut session = match session_id {
+        Some(session_id) => db::sessions::get(session_id),
+        None => {
+            let campaign = campaigns::get_dm_campaign(&ctx, campaign_id).await?;
+            db::sessions::get_latest_started(campaign.id, Utc::now())
+        }
+    }
+    .map_err(|_| RpgError::SessionNotFound)?;
+
+    let campaign = campaigns::get_dm_campaign(&ctx, Some(session.campaign_id)).await?;
+
+    if session.ended_at.is_some() {
+        return Err(Box::new(RpgError::SessionAlreadyEnded));
+    }
+
+    let placeholder = CreateReply::default().content("*Thinking, please wait...*");
+    let placeholder_message = ctx.send(placeholder).await?;
+
+    let recap = recaps::generate_recap(ctx.serenity_context(), &session, &campaign).await?;
+
+    session.ended_at = Some(Utc::now());
+    session.recap = Some(recap.clone());
+    db::sessions::update(&session)?;
+
+    placeholder_message
+        .edit(
+            ctx,
+            CreateReply::default()
+                .content("")
+                .embed(recap_embed(&session, &campaign, &recap)),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Show the recap of a session that has ended
+#[poise::command(slash_command, prefix_command)]
+pub async fn recap(ctx: Context<'_>, session_id: i32) -> Result<(), Error> {
+    let session = db::sessions::get(session_id).map_err(|_| RpgError::SessionNotFound)?;
+    let campaign = db::campaigns::get(session.campaign_id)?;
+
+    let recap = session.recap.as_deref().ok_or(RpgError::SessionNotEnded)?;
+
+    ctx.send(
+        CreateReply::default()
+            .embed(recap_embed(&session, &campaign, recap))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
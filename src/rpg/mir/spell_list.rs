@@ -0,0 +1,247 @@
+use poise::serenity_prelude::ButtonStyle;
+use poise::serenity_prelude::Colour;
+use poise::serenity_prelude::CreateActionRow;
+use poise::serenity_prelude::CreateButton;
+use poise::serenity_prelude::CreateEmbed;
+use poise::serenity_prelude::CreateEmbedFooter;
+
+use crate::common;
+use crate::common::Error;
+use crate::db::models::Character;
+
+use super::super::RpgError;
+use super::event_handlers::CastSpellEvent;
+use super::event_handlers::CastSpellEventParams;
+use super::event_handlers::SpellListPageEvent;
+use super::event_handlers::SpellListPageEventParams;
+use super::spell_sheet::SpellSheet;
+use super::spells::ManaSpellResource;
+use super::spells::Spell;
+use super::spells::SpellType;
+use super::stat_block::StatBlock;
+
+/// One row of cast buttons per page
+static SPELLS_PER_PAGE: usize = 5;
+
+/// Discord won't accept longer button labels
+static MAX_LABEL_LENGTH: usize = 80;
+
+fn sorted_spells(spell_sheet: &SpellSheet) -> Result<Vec<(&String, &Spell<ManaSpellResource>)>, Error> {
+    let mut spells: Vec<_> = spell_sheet
+        .spells
+        .as_ref()
+        .ok_or(RpgError::NoSpellSheet)?
+        .iter()
+        .collect();
+
+    spells.sort_by_key(|(key, _)| *key);
+
+    Ok(spells)
+}
+
+/// A short stand-in for a spell key - a long key would push a button over discord's 100 character id limit
+pub fn spell_id(spell_key: &str) -> String {
+    common::hash(spell_key).chars().take(8).collect()
+}
+
+/// The spell at a position in the list - buttons refer to spells by position and id,
+/// so a button from before the sheet was edited can't cast whatever has moved into its place
+pub fn spell_at<'a>(
+    spell_sheet: &'a SpellSheet,
+    index: usize,
+    id: &str,
+) -> Result<(&'a String, &'a Spell<ManaSpellResource>), Error> {
+    let (spell_key, spell) = sorted_spells(spell_sheet)?
+        .get(index)
+        .copied()
+        .ok_or(RpgError::SpellListChanged)?;
+
+    if spell_id(spell_key) != id {
+        return Err(Box::new(RpgError::SpellListChanged));
+    }
+
+    Ok((spell_key, spell))
+}
+
+/// The page a spell is listed on, so the list can stay where it was after casting
+pub fn page_of(spell_sheet: &SpellSheet, spell_key: &str) -> usize {
+    sorted_spells(spell_sheet)
+        .ok()
+        .and_then(|spells| spells.iter().position(|(key, _)| key.as_str() == spell_key))
+        .map(|index| index / SPELLS_PER_PAGE)
+        .unwrap_or(0)
+}
+
+fn display_name(spell_key: &str) -> String {
+    common::capitalize_first_letter(&spell_key.replace('_', " "))
+}
+
+fn type_name(spell: &Spell<ManaSpellResource>) -> &'static str {
+    match spell.spell_type {
+        Some(SpellType::Single) => "single",
+        Some(SpellType::Toggle) => "toggle",
+        Some(SpellType::Summon) => "summon",
+        _ => "unknown",
+    }
+}
+
+/// The cost, with what the character would have left after paying it
+fn describe_cost(spell: &Spell<ManaSpellResource>, mana: i32) -> String {
+    let Some(cost) = &spell.cost else {
+        return "No cost".to_string();
+    };
+
+    let per_turn = if matches!(spell.spell_type, Some(SpellType::Toggle)) {
+        " per turn"
+    } else {
+        ""
+    };
+
+    if cost.mana >= 0 {
+        format!("Gain {} mana{per_turn}", cost.mana)
+    } else if super::can_afford(spell, mana) {
+        format!(
+            "{} mana{per_turn} ({mana} → {})",
+            -cost.mana,
+            mana + cost.mana
+        )
+    } else {
+        format!("{} mana{per_turn} (❌ only {mana} left)", -cost.mana)
+    }
+}
+
+fn cast_button(
+    character_id: i32,
+    index: usize,
+    spell_key: &str,
+    spell: &Spell<ManaSpellResource>,
+    active: bool,
+    affordable: bool,
+) -> Result<CreateButton, Error> {
+    let verb = match (&spell.spell_type, active) {
+        (Some(SpellType::Toggle), true) => "Disable",
+        (Some(SpellType::Toggle), false) => "Enable",
+        _ => "Cast",
+    };
+
+    let label: String = format!("{verb} {}", display_name(spell_key))
+        .chars()
+        .take(MAX_LABEL_LENGTH)
+        .collect();
+
+    let castable = matches!(
+        spell.spell_type,
+        Some(SpellType::Single) | Some(SpellType::Toggle)
+    );
+
+    let style = if active {
+        ButtonStyle::Success
+    } else if affordable && castable {
+        ButtonStyle::Primary
+    } else {
+        ButtonStyle::Secondary
+    };
+
+    Ok(CastSpellEvent::create_button(
+        &label,
+        &CastSpellEventParams {
+            character_id,
+            spell: index,
+            id: spell_id(spell_key),
+        },
+        style,
+    )?
+    .disabled(!castable || !(affordable || active)))
+}
+
+fn page_button(character_id: i32, page: usize, text: &str, disabled: bool) -> Result<CreateButton, Error> {
+    Ok(SpellListPageEvent::create_button(
+        text,
+        &SpellListPageEventParams { character_id, page },
+        ButtonStyle::Secondary,
+    )?
+    .disabled(disabled))
+}
+
+/// One page of a character's spells, each with a button to cast or toggle it
+pub async fn render_page(
+    character: &Character,
+    stat_block: &StatBlock,
+    spell_sheet: &SpellSheet,
+    page: usize,
+    colour: Colour,
+) -> Result<(CreateEmbed, Vec<CreateActionRow>), Error> {
+    let spells = sorted_spells(spell_sheet)?;
+
+    let pages = spells.len().div_ceil(SPELLS_PER_PAGE).max(1);
+    let page = page.min(pages - 1);
+
+    let mana = super::current_mana(character, stat_block)?;
+
+    let mut embed = CreateEmbed::default()
+        .title(format!(
+            "{}'s spells",
+            character.name.clone().unwrap_or("No name?".to_string())
+        ))
+        .colour(colour)
+        .description(format!(
+            "🪄 Mana: {mana} / {}",
+            stat_block.energy_pool.unwrap_or(0)
+        ))
+        .footer(CreateEmbedFooter::new(format!("Page {} of {pages}", page + 1)));
+
+    let mut buttons = vec![];
+
+    for (index, (spell_key, spell)) in spells
+        .into_iter()
+        .enumerate()
+        .skip(page * SPELLS_PER_PAGE)
+        .take(SPELLS_PER_PAGE)
+    {
+        let active = super::is_spell_active(character.id, spell).await;
+        let affordable = super::can_afford(spell, mana);
+
+        let cast_time = spell
+            .cast_time
+            .clone()
+            .unwrap_or("No cast time found".to_string());
+
+        embed = embed.field(
+            format!(
+                "{}{}",
+                display_name(spell_key),
+                if active { " (active)" } else { "" }
+            ),
+            format!(
+                "Type: {}\nCost: {}\nCast time: {cast_time}",
+                type_name(spell),
+                describe_cost(spell, mana)
+            ),
+            false,
+        );
+
+        buttons.push(cast_button(
+            character.id,
+            index,
+            spell_key,
+            spell,
+            active,
+            affordable,
+        )?);
+    }
+
+    let mut rows = vec![];
+
+    if !buttons.is_empty() {
+        rows.push(CreateActionRow::Buttons(buttons));
+    }
+
+    if pages > 1 {
+        rows.push(CreateActionRow::Buttons(vec![
+            page_button(character.id, page.saturating_sub(1), "◀", page == 0)?,
+            page_button(character.id, page + 1, "▶", page + 1 >= pages)?,
+        ]));
+    }
+
+    Ok((embed, rows))
+}
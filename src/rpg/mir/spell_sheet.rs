@@ -86,6 +86,14 @@ impl CharacterSheetable for SpellSheet {
                     spell_cost = None;
                 }
 
+                let text = |key: &str| {
+                    spell_data
+                        .get(key)
+                        .and_then(|value| value.as_str())
+                        .map(|value| value.trim().to_string())
+                        .filter(|value| !value.is_empty())
+                };
+
                 let spell = Spell {
                     name: Some(spell_name.to_string()),
                     cast_time: spell_data
@@ -93,6 +101,14 @@ impl CharacterSheetable for SpellSheet {
                         .and_then(|c| Some(c.to_string())),
                     spell_type: spell_type_enum,
                     cost: spell_cost,
+                    damage: text("damage"),
+                    healing: text("healing"),
+                    save_stat: text("save"),
+                    targets: spell_data
+                        .get("targets")
+                        .and_then(|targets| targets.as_i64())
+                        .map(|targets| targets as i32),
+                    description: text("description"),
                 };
 
                 spells.insert(spell_name.to_string(), spell);
@@ -162,25 +178,52 @@ impl CharacterSheetable for SpellSheet {
     {
 
         "spells": {
-            fireball": {
+            "fireball": {
                 "type": "single",
                 "cost": -150,
-                "cast_time": "1 turn"
+                "cast_time": "1 turn",
+                "damage": "8d6+int",
+                "healing": null,
+                "save": "agl",
+                "targets": 3,
+                "description": "A ball of fire that explodes on impact"
             },
             "invisibility": {
-                "type": "toggle"
+                "type": "toggle",
                 "cost": -50,
-                "cast_time": "instant"
+                "cast_time": "instant",
+                "damage": null,
+                "healing": null,
+                "save": null,
+                "targets": 1,
+                "description": "The caster can't be seen until they attack"
             },
             "regen": {
                 "type": "toggle",
                 "cost": 50,
-                "cast_time": "1 turn"
+                "cast_time": "1 turn",
+                "damage": null,
+                "healing": null,
+                "save": null,
+                "targets": null,
+                "description": null
+            },
+            "mend": {
+                "type": "single",
+                "cost": -40,
+                "cast_time": "1 action",
+                "damage": null,
+                "healing": "2d8+wis",
+                "save": null,
+                "targets": 1,
+                "description": "Closes the wounds of a touched ally"
             }
         }
     }    
 
     If there are missing values, interpret them as null
+    Damage and healing are dice expressions like '8d6' or '2d4+int' using the stat names from the sheet, and save is the stat a target rolls to resist the spell
+    Targets is how many creatures the spell can affect, and description is a short summary of what the spell does
     For cast time, use the middle value that should look like '2 actions', 'entire turn', '3 turns', '1 action', '1 turn' etc
     If there are spaces in spell names, replace them with underscores
     If you are expecting a value in a specific format but it is incorrect, instead set the value as 'ERROR - (explanation)'
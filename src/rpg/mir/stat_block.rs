@@ -7,8 +7,12 @@ use crate::db::models::Character;
 use super::super::CharacterSheetable;
 use super::super::RpgError;
 use super::super::SheetInfo;
+use super::revisions;
+use super::revisions::BlockKind;
+use super::revisions::RevisionSource;
 
 use poise::serenity_prelude::Message;
+use regex::Regex;
 
 #[derive(Clone)]
 pub struct StatBlock {
@@ -27,10 +31,24 @@ pub struct StatBlock {
 
     pub hunger: Option<i64>,
 
+    pub actions: Option<i64>,
+    pub reactions: Option<i64>,
+
     pub default_roll: Option<String>,
     pub modifier_formula: Option<String>,
 }
 
+impl StatBlock {
+    /// Names of the stats and special stats, as written on the sheet
+    pub fn stat_keys(&self) -> Vec<String> {
+        [&self.stats, &self.special_stats]
+            .into_iter()
+            .filter_map(|stats| stats.as_ref().and_then(|stats| stats.as_object()))
+            .flat_map(|stats| stats.keys().cloned())
+            .collect()
+    }
+}
+
 impl fmt::Display for StatBlock {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if let Some(json) = self.sheet_info.jsonified_message.as_ref() {
@@ -44,6 +62,71 @@ impl fmt::Display for StatBlock {
     }
 }
 
+/// Writes values back into a character's stored stat block JSON (e.g. current_hp after resting)
+/// and records the new stat block in the revision history
+/// The character still needs saving with db::characters::update afterwards
+pub fn update_stat_block_values(
+    character: &mut Character,
+    values: &[(&str, serde_json::Value)],
+    author_id: Option<u64>,
+    source: RevisionSource,
+) -> Result<(), Error> {
+    let previous = set_stat_block_values(character, values)?;
+
+    revisions::record(
+        character.id,
+        BlockKind::Stat,
+        previous.as_deref(),
+        character.stat_block.as_deref().unwrap_or_default(),
+        author_id,
+        source,
+    )?;
+
+    Ok(())
+}
+
+/// Like update_stat_block_values, but leaves recording the revision to the caller
+/// Returns the stat block JSON from before
+pub fn set_stat_block_values(
+    character: &mut Character,
+    values: &[(&str, serde_json::Value)],
+) -> Result<Option<String>, Error> {
+    let mut stat_block: serde_json::Value = serde_json::from_str(
+        character
+            .stat_block
+            .as_ref()
+            .ok_or(RpgError::NoCharacterSheetData)?,
+    )?;
+
+    let object = stat_block
+        .as_object_mut()
+        .ok_or(RpgError::NoCharacterSheetData)?;
+
+    for (key, value) in values {
+        object.insert(key.to_string(), value.clone());
+    }
+
+    Ok(character.stat_block.replace(serde_json::to_string(&stat_block)?))
+}
+
+/// Parses stats written like "str 12, dex 14" or "str: 12, dex: -1"
+pub fn parse_stat_list(stats: &str) -> Result<serde_json::Map<String, serde_json::Value>, Error> {
+    let re = Regex::new(r"^\s*([A-Za-z_][\w ]*?)\s*:?\s*([+-]?\d+)\s*$").unwrap();
+
+    let mut map = serde_json::Map::new();
+
+    for stat in stats.split(',').filter(|stat| !stat.trim().is_empty()) {
+        let captures = re.captures(stat).ok_or(RpgError::InvalidStatList)?;
+
+        map.insert(
+            captures[1].to_lowercase(),
+            serde_json::Value::from(captures[2].trim_start_matches('+').parse::<i64>()?),
+        );
+    }
+
+    Ok(map)
+}
+
 impl CharacterSheetable for StatBlock {
     fn new() -> Self {
         return Self {
@@ -71,6 +154,9 @@ impl CharacterSheetable for StatBlock {
 
             hunger: None,
 
+            actions: None,
+            reactions: None,
+
             default_roll: None,
             modifier_formula: None,
         };
@@ -115,12 +201,14 @@ impl CharacterSheetable for StatBlock {
 
         self.hunger = deserialized_message.get("hunger").and_then(|v| v.as_i64());
 
+        self.actions = deserialized_message.get("actions").and_then(|v| v.as_i64());
+        self.reactions = deserialized_message.get("reactions").and_then(|v| v.as_i64());
+
         self.default_roll = deserialized_message
             .get("default_roll")
             .and_then(|v| v.as_str())
             .filter(|s| !s.is_empty())
-            .map(|s| s.to_string())
-            .or(Some("1d100".to_string()));
+            .map(|s| s.to_string());
 
         self.modifier_formula = deserialized_message
             .get("modifier_formula")
@@ -213,9 +301,14 @@ impl CharacterSheetable for StatBlock {
 
             "energy_pool": (number),            
     
-            "energy_die_per_level": (number)d(number),
-            "magic_die_per_level": (number)d(number),
-            "training_die_per_level": (number)d(number),
+            "level_up_rolls": [
+                {
+                    "name": (string),
+                    "emoji": (string),
+                    "expression": (number)d(number),
+                    "target": (string)
+                }
+            ],
 
             "special_stats": {
             },
@@ -236,6 +329,11 @@ impl CharacterSheetable for StatBlock {
             }
         }
 
+        level_up_rolls should contain one entry for each die the sheet rolls per level (e.g. "Energy Die Per Lev : 1d4")
+        the name should be the label without "die per level", the emoji should be a single emoji that suits the name
+        target is the key the result is added to: "energy_pool" for energy, otherwise "special_stats." followed by the name in lowercase with underscores and "_points" appended
+        if the sheet has no per level dice, set level_up_rolls to null
+
         every value that is a number on the character sheet should be put in special_stats
         the key should be the label in the message, except lowercase with underscores instead of spaces and special characters stripped
             
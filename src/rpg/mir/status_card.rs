@@ -0,0 +1,440 @@
+use ab_glyph::Font;
+use ab_glyph::FontRef;
+use ab_glyph::PxScale;
+use ab_glyph::ScaleFont;
+use poise::serenity_prelude::CreateAttachment;
+use poise::serenity_prelude::Message;
+use serde_json::Value;
+use tiny_skia::Color;
+use tiny_skia::FillRule;
+use tiny_skia::Paint;
+use tiny_skia::PathBuilder;
+use tiny_skia::Pixmap;
+use tiny_skia::Transform;
+
+use crate::common::Error;
+use crate::db;
+use crate::db::models::Character;
+
+use super::super::get_sheet;
+use super::super::RpgError;
+use super::actions;
+use super::color_matcher;
+use super::conditions;
+use super::stat_block::StatBlock;
+use super::ACTIVE_SPELLS;
+
+pub static STATUS_CARD_FILENAME: &str = "status_card.png";
+
+static REGULAR_FONT: &[u8] = include_bytes!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/resources/fonts/DejaVuSans.ttf"
+));
+static BOLD_FONT: &[u8] = include_bytes!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/resources/fonts/DejaVuSans-Bold.ttf"
+));
+
+static CARD_WIDTH: u32 = 640;
+static PADDING: f32 = 24.0;
+static BAR_HEIGHT: f32 = 22.0;
+static STATS_PER_ROW: usize = 6;
+
+static BACKGROUND: (u8, u8, u8) = (43, 45, 49);
+static PANEL: (u8, u8, u8) = (30, 31, 34);
+static TEXT: (u8, u8, u8) = (242, 243, 245);
+static MUTED_TEXT: (u8, u8, u8) = (181, 186, 193);
+static DEFAULT_BAR: (u8, u8, u8) = (88, 101, 242);
+
+pub struct CardBar {
+    pub name: String,
+    pub value: i32,
+    pub max: i32,
+    pub colour: (u8, u8, u8),
+}
+
+/// Everything shown on a status card
+pub struct StatusCard {
+    pub name: String,
+    pub bars: Vec<CardBar>,
+    pub stats: Vec<(String, f64)>,
+    /// Actions and reactions left this turn
+    pub turn: Vec<String>,
+    pub active_spells: Vec<String>,
+    pub conditions: Vec<String>,
+}
+
+struct Fonts<'a> {
+    regular: FontRef<'a>,
+    bold: FontRef<'a>,
+}
+
+fn colour(rgb: (u8, u8, u8)) -> Color {
+    Color::from_rgba8(rgb.0, rgb.1, rgb.2, 255)
+}
+
+fn fill_rounded_rect(
+    pixmap: &mut Pixmap,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    rgb: (u8, u8, u8),
+) {
+    if width <= 0.0 || height <= 0.0 {
+        return;
+    }
+
+    let radius = (height / 2.0).min(width / 2.0).min(8.0);
+    let (right, bottom) = (x + width, y + height);
+
+    let mut builder = PathBuilder::new();
+    builder.move_to(x + radius, y);
+    builder.line_to(right - radius, y);
+    builder.quad_to(right, y, right, y + radius);
+    builder.line_to(right, bottom - radius);
+    builder.quad_to(right, bottom, right - radius, bottom);
+    builder.line_to(x + radius, bottom);
+    builder.quad_to(x, bottom, x, bottom - radius);
+    builder.line_to(x, y + radius);
+    builder.quad_to(x, y, x + radius, y);
+    builder.close();
+
+    let Some(path) = builder.finish() else {
+        return;
+    };
+
+    let mut paint = Paint::default();
+    paint.set_color(colour(rgb));
+    paint.anti_alias = true;
+
+    pixmap.fill_path(
+        &path,
+        &paint,
+        FillRule::Winding,
+        Transform::identity(),
+        None,
+    );
+}
+
+fn text_width(font: &FontRef, size: f32, text: &str) -> f32 {
+    let scaled = font.as_scaled(PxScale::from(size));
+
+    text.chars()
+        .map(|c| scaled.h_advance(scaled.glyph_id(c)))
+        .sum()
+}
+
+/// Draws text with its baseline at `y`, blending each glyph's coverage onto the pixmap
+fn draw_text(
+    pixmap: &mut Pixmap,
+    font: &FontRef,
+    size: f32,
+    x: f32,
+    y: f32,
+    text: &str,
+    rgb: (u8, u8, u8),
+) {
+    let scale = PxScale::from(size);
+    let scaled = font.as_scaled(scale);
+    let (width, height) = (pixmap.width() as i32, pixmap.height() as i32);
+    let pixels = pixmap.pixels_mut();
+
+    let mut caret = x;
+    let mut previous = None;
+
+    for c in text.chars() {
+        let glyph_id = scaled.glyph_id(c);
+
+        if let Some(previous) = previous {
+            caret += scaled.kern(previous, glyph_id);
+        }
+        previous = Some(glyph_id);
+
+        let glyph = glyph_id.with_scale_and_position(scale, ab_glyph::point(caret, y));
+        caret += scaled.h_advance(glyph_id);
+
+        let Some(outlined) = font.outline_glyph(glyph) else {
+            continue;
+        };
+
+        let bounds = outlined.px_bounds();
+
+        outlined.draw(|gx, gy, coverage| {
+            let px = bounds.min.x as i32 + gx as i32;
+            let py = bounds.min.y as i32 + gy as i32;
+
+            if px < 0 || py < 0 || px >= width || py >= height {
+                return;
+            }
+
+            let pixel = &mut pixels[(py * width + px) as usize];
+            let coverage = coverage.clamp(0.0, 1.0);
+            let blend = |source: u8, destination: u8| {
+                (source as f32 * coverage + destination as f32 * (1.0 - coverage)).round() as u8
+            };
+
+            // The card is opaque, so premultiplied and straight colours are the same here
+            if let Some(blended) = tiny_skia::PremultipliedColorU8::from_rgba(
+                blend(rgb.0, pixel.red()),
+                blend(rgb.1, pixel.green()),
+                blend(rgb.2, pixel.blue()),
+                255,
+            ) {
+                *pixel = blended;
+            }
+        });
+    }
+}
+
+fn truncate_to_width(font: &FontRef, size: f32, text: &str, max_width: f32) -> String {
+    if text_width(font, size, text) <= max_width {
+        return text.to_string();
+    }
+
+    let mut truncated = text.to_string();
+
+    while !truncated.is_empty() && text_width(font, size, &format!("{truncated}...")) > max_width {
+        truncated.pop();
+    }
+
+    format!("{truncated}...")
+}
+
+fn format_stat(value: f64) -> String {
+    value.floor().to_string()
+}
+
+/// Renders a status card as a PNG
+pub fn render(card: &StatusCard) -> Result<Vec<u8>, Error> {
+    let fonts = Fonts {
+        regular: FontRef::try_from_slice(REGULAR_FONT)?,
+        bold: FontRef::try_from_slice(BOLD_FONT)?,
+    };
+
+    let inner_width = CARD_WIDTH as f32 - PADDING * 2.0;
+    let stat_rows = card.stats.len().div_ceil(STATS_PER_ROW);
+    let list_lines = |items: &Vec<String>| if items.is_empty() { 0 } else { items.len() + 1 };
+
+    let height = PADDING * 2.0
+        + 40.0
+        + card.bars.len() as f32 * (BAR_HEIGHT + 34.0)
+        + stat_rows as f32 * 62.0
+        + (list_lines(&card.turn) + list_lines(&card.active_spells) + list_lines(&card.conditions))
+            as f32
+            * 26.0
+        + 24.0;
+
+    let mut pixmap =
+        Pixmap::new(CARD_WIDTH, height.ceil() as u32).ok_or(RpgError::StatusCardFailed)?;
+    pixmap.fill(colour(BACKGROUND));
+
+    let mut y = PADDING + 30.0;
+
+    let name = truncate_to_width(&fonts.bold, 30.0, &card.name, inner_width);
+    draw_text(&mut pixmap, &fonts.bold, 30.0, PADDING, y, &name, TEXT);
+    y += 24.0;
+
+    for bar in &card.bars {
+        y += 22.0;
+
+        let amount = format!("{} / {}", bar.value, bar.max);
+        let amount_width = text_width(&fonts.regular, 17.0, &amount);
+        let label = truncate_to_width(
+            &fonts.bold,
+            17.0,
+            &bar.name,
+            inner_width - amount_width - 12.0,
+        );
+
+        draw_text(&mut pixmap, &fonts.bold, 17.0, PADDING, y, &label, TEXT);
+        draw_text(
+            &mut pixmap,
+            &fonts.regular,
+            17.0,
+            PADDING + inner_width - amount_width,
+            y,
+            &amount,
+            MUTED_TEXT,
+        );
+        y += 8.0;
+
+        let fraction = if bar.max > 0 {
+            (bar.value as f32 / bar.max as f32).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        fill_rounded_rect(&mut pixmap, PADDING, y, inner_width, BAR_HEIGHT, PANEL);
+        fill_rounded_rect(
+            &mut pixmap,
+            PADDING,
+            y,
+            inner_width * fraction,
+            BAR_HEIGHT,
+            bar.colour,
+        );
+        y += BAR_HEIGHT + 4.0;
+    }
+
+    if !card.stats.is_empty() {
+        y += 16.0;
+
+        let gap = 8.0;
+        let box_width = (inner_width - gap * (STATS_PER_ROW - 1) as f32) / STATS_PER_ROW as f32;
+
+        for (i, (name, value)) in card.stats.iter().enumerate() {
+            let column = (i % STATS_PER_ROW) as f32;
+            let row = (i / STATS_PER_ROW) as f32;
+            let x = PADDING + column * (box_width + gap);
+            let top = y + row * 62.0;
+
+            fill_rounded_rect(&mut pixmap, x, top, box_width, 54.0, PANEL);
+
+            let label =
+                truncate_to_width(&fonts.regular, 13.0, &name.to_uppercase(), box_width - 8.0);
+            let label_width = text_width(&fonts.regular, 13.0, &label);
+            draw_text(
+                &mut pixmap,
+                &fonts.regular,
+                13.0,
+                x + (box_width - label_width) / 2.0,
+                top + 19.0,
+                &label,
+                MUTED_TEXT,
+            );
+
+            let value = format_stat(*value);
+            let value_width = text_width(&fonts.bold, 20.0, &value);
+            draw_text(
+                &mut pixmap,
+                &fonts.bold,
+                20.0,
+                x + (box_width - value_width) / 2.0,
+                top + 44.0,
+                &value,
+                TEXT,
+            );
+        }
+
+        y += stat_rows as f32 * 62.0;
+    }
+
+    for (heading, items) in [
+        ("This turn", &card.turn),
+        ("Active spells", &card.active_spells),
+        ("Conditions", &card.conditions),
+    ] {
+        if items.is_empty() {
+            continue;
+        }
+
+        y += 26.0;
+        draw_text(&mut pixmap, &fonts.bold, 17.0, PADDING, y, heading, TEXT);
+
+        for item in items {
+            y += 26.0;
+            let item = truncate_to_width(&fonts.regular, 16.0, &format!("• {item}"), inner_width);
+            draw_text(
+                &mut pixmap,
+                &fonts.regular,
+                16.0,
+                PADDING,
+                y,
+                &item,
+                MUTED_TEXT,
+            );
+        }
+    }
+
+    Ok(pixmap.encode_png()?)
+}
+
+/// Gathers a character's gauges, stats, actions, active spells and conditions into a card
+async fn card_for(
+    ctx: &poise::serenity_prelude::Context,
+    character: &Character,
+) -> Result<StatusCard, Error> {
+    let stat_block: StatBlock = get_sheet(Some(ctx), character).await?;
+
+    let bars = db::gauges::get_for_character(character.id)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|gauge| CardBar {
+            colour: gauge
+                .colour
+                .as_deref()
+                .and_then(color_matcher::parse_rgb)
+                .unwrap_or(DEFAULT_BAR),
+            name: gauge.name,
+            value: gauge.value,
+            max: gauge.max,
+        })
+        .collect();
+
+    let turn = actions::economy_of(character.id, &stat_block)
+        .map(|economy| {
+            vec![
+                format!("Actions: {} / {}", economy.actions_left, economy.max_actions),
+                format!("Reactions: {} / {}", economy.reactions_left, economy.max_reactions),
+            ]
+        })
+        .unwrap_or_default();
+
+    let stats = match stat_block.stats {
+        Some(Value::Object(map)) => map
+            .into_iter()
+            .filter_map(|(key, value)| value.as_f64().map(|value| (key, value)))
+            .collect(),
+        _ => vec![],
+    };
+
+    let active_spells = ACTIVE_SPELLS
+        .lock()
+        .await
+        .get(&character.id)
+        .map(|spells| {
+            spells
+                .iter()
+                .map(|spell| {
+                    let name = spell.name.clone().unwrap_or("No spell name".to_string());
+
+                    match &spell.cost {
+                        Some(cost) => format!("{name}: {cost} per turn"),
+                        None => name,
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(StatusCard {
+        name: character.name.clone().unwrap_or("No name?".to_string()),
+        bars,
+        stats,
+        turn,
+        active_spells,
+        conditions: conditions::descriptions(character.id),
+    })
+}
+
+/// Renders a character's status card, ready to attach to their status embed
+pub async fn attachment_for(
+    ctx: &poise::serenity_prelude::Context,
+    character: &Character,
+) -> Result<CreateAttachment, Error> {
+    let card = card_for(ctx, character).await?;
+
+    Ok(CreateAttachment::bytes(
+        render(&card)?,
+        STATUS_CARD_FILENAME,
+    ))
+}
+
+/// Whether a status message is showing a card rather than emoji bars, so refreshes keep it that way
+pub fn is_card_message(message: &Message) -> bool {
+    message
+        .attachments
+        .iter()
+        .any(|attachment| attachment.filename == STATUS_CARD_FILENAME)
+}
@@ -0,0 +1,348 @@
+use poise::serenity_prelude::ButtonStyle;
+use poise::serenity_prelude::CreateActionRow;
+use poise::serenity_prelude::UserId;
+use poise::CreateReply;
+use serde_json::Value;
+
+use crate::common::Context;
+use crate::common::Error;
+use crate::db;
+use crate::db::models::Character;
+use crate::db::models::Gauge;
+use crate::db::models::NewLedgerEntry;
+use crate::db::models::NewUndoEntry;
+use crate::db::models::UndoEntry;
+use crate::db::undo::RevertOutcome;
+
+use super::super::get_sheet;
+use super::super::RpgError;
+use super::currency;
+use super::engine::ResourceChange;
+use super::event_handlers::UndoEvent;
+use super::event_handlers::UndoEventParams;
+use super::gauges::clamp_gauge_value;
+use super::revisions;
+use super::revisions::RevisionSource;
+use super::stat_block::set_stat_block_values;
+use super::stat_block::StatBlock;
+
+/// A resource that can be changed back
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resource {
+    Mana,
+    Hp,
+    Soul,
+    Gauge(uuid::Uuid),
+    Currency,
+}
+
+impl Resource {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Resource::Mana => "mana",
+            Resource::Hp => "hp",
+            Resource::Soul => "soul",
+            Resource::Gauge(_) => "gauge",
+            Resource::Currency => "currency",
+        }
+    }
+
+    fn of_entry(entry: &UndoEntry) -> Option<Self> {
+        match entry.resource.as_str() {
+            "mana" => Some(Resource::Mana),
+            "hp" => Some(Resource::Hp),
+            "soul" => Some(Resource::Soul),
+            "gauge" => entry.gauge_id.map(Resource::Gauge),
+            "currency" => Some(Resource::Currency),
+            _ => None,
+        }
+    }
+}
+
+/// One resource of one character going from one value to another
+pub struct Change {
+    pub character_id: i32,
+    pub resource: Resource,
+    pub old: i64,
+    pub new: i64,
+}
+
+impl Change {
+    pub fn new(character_id: i32, resource: Resource, old: impl Into<i64>, new: impl Into<i64>) -> Self {
+        Change {
+            character_id,
+            resource,
+            old: old.into(),
+            new: new.into(),
+        }
+    }
+}
+
+impl From<&ResourceChange> for Change {
+    fn from(change: &ResourceChange) -> Self {
+        Change::new(change.character_id, change.resource, change.old, change.new)
+    }
+}
+
+/// Records changes made together so they can be undone as one
+/// Returns None if nothing actually changed
+pub fn record(actor: UserId, changes: &[Change]) -> Result<Option<uuid::Uuid>, Error> {
+    let batch = uuid::Uuid::new_v4();
+
+    let entries: Vec<NewUndoEntry> = changes
+        .iter()
+        .filter(|change| change.old != change.new)
+        .map(|change| NewUndoEntry {
+            batch_id: batch,
+            character_id: change.character_id,
+            campaign_id: db::characters::get(change.character_id)
+                .ok()
+                .and_then(|character| character.campaign_id),
+            actor_id: actor.to_string(),
+            resource: change.resource.as_str().to_string(),
+            gauge_id: match change.resource {
+                Resource::Gauge(gauge_id) => Some(gauge_id),
+                _ => None,
+            },
+            old_value: change.old,
+            new_value: change.new,
+        })
+        .collect();
+
+    if entries.is_empty() {
+        return Ok(None);
+    }
+
+    db::undo::create(&entries)?;
+
+    Ok(Some(batch))
+}
+
+/// The ↩️ button for a confirmation message - nothing if there's nothing to undo
+pub fn undo_row(batch: Option<uuid::Uuid>) -> Result<Vec<CreateActionRow>, Error> {
+    let Some(batch) = batch else {
+        return Ok(vec![]);
+    };
+
+    Ok(vec![CreateActionRow::Buttons(vec![UndoEvent::create_button(
+        "↩️",
+        &UndoEventParams {
+            batch_id: batch.to_string(),
+        },
+        ButtonStyle::Secondary,
+    )?])])
+}
+
+/// Whoever made a change can undo it, as can the DM of the campaign it was made in
+fn can_undo(entries: &[UndoEntry], user: UserId) -> bool {
+    let user = user.to_string();
+
+    entries.iter().all(|entry| {
+        entry.actor_id == user
+            || entry
+                .campaign_id
+                .and_then(|campaign_id| db::campaigns::get(campaign_id).ok())
+                .is_some_and(|campaign| campaign.dungeon_master_id.as_deref() == Some(user.as_str()))
+    })
+}
+
+/// Everything reverting a batch changes, kept until it can all be saved together
+#[derive(Default)]
+struct Reversal {
+    /// Each character as it was, and with the batch reverted
+    characters: Vec<(Character, Character)>,
+    gauges: Vec<Gauge>,
+    ledger_entries: Vec<NewLedgerEntry>,
+}
+
+impl Reversal {
+    fn character(&mut self, character_id: i32) -> Result<&mut Character, Error> {
+        let index = match self
+            .characters
+            .iter()
+            .position(|(character, _)| character.id == character_id)
+        {
+            Some(index) => index,
+            None => {
+                let character = db::characters::get(character_id)?;
+                self.characters.push((character.clone(), character));
+                self.characters.len() - 1
+            }
+        };
+
+        Ok(&mut self.characters[index].1)
+    }
+
+    fn gauge(&mut self, gauge_id: uuid::Uuid) -> Result<&mut Gauge, Error> {
+        let index = match self.gauges.iter().position(|gauge| gauge.id == gauge_id) {
+            Some(index) => index,
+            None => {
+                let gauge = db::gauges::get(gauge_id).map_err(|_| RpgError::GaugeNotFound)?;
+                self.gauges.push(gauge);
+                self.gauges.len() - 1
+            }
+        };
+
+        Ok(&mut self.gauges[index])
+    }
+}
+
+/// Works out one change in reverse, so anything changed since is kept
+async fn revert(
+    ctx: &poise::serenity_prelude::Context,
+    entry: &UndoEntry,
+    resource: Resource,
+    user: UserId,
+    reversal: &mut Reversal,
+) -> Result<String, Error> {
+    let character = reversal.character(entry.character_id)?;
+    let name = character.name.clone().unwrap_or("No name?".to_string());
+
+    let delta = entry.old_value - entry.new_value;
+
+    let line = match resource {
+        Resource::Mana => {
+            let old = character.mana.map_or(entry.new_value, i64::from);
+            let new = old + delta;
+
+            character.mana = Some(new as i32);
+
+            format!("{name}: Mana {old} → {new}")
+        }
+        Resource::Hp | Resource::Soul => {
+            let stat_block: StatBlock = get_sheet(Some(ctx), character).await?;
+
+            let (label, key, current) = if resource == Resource::Hp {
+                ("HP", "current_hp", stat_block.hp)
+            } else {
+                ("Soul", "current_soul", stat_block.soul)
+            };
+
+            let old = current.unwrap_or(entry.new_value);
+            let new = old + delta;
+
+            set_stat_block_values(
+                reversal.character(entry.character_id)?,
+                &[(key, Value::from(new))],
+            )?;
+
+            format!("{name}: {label} {old} → {new}")
+        }
+        Resource::Gauge(gauge_id) => {
+            let gauge = reversal.gauge(gauge_id)?;
+
+            let old = gauge.value;
+
+            gauge.value = clamp_gauge_value(gauge, old + delta as i32);
+
+            format!("{name}: {} {old} → {}", gauge.name, gauge.value)
+        }
+        Resource::Currency => {
+            let campaign_id = character.campaign_id;
+
+            reversal.ledger_entries.push(NewLedgerEntry {
+                character_id: entry.character_id,
+                campaign_id,
+                amount: delta,
+                kind: "undo".to_string(),
+                reason: None,
+                counterparty_id: None,
+                author_id: user.to_string(),
+            });
+
+            let denominations = currency::denominations_for_campaign(campaign_id);
+
+            format!(
+                "{name}: {}{}",
+                if delta < 0 { "-" } else { "+" },
+                currency::format_amount(delta.abs(), &denominations)
+            )
+        }
+    };
+
+    Ok(line)
+}
+
+/// Reverts every change in a batch, newest first, returning a line describing each
+/// Nothing is saved unless the whole batch can be reverted
+pub async fn undo_batch(
+    ctx: &poise::serenity_prelude::Context,
+    batch: uuid::Uuid,
+    user: UserId,
+) -> Result<Vec<String>, Error> {
+    let entries = db::undo::get_batch(batch)?;
+
+    if entries.is_empty() {
+        return Err(Box::new(RpgError::NothingToUndo));
+    }
+
+    if !can_undo(&entries, user) {
+        return Err(Box::new(RpgError::CannotUndo));
+    }
+
+    let mut reversal = Reversal::default();
+    let mut lines = vec![];
+
+    for entry in entries.iter().rev() {
+        let Some(resource) = Resource::of_entry(entry) else {
+            println!("Unknown undo resource {}", entry.resource);
+            continue;
+        };
+
+        lines.push(revert(ctx, entry, resource, user, &mut reversal).await?);
+    }
+
+    let reverted: Vec<Character> = reversal
+        .characters
+        .iter()
+        .map(|(_, reverted)| reverted.clone())
+        .collect();
+
+    match db::undo::revert(batch, &reverted, &reversal.gauges, &reversal.ledger_entries)? {
+        RevertOutcome::Reverted => {}
+        RevertOutcome::AlreadyUndone => return Err(Box::new(RpgError::AlreadyUndone)),
+        RevertOutcome::InsufficientFunds => return Err(Box::new(RpgError::InsufficientFunds)),
+    }
+
+    for (before, after) in &reversal.characters {
+        revisions::record_changes(before, after, Some(user.get()), RevisionSource::Undo)?;
+    }
+
+    Ok(lines)
+}
+
+pub fn describe(lines: &[String]) -> String {
+    format!(
+        "↩️ Undone\n{}",
+        lines
+            .iter()
+            .map(|line| format!("- {line}"))
+            .collect::<Vec<String>>()
+            .join("\n")
+    )
+}
+
+/// Undo your last change to mana, HP, gauges or money - DMs can undo any change in their campaign
+#[poise::command(slash_command, prefix_command)]
+pub async fn undo(
+    ctx: Context<'_>,
+    #[description = "Undo the last change to this character instead (DM only)"]
+    character_id: Option<i32>,
+) -> Result<(), Error> {
+    let placeholder = CreateReply::default().content("*Thinking, please wait...*");
+    let placeholder_message = ctx.send(placeholder).await?;
+
+    let batch = match character_id {
+        Some(character_id) => db::undo::latest_batch_for_character(character_id)?,
+        None => db::undo::latest_batch_by_actor(&ctx.author().id.to_string())?,
+    }
+    .ok_or(RpgError::NothingToUndo)?;
+
+    let lines = undo_batch(ctx.serenity_context(), batch, ctx.author().id).await?;
+
+    placeholder_message
+        .edit(ctx, CreateReply::default().content(describe(&lines)))
+        .await?;
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use crate::dice::RollResult;
+use super::roll_mode::RollMode;
 use axum::{extract::Path, http::StatusCode, Json};
 
 pub async fn roll_for_internal(
@@ -11,7 +12,7 @@ pub async fn roll_for_internal(
     );
 
     if let Ok(char) = crate::db::characters::get(char_id) {
-        match super::roll_with_char_sheet(None, roll_expression, &char).await {
+        match super::roll_with_char_sheet(None, roll_expression, &char, RollMode::Normal).await {
             Ok(res) => Ok(Json(res)),
             Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
         }
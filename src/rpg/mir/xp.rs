@@ -0,0 +1,286 @@
+use chrono::Utc;
+use poise::serenity_prelude::ButtonStyle;
+use poise::serenity_prelude::CreateActionRow;
+use poise::CreateReply;
+use serde_json::Value;
+
+use crate::common::Context;
+use crate::common::Error;
+use crate::db;
+use crate::db::models::Character;
+use crate::db::models::NewXpAward;
+
+use super::super::get_sheet;
+use super::super::RpgError;
+use super::campaigns;
+use super::event_handlers::LevelUpEvent;
+use super::event_handlers::LevelUpEventParams;
+use super::ownership::get_managed_character;
+use super::sessions;
+use super::stat_block::StatBlock;
+
+/// XP needed to reach each level, for game systems that have a standard table
+static GAME_SYSTEM_THRESHOLDS: [(&str, &str); 1] = [(
+    "5e",
+    "0, 300, 900, 2700, 6500, 14000, 23000, 34000, 48000, 64000, 85000, 100000, 120000, 140000, 165000, 195000, 225000, 265000, 305000, 355000",
+)];
+
+/// Parses the XP needed for level 1, 2, 3... written like "0, 300, 900"
+pub fn parse_thresholds(thresholds: &str) -> Result<Vec<i64>, Error> {
+    let parsed = thresholds
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|threshold| !threshold.is_empty())
+        .map(|threshold| threshold.parse::<i64>())
+        .collect::<Result<Vec<i64>, _>>()
+        .map_err(|_| RpgError::InvalidXpThresholds)?;
+
+    let ascending = parsed.windows(2).all(|pair| pair[0] < pair[1]);
+
+    if parsed.is_empty() || !ascending || parsed[0] < 0 {
+        return Err(Box::new(RpgError::InvalidXpThresholds));
+    }
+
+    Ok(parsed)
+}
+
+/// The level a character with this much XP should be
+pub fn level_for_xp(xp: i64, thresholds: &[i64]) -> i64 {
+    thresholds.iter().filter(|threshold| **threshold <= xp).count() as i64
+}
+
+/// The campaign's XP thresholds, or the standard table for its game system
+pub fn thresholds_for(character: &Character, stat_block: Option<&StatBlock>) -> Option<Vec<i64>> {
+    let campaign_thresholds = character
+        .campaign_id
+        .and_then(|campaign_id| db::campaigns::get(campaign_id).ok())
+        .and_then(|campaign| campaign.xp_thresholds);
+
+    if let Some(thresholds) = campaign_thresholds {
+        return parse_thresholds(&thresholds).ok();
+    }
+
+    let game_system = campaigns::game_system_for(character, stat_block);
+
+    GAME_SYSTEM_THRESHOLDS
+        .iter()
+        .find(|(system, _)| *system == game_system)
+        .and_then(|(_, thresholds)| parse_thresholds(thresholds).ok())
+}
+
+/// Where a character is with their XP
+pub struct XpProgress {
+    pub xp: i64,
+    pub level: i64,
+    pub thresholds: Option<Vec<i64>>,
+}
+
+impl XpProgress {
+    /// How many levels the character's XP entitles them to that they haven't taken yet
+    pub fn pending_levels(&self) -> i64 {
+        self.thresholds
+            .as_ref()
+            .map(|thresholds| (level_for_xp(self.xp, thresholds) - self.level).max(0))
+            .unwrap_or(0)
+    }
+
+    pub fn next_threshold(&self) -> Option<i64> {
+        self.thresholds
+            .as_ref()
+            .and_then(|thresholds| thresholds.iter().find(|threshold| **threshold > self.xp))
+            .copied()
+    }
+}
+
+pub async fn progress_of(
+    ctx: &poise::serenity_prelude::Context,
+    character: &Character,
+) -> Result<XpProgress, Error> {
+    let stat_block: Option<StatBlock> = get_sheet(Some(ctx), character).await.ok();
+
+    let level = stat_block
+        .as_ref()
+        .and_then(|stat_block| stat_block.sheet_info.jsonified_message.as_ref())
+        .and_then(|json| serde_json::from_str::<Value>(json).ok())
+        .and_then(|stats| stats.get("level").and_then(|level| level.as_i64()))
+        .unwrap_or(0);
+
+    Ok(XpProgress {
+        xp: db::xp::total(character.id)?,
+        level,
+        thresholds: thresholds_for(character, stat_block.as_ref()),
+    })
+}
+
+fn character_name(character: &Character) -> String {
+    character.name.clone().unwrap_or("No name?".to_string())
+}
+
+/// Asks a character's owner to level up if their XP has crossed a threshold
+async fn prompt_level_up(ctx: &Context<'_>, character: &Character) -> Result<(), Error> {
+    let progress = progress_of(ctx.serenity_context(), character).await?;
+    let pending = progress.pending_levels();
+
+    if pending == 0 {
+        return Ok(());
+    }
+
+    let owner = character
+        .user_id
+        .as_ref()
+        .map(|id| format!("<@{id}>, "))
+        .unwrap_or_default();
+
+    let button = LevelUpEvent::create_button(
+        &format!(
+            "⬆️ Level up ({} level{})",
+            pending,
+            if pending == 1 { "" } else { "s" }
+        ),
+        &LevelUpEventParams {
+            character_id: character.id,
+        },
+        ButtonStyle::Success,
+    )?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "{owner}{} has {} XP and can reach level {}!",
+                character_name(character),
+                progress.xp,
+                progress.level + pending
+            ))
+            .components(vec![CreateActionRow::Buttons(vec![button])]),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands("award", "show"),
+    subcommand_required
+)]
+pub async fn xp(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Award XP to a character in your campaign, or to everyone in it (DM only)
+#[poise::command(slash_command, prefix_command)]
+pub async fn award(
+    ctx: Context<'_>,
+    amount: i32,
+    #[description = "Defaults to your selected character"] character_id: Option<i32>,
+    #[description = "Award it to every character in your campaign (DM only)"] party: Option<bool>,
+    #[description = "Award it to the characters of everyone who attended the last session (DM only)"]
+    attended: Option<bool>,
+    reason: Option<String>,
+    #[description = "Only needed if you DM more than one campaign"] campaign_id: Option<i32>,
+) -> Result<(), Error> {
+    let characters = if party.unwrap_or(false) {
+        let campaign = campaigns::get_dm_campaign(&ctx, campaign_id).await?;
+        let characters = db::characters::get_by_campaign(campaign.id)?;
+
+        if characters.is_empty() {
+            return Err(Box::new(RpgError::NoCampaignCharacters));
+        }
+
+        characters
+    } else if attended.unwrap_or(false) {
+        let campaign = campaigns::get_dm_campaign(&ctx, campaign_id).await?;
+        let session = db::sessions::get_latest_started(campaign.id, Utc::now())
+            .map_err(|_| RpgError::SessionNotFound)?;
+        let attendees = sessions::attendees(session.id)?;
+
+        let mut characters = vec![];
+
+        for character in db::characters::get_by_campaign(campaign.id)? {
+            let mut attended = false;
+
+            for user_id in &attendees {
+                attended |= db::character_owners::is_owner(&character, *user_id)?;
+            }
+
+            if attended {
+                characters.push(character);
+            }
+        }
+
+        if characters.is_empty() {
+            return Err(Box::new(RpgError::NoCampaignCharacters));
+        }
+
+        characters
+    } else {
+        let character = get_managed_character(&ctx, character_id).await?;
+
+        // Only the DM hands out XP, even to a single character
+        campaigns::get_dm_campaign(
+            &ctx,
+            Some(character.campaign_id.ok_or(RpgError::NotDungeonMaster)?),
+        )
+        .await?;
+
+        vec![character]
+    };
+
+    for character in &characters {
+        db::xp::award(&NewXpAward {
+            character_id: character.id,
+            amount,
+            reason: reason.clone(),
+            author_id: ctx.author().id.to_string(),
+        })?;
+    }
+
+    let names = characters
+        .iter()
+        .map(character_name)
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    ctx.send(CreateReply::default().content(format!("Awarded {amount} XP to {names}")))
+        .await?;
+
+    for character in &characters {
+        prompt_level_up(&ctx, character).await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command)]
+pub async fn show(
+    ctx: Context<'_>,
+    #[description = "Defaults to your selected character"] character_id: Option<i32>,
+) -> Result<(), Error> {
+    let character = get_managed_character(&ctx, character_id).await?;
+
+    let progress = progress_of(ctx.serenity_context(), &character).await?;
+
+    let mut content = format!(
+        "**{}**: {} XP (level {})",
+        character_name(&character),
+        progress.xp,
+        progress.level
+    );
+
+    match (&progress.thresholds, progress.next_threshold()) {
+        (None, _) => content += "\nNo XP thresholds set - set them with /campaign settings",
+        (Some(_), Some(next)) => {
+            content += &format!("\n{} XP until the next level", next - progress.xp)
+        }
+        (Some(_), None) => content += "\nMax level reached",
+    }
+
+    ctx.send(CreateReply::default().content(content).ephemeral(true))
+        .await?;
+
+    if progress.pending_levels() > 0 {
+        prompt_level_up(&ctx, &character).await?;
+    }
+
+    Ok(())
+}
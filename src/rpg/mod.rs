@@ -50,13 +50,74 @@ pub enum RpgError {
     NoSpellSheet,
     NoSpellSheetData,
     SpellNotFound,
+    SpellListChanged,
     NoSpellCost,
     NoMaxEnergy,
     GaugeMessageMissing,
-
-    NoEnergyDie,
-    NoMagicDie,
-    NoTrainingDie,
+    GaugeNotFound,
+    GaugeAlreadyExists,
+    InvalidGaugeMax,
+
+    CampaignNotFound,
+    NotDungeonMaster,
+    AmbiguousCampaign,
+    CampaignArchived,
+    DashboardExpired,
+
+    NoLevelUpRolls,
+    InvalidLevelUpRolls,
+    NoLevelUpToUndo,
+    InvalidLevelCount,
+    StatBlockChangedSinceLevelUp,
+    InvalidLevelUpTarget,
+    InvalidXpThresholds,
+
+    NotCharacterOwner,
+    AlreadyCharacterOwner,
+
+    InvalidCharacterExport,
+    UnsupportedExportVersion,
+
+    RevisionNotFound,
+    BlockTooLongToEdit,
+    InvalidStatList,
+
+    ItemNotFound,
+    NotEnoughItems,
+    RecipientNotInCampaign,
+    NoCampaign,
+
+    InvalidCurrencyAmount,
+    InvalidDenominations,
+    InsufficientFunds,
+    RecipientHasNoCharacter,
+    CannotPaySelf,
+    NoCampaignCharacters,
+
+    ConditionNotFound,
+    InvalidConditionModifier,
+
+    NpcNotFound,
+    NpcAlreadyExists,
+    NotNpcTemplate,
+
+    SessionNotFound,
+    SessionCancelled,
+    InvalidSessionTime,
+    InvalidReminderOffsets,
+    SessionAlreadyEnded,
+    SessionNotEnded,
+
+    StatusCardFailed,
+
+    StatNotFound,
+
+    NotEnoughActions,
+    NotEnoughReactions,
+
+    NothingToUndo,
+    AlreadyUndone,
+    CannotUndo,
 
     JsonNotInitialised,
     TestingError,
@@ -82,12 +143,60 @@ impl fmt::Display for RpgError {
                 write!(f, "No spell sheet data found. Either set Discord message IDs or populate the spell_block field in the database with JSON.")
             }
             RpgError::SpellNotFound => write!(f, "Spell not found"),
+            RpgError::SpellListChanged => write!(f, "The spell sheet has changed since this list was posted - list your spells again"),
             RpgError::NoSpellCost => write!(f, "Spell cost appears to be missing from your spell block"),
             RpgError::NoMaxEnergy => write!(f, "Energy pool appears to be missing from your stat block"),
             RpgError::GaugeMessageMissing => write!(f, "Gauge message is missing - was it deleted?"),
-            RpgError::NoEnergyDie => write!(f, "Energy die per level appears to be missing from your stat block"),
-            RpgError::NoMagicDie => write!(f, "Magic die per level appears to be missing from your stat block"),
-            RpgError::NoTrainingDie => write!(f, "Training die per level appears to be missing from your stat block"),
+            RpgError::GaugeNotFound => write!(f, "Gauge not found - list your gauges with /gauge list"),
+            RpgError::GaugeAlreadyExists => write!(f, "Your character already has a gauge with that name"),
+            RpgError::InvalidGaugeMax => write!(f, "A gauge's max must be greater than 0"),
+            RpgError::CampaignNotFound => write!(f, "Campaign not found"),
+            RpgError::NotDungeonMaster => write!(f, "Only the campaign's dungeon master can do that"),
+            RpgError::DashboardExpired => write!(f, "This dashboard has expired - run /party again"),
+            RpgError::CampaignArchived => write!(f, "That campaign has been archived"),
+            RpgError::AmbiguousCampaign => write!(f, "You are running more than one campaign - please specify a campaign id"),
+            RpgError::NoLevelUpRolls => write!(f, "No level up rolls found - add level_up_rolls to your stat block or campaign"),
+            RpgError::InvalidLevelUpRolls => write!(f, "Level up rolls should be a list of name, emoji, expression and target"),
+            RpgError::InvalidXpThresholds => write!(f, "XP thresholds should be the XP needed for each level in ascending order, e.g. 0, 300, 900"),
+            RpgError::NoLevelUpToUndo => write!(f, "There is no level up to undo"),
+            RpgError::InvalidLevelCount => write!(f, "You can level up between 1 and 20 levels at a time"),
+            RpgError::StatBlockChangedSinceLevelUp => write!(f, "Your stat block has changed since that level up, undoing it would lose those changes"),
+            RpgError::InvalidLevelUpTarget => write!(f, "A level up roll adds to something on your stat block that isn't a number"),
+            RpgError::NotCharacterOwner => write!(f, "That's not your character!"),
+            RpgError::AlreadyCharacterOwner => write!(f, "They already own that character"),
+            RpgError::InvalidCharacterExport => write!(f, "That file isn't a character export (make one with /export_character)"),
+            RpgError::RevisionNotFound => write!(f, "Revision not found - list them with /character_history"),
+            RpgError::BlockTooLongToEdit => write!(f, "That block is too long to edit in discord (the limit is 4000 characters)"),
+            RpgError::UnsupportedExportVersion => write!(f, "That character export was made with a different version of the bot and can't be imported"),
+            RpgError::ItemNotFound => write!(f, "Item not found - check your inventory with /inventory show"),
+            RpgError::NotEnoughItems => write!(f, "You don't have that many"),
+            RpgError::RecipientNotInCampaign => write!(f, "You can only give items or money to characters in your campaign"),
+            RpgError::NoCampaign => write!(f, "Your character isn't in a campaign - join one with /campaign join"),
+            RpgError::InvalidCurrencyAmount => write!(f, "Amounts should look like 30gp or 2gp 5sp, using your campaign's currencies"),
+            RpgError::InvalidDenominations => write!(f, "Currencies should look like \"gp 100, sp 10, cp 1\" (each coin and what it's worth in the smallest coin)"),
+            RpgError::InsufficientFunds => write!(f, "You can't afford that"),
+            RpgError::RecipientHasNoCharacter => write!(f, "They don't have a character selected"),
+            RpgError::CannotPaySelf => write!(f, "You can't pay your own character"),
+            RpgError::NoCampaignCharacters => write!(f, "No characters have joined this campaign yet"),
+            RpgError::ConditionNotFound => write!(f, "Condition not found - list them with /condition list"),
+            RpgError::InvalidConditionModifier => write!(f, "A condition's modifier should be a number or dice, e.g. -2 or +1d4"),
+            RpgError::NpcNotFound => write!(f, "NPC not found - list yours with /npc list"),
+            RpgError::NpcAlreadyExists => write!(f, "You already have an NPC with that name"),
+            RpgError::NotNpcTemplate => write!(f, "That NPC isn't a template - create one with /npc create template:True"),
+            RpgError::InvalidStatList => write!(f, "Stats should look like \"str 12, dex 14\""),
+            RpgError::SessionNotFound => write!(f, "Session not found - list them with /session list"),
+            RpgError::SessionCancelled => write!(f, "That session has been cancelled"),
+            RpgError::InvalidSessionTime => write!(f, "Sessions need a time in the future, e.g. 2026-10-25 19:00 or a discord timestamp"),
+            RpgError::InvalidReminderOffsets => write!(f, "Reminders should be how long before the session to send them, e.g. 24h, 1h, 30m"),
+            RpgError::SessionAlreadyEnded => write!(f, "That session has already ended - see its recap with /session recap"),
+            RpgError::SessionNotEnded => write!(f, "That session hasn't ended yet - end it with /session end"),
+            RpgError::StatusCardFailed => write!(f, "Failed to draw the status card"),
+            RpgError::StatNotFound => write!(f, "Stat not found on your stat block"),
+            RpgError::NotEnoughActions => write!(f, "Not enough actions left this turn - they come back with /end_turn"),
+            RpgError::NotEnoughReactions => write!(f, "No reactions left this turn - they come back with /end_turn"),
+            RpgError::NothingToUndo => write!(f, "There is nothing to undo"),
+            RpgError::AlreadyUndone => write!(f, "That change has already been undone"),
+            RpgError::CannotUndo => write!(f, "You can only undo your own changes, or changes in a campaign you DM"),
             RpgError::JsonNotInitialised => write!(f, "JSON is not initialised - this should never happen"),
             _ => write!(f,"Testing"),
         }
@@ -411,6 +520,13 @@ pub async fn get_sheet<T: CharacterSheetable + 'static>(ctx: Option<&poise::sere
             .expect("Tried to update a non existent character?!");
 
         let _ = db::characters::update(&new_char);
+
+        let _ = mir::revisions::record_changes(
+            character,
+            &new_char,
+            None,
+            mir::revisions::RevisionSource::Llm,
+        );
     }
 
     Ok(character_sheet.clone())
@@ -59,4 +59,12 @@ pub struct Spell<T: SpellResource> {
 
     #[allow(dead_code)] //cargo is lying
     pub cast_time: Option<String>,
+
+    /// Rolled against the targets when cast, e.g. "8d6" or "2d4+int"
+    pub damage: Option<String>,
+    pub healing: Option<String>,
+    /// Stat the targets roll to resist the spell
+    pub save_stat: Option<String>,
+    pub targets: Option<i32>,
+    pub description: Option<String>,
 }